@@ -1,6 +1,7 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	pallet_bo_trading::TradingPair, AccountId, AssetsConfig, AuraConfig, BalancesConfig,
+	BoLiquidityModuleConfig, BoTradingModuleConfig, GenesisConfig, GrandpaConfig, Signature,
+	SudoConfig, SymbolPriceModuleConfig, SystemConfig, USDT, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -8,6 +9,9 @@ use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
 
+/// 1 USDT, the asset has 6 decimals
+const UNIT: u128 = 1_000_000;
+
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
@@ -139,6 +143,36 @@ fn testnet_genesis(
 			// Configure endowed accounts with initial balance of 1 << 60.
 			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
 		},
+		assets: AssetsConfig {
+			// Dev USDT: sufficient, so pool and pallet accounts need no native balance to hold it
+			assets: vec![(USDT, root_key.clone(), true, 1)],
+			metadata: vec![(USDT, b"Tether USD".to_vec(), b"USDT".to_vec(), 6)],
+			// 1M USDT for each endowed account
+			accounts: endowed_accounts.iter().cloned().map(|k| (USDT, k, 1_000_000_000_000)).collect(),
+		},
+		bo_liquidity_module: BoLiquidityModuleConfig {
+			// 100k USDT pool paying 90%, funded out of the USDT of the sudo account
+			pools: vec![(root_key.clone(), b"Dev pool".to_vec(), 90, USDT, 100_000 * UNIT)],
+		},
+		symbol_price_module: SymbolPriceModuleConfig {
+			// the //Alice key inserted in the keystore by the service submits the prices
+			oracles: vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
+			// in cents, until the offchain worker fetches the first price
+			prices: vec![(b"BTC_USDT".to_vec(), 4_000_000)],
+		},
+		bo_trading_module: BoTradingModuleConfig {
+			pairs: vec![TradingPair {
+				base: b"BTC".to_vec(),
+				quote: b"USDT".to_vec(),
+				oracle_symbol: b"BTC_USDT".to_vec(),
+				asset_id: USDT,
+				// the runtime `DefaultTimeframes`
+				timeframes: Default::default(),
+				min_stake: UNIT,
+				max_stake: 10_000 * UNIT,
+				enabled: true,
+			}],
+		},
 		aura: AuraConfig {
 			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
 		},
@@ -1,20 +1,93 @@
-//! Benchmarking setup for pallet-template
+//! Benchmarking setup for pallet-bo-liquidity
 
 use super::*;
 
 #[allow(unused)]
-use crate::Pallet as Template;
-use frame_benchmarking::{benchmarks, whitelisted_caller};
+use crate::Pallet as BoLiquidity;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::traits::tokens::fungibles::{Create, Mutate};
 use frame_system::RawOrigin;
+use sp_runtime::traits::Saturating;
+
+const SEED: u32 = 0;
+/// Asset of the benchmarked pools
+const ASSET: u32 = 1;
+/// Asset of the pools created before the benchmarked call
+const OTHER_ASSET: u32 = 2;
+/// Funds of a pool, above the minimum of `create_lp`
+const POOL_FUNDS: u32 = 1_000_000;
+
+/// Give `who` enough of `asset_id` to fund a pool, the asset is created if missing
+fn fund<T: Config>(asset_id: u32, who: &T::AccountId) -> BalanceOf<T>
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	AssetIdOf<T>: From<u32>,
+{
+	// fails if the asset already exists, eg: in the genesis of the mock runtime
+	let _ = T::Assets::create(asset_id.into(), who.clone(), true, 1u32.into());
+	let amount: BalanceOf<T> = POOL_FUNDS.into();
+	T::Assets::mint_into(asset_id.into(), who, amount.saturating_add(amount))
+		.expect("the asset exists; qed");
+	amount
+}
+
+/// Create `count` pools of `asset_id`, each one with its own admin
+fn create_pools<T: Config>(asset_id: u32, count: u32)
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	AssetIdOf<T>: From<u32>,
+{
+	for i in 0..count {
+		let admin: T::AccountId = account("admin", i, SEED);
+		let amount = fund::<T>(asset_id, &admin);
+		BoLiquidity::<T>::create_lp(
+			RawOrigin::Signed(admin).into(),
+			b"pool".to_vec(),
+			90,
+			asset_id.into(),
+			amount,
+		)
+		.expect("below MaxPools; qed");
+	}
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	where_clause {
+		where
+			T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+			AssetIdOf<T>: From<u32>,
+	}
+
+	create_lp {
+		let p in 0 .. T::MaxPools::get() - 1;
+		create_pools::<T>(OTHER_ASSET, p);
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		let amount = fund::<T>(ASSET, &caller);
+	}: _(RawOrigin::Signed(caller.clone()), b"pool".to_vec(), 90, ASSET.into(), amount)
+	verify {
+		assert_eq!(LpCount::<T>::get(), p + 1);
+		assert_eq!(LiquidityPoolsOwned::<T>::get(&caller).len(), 1);
+	}
+
+	deposit_lp {
+		let caller: T::AccountId = whitelisted_caller();
+		create_pools::<T>(ASSET, 1);
+		let lp_id = BoLiquidity::<T>::sub_account_id(0);
+		let amount = fund::<T>(ASSET, &caller);
+	}: _(RawOrigin::Signed(caller), lp_id.clone(), amount)
+	verify {
+		let pool = LiquidityPools::<T>::get(&lp_id).unwrap();
+		assert_eq!(pool.amount, amount.saturating_add(amount));
+	}
+
+	update_lp {
+		let admin: T::AccountId = account("admin", 0, SEED);
+		create_pools::<T>(ASSET, 1);
+		let lp_id = BoLiquidity::<T>::sub_account_id(0);
+	}: _(RawOrigin::Signed(admin), lp_id.clone(), b"renamed".to_vec(), 80)
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert_eq!(LiquidityPools::<T>::get(&lp_id).unwrap().payout_rate, 80);
 	}
 
-	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
+	impl_benchmark_test_suite!(BoLiquidity, crate::mock::new_test_ext(), crate::mock::Test);
 }
@@ -11,8 +11,12 @@ mod mock;
 #[cfg(test)]
 mod tests;
 //
-// #[cfg(feature = "runtime-benchmarks")]
-// mod benchmarking;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -23,23 +27,24 @@ pub mod pallet {
 		sp_runtime::traits::Hash, // support T::Hashing
 		traits::{
 			Randomness,
-			Currency,
-			tokens::ExistenceRequirement,
+			tokens::fungibles::{Inspect, Transfer},
 		},
 		PalletId,
 	};
 
-	use sp_runtime::traits::AccountIdConversion;
+	use sp_runtime::traits::{AccountIdConversion, MaybeSerializeDeserialize, Saturating};
 
 	use scale_info::TypeInfo;
 	// use scale_info::prelude::string::String; // support String
 	use scale_info::prelude::vec::Vec;	// support Vec
+	use crate::weights::WeightInfo;
 
 	#[cfg(feature = "std")]
 	use frame_support::serde::{Deserialize, Serialize};
 
 	type AccountOf<T> = <T as frame_system::Config>::AccountId;
-	type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub(crate) type BalanceOf<T> = <<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+	pub(crate) type AssetIdOf<T> = <<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
@@ -47,19 +52,34 @@ pub mod pallet {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
-		/// The Currency handler for the BoTrading pallet.
-		type Currency: Currency<Self::AccountId>;
+		/// Fungible assets the pools are funded with, eg: USDT of pallet_assets
+		type Assets: Inspect<Self::AccountId> + Transfer<Self::AccountId>;
 
 		/// Use for create random data
 		type MyRandomness: Randomness<Self::Hash, Self::BlockNumber>;
 
 		#[pallet::constant]
         type PalletId: Get<PalletId>;
+
+		/// Maximum number of pools, picking a pool for an order may visit all of them
+		#[pallet::constant]
+		type MaxPools: Get<u32>;
+
+		/// Maximum number of points of a pricing curve
+		#[pallet::constant]
+		type MaxCurvePoints: Get<u32>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
 
+	/// The current storage version
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	///
@@ -87,12 +107,22 @@ pub mod pallet {
 	}
 
 
+	/// Side of an open order the LP is exposed to
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum PositionSide {
+		Call,
+		Put,
+	}
+
 	/// Struct for holding Liquidity information.
 	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
 	#[scale_info(skip_type_params(T))]
 	pub struct LiquidityPool<T: Config> {
 		pub id: T::AccountId,
 		pub name: Vec<u8>,
+		/// Asset the pool is funded with and pays out in
+		pub asset_id: AssetIdOf<T>,
 		pub amount: BalanceOf<T>,
 		pub payout_rate: u8,
 		pub admin: T::AccountId,
@@ -133,6 +163,67 @@ pub mod pallet {
 	#[pallet::getter(fn lp_random_index)]
 	pub(super) type LpRandomIndex<T: Config> = StorageValue<_, u32, ValueQuery>;
 
+	/// A point of a pool pricing curve: (strike distance, payout rate)
+	/// - strike distance: how far the strike is from the current price, in basis points
+	/// 	positive = out of the money, negative = in the money
+	/// - payout rate: percent of the stake paid as profit if the order win
+	pub type CurvePoint = (i32, u32);
+
+	#[pallet::storage]
+	#[pallet::getter(fn open_liability)]
+	/// Total payout the LP owes to the open orders of a pair if they all win: (Call side, Put side)
+	/// The pair is identified by its price symbol, eg: BTC_USDT
+	pub(super) type OpenLiability<T: Config> = StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, Vec<u8>, (BalanceOf<T>, BalanceOf<T>), ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn pricing_curves)]
+	/// Pricing curve of each LP for strike orders, sorted by strike distance
+	/// Empty mean the LP does not accept strike orders
+	pub(super) type PricingCurves<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, BoundedVec<CurvePoint, T::MaxCurvePoints>, ValueQuery>;
+
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config>
+	where
+		AssetIdOf<T>: MaybeSerializeDeserialize,
+		BalanceOf<T>: MaybeSerializeDeserialize,
+	{
+		/// Pools created at genesis: (admin, name, payout rate, asset, amount)
+		/// The admin funds the pool, it must hold `amount` of `asset` in the assets genesis
+		pub pools: Vec<(T::AccountId, Vec<u8>, u8, AssetIdOf<T>, BalanceOf<T>)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T>
+	where
+		AssetIdOf<T>: MaybeSerializeDeserialize,
+		BalanceOf<T>: MaybeSerializeDeserialize,
+	{
+		fn default() -> Self {
+			Self { pools: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T>
+	where
+		AssetIdOf<T>: MaybeSerializeDeserialize,
+		BalanceOf<T>: MaybeSerializeDeserialize,
+	{
+		fn build(&self) {
+			for (admin, name, payout_rate, asset_id, amount) in &self.pools {
+				Pallet::<T>::create_lp(
+					frame_system::RawOrigin::Signed(admin.clone()).into(),
+					name.clone(),
+					*payout_rate,
+					*asset_id,
+					*amount,
+				)
+				.expect("genesis pools are valid and funded; qed");
+			}
+		}
+	}
 
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/v3/runtime/events-and-errors
@@ -145,6 +236,7 @@ pub mod pallet {
 		LPDeposit(T::AccountId, T::AccountId),
 		LPUpdated(T::AccountId, T::AccountId),
 		LPGetRandom(T::AccountId, T::AccountId),
+		LPPricingCurveUpdated(T::AccountId, T::AccountId),
 	}
 
 	// Errors inform users that something went wrong.
@@ -162,6 +254,12 @@ pub mod pallet {
 		NotEnoughBalance,
 		/// Handles checking payout rate when create LP
 		InvalidPayoutRate,
+		/// Curve points must be sorted by strike distance and have a positive payout rate
+		InvalidPricingCurve,
+		/// Only the LP admin can do this
+		NotLpAdmin,
+		/// MaxPools pools were already created
+		TooManyPools,
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -174,8 +272,8 @@ pub mod pallet {
 
 		/// An example dispatchable that takes a singles value as a parameter, writes the value to
 		/// storage and emits an event. This function must be dispatched by a signed extrinsic.
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn create_lp(origin: OriginFor<T>, name: Vec<u8>, payout_rate: u8, amount: BalanceOf<T>) -> DispatchResult {
+		#[pallet::weight(T::WeightInfo::create_lp(T::MaxPools::get()))]
+		pub fn create_lp(origin: OriginFor<T>, name: Vec<u8>, payout_rate: u8, asset_id: AssetIdOf<T>, amount: BalanceOf<T>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
 			let min_amount: BalanceOf<T> = Self::u64_to_balance(10000).ok_or(<Error<T>>::InvalidAmount)?;
@@ -183,17 +281,19 @@ pub mod pallet {
 
 			let new_cnt = Self::lp_count().checked_add(1)
 				.ok_or(<Error<T>>::LPCntOverflow)?;
+			ensure!(new_cnt <= T::MaxPools::get(), <Error<T>>::TooManyPools);
 			let current_lp_idx = new_cnt - 1;
 
 			// let pallet_account_id = Self::account_id();
 			let lp_id = Self::sub_account_id(current_lp_idx);
 
-			let balance = T::Currency::free_balance(&lp_id);
+			let balance = T::Assets::balance(asset_id, &lp_id);
 			let final_amount = balance + amount;
 
 			let mut liquidity_pool = LiquidityPool::<T> {
 				id: lp_id.clone(),
 				name: name,
+				asset_id,
 				amount: final_amount,
 				payout_rate: payout_rate,
 				admin: sender.clone(),
@@ -206,10 +306,10 @@ pub mod pallet {
 			ensure!(Self::liquidity_pools(&lp_id) == None, <Error<T>>::NoLiquidityPool);
 
 			// Check the buyer has enough free balance to create this lp
-			ensure!(T::Currency::free_balance(&sender) >= amount, <Error<T>>::NotEnoughBalance);
+			ensure!(T::Assets::reducible_balance(asset_id, &sender, true) >= amount, <Error<T>>::NotEnoughBalance);
 
-			// amount need larger than ExistentialDeposit const define in Runtime
-			T::Currency::transfer(&sender, &lp_id, amount, ExistenceRequirement::KeepAlive)?;
+			// amount need larger than the asset min_balance
+			T::Assets::transfer(asset_id, &sender, &lp_id, amount, true)?;
 
 			<LiquidityPools<T>>::insert(lp_id.clone(), liquidity_pool);
 			<LpCount<T>>::put(new_cnt);
@@ -226,7 +326,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		#[pallet::weight(T::WeightInfo::deposit_lp())]
 		pub fn deposit_lp(origin: OriginFor<T>, lp_id: T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
@@ -236,12 +336,12 @@ pub mod pallet {
 			LiquidityPools::<T>::try_mutate_exists(&lp_id, |liquidity_pool| -> DispatchResult {
 				let mut lp = liquidity_pool.as_mut().ok_or(Error::<T>::NoLiquidityPool)?;
 				
-				let balance = T::Currency::free_balance(&lp_id);
+				let balance = T::Assets::balance(lp.asset_id, &lp_id);
 				let final_amount = balance + amount;
 				lp.amount = final_amount;
 
-				// amount need larger than ExistentialDeposit const define in Runtime
-				T::Currency::transfer(&sender, &lp_id, amount, ExistenceRequirement::KeepAlive)?;
+				// amount need larger than the asset min_balance
+				T::Assets::transfer(lp.asset_id, &sender, &lp_id, amount, true)?;
 
 				Ok(())
 			})?;
@@ -251,7 +351,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		#[pallet::weight(T::WeightInfo::update_lp())]
 		pub fn update_lp(origin: OriginFor<T>, lp_id: T::AccountId, name: Vec<u8>, payout_rate: u8) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
@@ -274,10 +374,10 @@ pub mod pallet {
 		}
 
 		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn get_lp(origin: OriginFor<T>, volumn: u64) -> DispatchResult {
+		pub fn get_lp(origin: OriginFor<T>, asset_id: AssetIdOf<T>, volumn: u64) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
-			let lp_id = Self::pick_a_suitable_lp(volumn);
+			let (lp_id, _) = Self::pick_a_suitable_lp(asset_id, volumn);
 			ensure!(lp_id.is_some(), <Error<T>>::NoLiquidityPool);
 
 			log::info!("Random LP: {:?}.", lp_id.clone().unwrap());
@@ -286,6 +386,31 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Set the pricing curve used to quote strike orders of a LP
+		/// - points: [(strike distance in bps, payout rate)], sorted by strike distance
+		/// - an empty curve stop the LP from accepting strike orders
+		/// - at most `MaxCurvePoints` points
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_pricing_curve(
+			origin: OriginFor<T>,
+			lp_id: T::AccountId,
+			points: BoundedVec<CurvePoint, T::MaxCurvePoints>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let lp = Self::liquidity_pools(&lp_id).ok_or(<Error<T>>::NoLiquidityPool)?;
+			ensure!(lp.admin == sender, <Error<T>>::NotLpAdmin);
+
+			ensure!(points.iter().all(|(_, rate)| *rate > 0), <Error<T>>::InvalidPricingCurve);
+			ensure!(points.windows(2).all(|w| w[0].0 < w[1].0), <Error<T>>::InvalidPricingCurve);
+
+			<PricingCurves<T>>::insert(&lp_id, points);
+
+			Self::deposit_event(Event::LPPricingCurveUpdated(sender, lp_id));
+
+			Ok(())
+		}
 		
 	}
 
@@ -325,22 +450,29 @@ pub mod pallet {
 			}
 		}
 
-		/// Randomly choose a LP from among the total number of lps.
-		/// Returns `None` if there are no lp.
-		fn choose_lp(total: u32) -> Option<u32> {
+		/// Round robin choose a LP funded with `asset_id` from among the total number of lps.
+		/// Returns `None` if there are no lp of this asset, and the number of lps visited.
+		fn choose_lp(asset_id: AssetIdOf<T>, total: u32) -> (Option<u32>, u32) {
 			if total == 0 {
-				return None
+				return (None, 0)
 			}
 
 			let mut pool_index = LpRandomIndex::<T>::get();
 			log::info!("current pool_index: {:?}.", pool_index);
-			let total = LpCount::<T>::get();
 
-			pool_index = (pool_index + 1) % total;
-			LpRandomIndex::<T>::put(pool_index);
-			log::info!("new pool_index: {:?}.", pool_index);
+			for visited in 1..=total {
+				pool_index = (pool_index + 1) % total;
+				let same_asset = <LiquidityPoolsIndex<T>>::get(pool_index)
+					.and_then(|lp_id| Self::liquidity_pools(lp_id))
+					.map_or(false, |lp| lp.asset_id == asset_id);
+				if same_asset {
+					LpRandomIndex::<T>::put(pool_index);
+					log::info!("new pool_index: {:?}.", pool_index);
+					return (Some(pool_index), visited)
+				}
+			}
 
-			Some(pool_index)
+			(None, total)
 
 			// let mut random_number = Self::generate_random_number(0);
 	
@@ -365,12 +497,12 @@ pub mod pallet {
 			random_number
 		}
 
-		/// Get a unique hash to use as lp id
-		fn get_next_lp_id() -> Option<T::AccountId> {
+		/// Get a unique hash to use as lp id, and the number of lps visited
+		fn get_next_lp_id(asset_id: AssetIdOf<T>) -> (Option<T::AccountId>, u32) {
 			// Use Round robin get random LP
-			match Self::choose_lp(<LpCount<T>>::get()) {
-				None => None,
-				Some(lp) => <LiquidityPoolsIndex<T>>::get(lp),
+			match Self::choose_lp(asset_id, <LpCount<T>>::get()) {
+				(None, visited) => (None, visited),
+				(Some(lp), visited) => (<LiquidityPoolsIndex<T>>::get(lp), visited),
 			}
 		}
 
@@ -383,16 +515,48 @@ pub mod pallet {
 		}
 		So picking a LP from LpItemsRank will have O(1) time-complexity
 		 */
-		fn pick_a_suitable_lp(volumn:u64) -> Option<T::AccountId> {
+		fn pick_a_suitable_lp(asset_id: AssetIdOf<T>, volumn:u64) -> (Option<T::AccountId>, u32) {
 			// TODO: Round robin or implement a suitable approach to get suitable LP
 			// And improve the picking speed
-			Self::get_next_lp_id()
+			Self::get_next_lp_id(asset_id)
+		}
+
+		/// Payout rate of a strike order, linearly interpolated between the two nearest curve points
+		/// Returns `None` if the strike distance is outside of the curve
+		pub fn strike_payout_rate(lp_id: &T::AccountId, distance: i32) -> Option<u32> {
+			let curve = Self::pricing_curves(lp_id);
+			let (first, last) = (curve.first()?, curve.last()?);
+			if distance < first.0 || distance > last.0 {
+				return None;
+			}
+
+			for w in curve.windows(2) {
+				let ((d0, r0), (d1, r1)) = (w[0], w[1]);
+				if distance >= d0 && distance <= d1 {
+					let rate = r0 as i64 + (r1 as i64 - r0 as i64) * (distance as i64 - d0 as i64) / (d1 as i64 - d0 as i64);
+					return Some(rate as u32);
+				}
+			}
+
+			// single point curve
+			Some(first.1)
+		}
+
+		/// Add or remove `amount` from the LP liability of a pair side
+		fn mutate_liability(lp_id: &T::AccountId, pair: &Vec<u8>, side: PositionSide, amount: BalanceOf<T>, increase: bool) {
+			<OpenLiability<T>>::mutate(lp_id, pair, |(call, put)| {
+				let liability = match side {
+					PositionSide::Call => call,
+					PositionSide::Put => put,
+				};
+				*liability = if increase { liability.saturating_add(amount) } else { liability.saturating_sub(amount) };
+			});
 		}
 
 		fn update_lp_balance(lp_id: T::AccountId) -> Option<T::AccountId> {
 			LiquidityPools::<T>::try_mutate_exists(&lp_id, |liquidity_pool| -> DispatchResult {
 				let mut lp = liquidity_pool.as_mut().ok_or(Error::<T>::NoLiquidityPool)?;
-				let balance = T::Currency::free_balance(&lp_id);
+				let balance = T::Assets::balance(lp.asset_id, &lp_id);
 				lp.amount = balance;
 				Ok(())
 			}).ok()?;
@@ -405,21 +569,71 @@ pub mod pallet {
 	/// Expose for loosely coupling
 	/// for using in other pallet
 	///
-	pub trait BoLiquidityInterface<TAccountId> {
-		fn get_suitable_lp(volumn:u64) -> Option<TAccountId>;
+	pub trait BoLiquidityInterface<TAccountId, TAssetId, TBalance> {
+		/// Pick a LP funded with `asset_id` for an order of `volumn`
+		/// Also returns the number of LPs visited, at most `MaxPools`, to weight the caller
+		fn get_suitable_lp(asset_id: TAssetId, volumn:u64) -> (Option<TAccountId>, u32);
 		fn update_lp_balance(lp_id:TAccountId) -> Option<TAccountId>;
+		/// Payout rate offered by a LP for a strike `distance` bps out of the money
+		fn get_strike_payout_rate(lp_id: &TAccountId, distance: i32) -> Option<u32>;
+		/// Base payout rate of a LP
+		fn get_payout_rate(lp_id: &TAccountId) -> Option<u32>;
+		/// Open (Call, Put) liability of a LP on a pair
+		fn get_open_liability(lp_id: &TAccountId, pair: &Vec<u8>) -> (TBalance, TBalance);
+		fn add_liability(lp_id: &TAccountId, pair: &Vec<u8>, side: PositionSide, amount: TBalance);
+		fn release_liability(lp_id: &TAccountId, pair: &Vec<u8>, side: PositionSide, amount: TBalance);
+
+		/// Create a pool of `asset_id` funded with `amount` by `admin`, for the benchmarks of
+		/// the pallets picking pools
+		#[cfg(feature = "runtime-benchmarks")]
+		fn create_pool(
+			admin: TAccountId,
+			asset_id: TAssetId,
+			amount: TBalance,
+		) -> Result<TAccountId, DispatchError>;
 	}
 
 	// impl<T: Config> BoLiquidityInterface for Module<T> {
-	impl<T: Config> BoLiquidityInterface<T::AccountId> for Pallet<T> {
+	impl<T: Config> BoLiquidityInterface<T::AccountId, AssetIdOf<T>, BalanceOf<T>> for Pallet<T> {
 		// use Pallet<T> instead of Module<T> to support calling in other impl of Pallet?
-		fn get_suitable_lp(volumn:u64) -> Option<T::AccountId> {
-			Self::pick_a_suitable_lp(volumn)
+		fn get_suitable_lp(asset_id: AssetIdOf<T>, volumn:u64) -> (Option<T::AccountId>, u32) {
+			Self::pick_a_suitable_lp(asset_id, volumn)
 		}
 
 		fn update_lp_balance(lp_id: T::AccountId) -> Option<T::AccountId> {
 			Self::update_lp_balance(lp_id)
 		}
+
+		fn get_strike_payout_rate(lp_id: &T::AccountId, distance: i32) -> Option<u32> {
+			Self::strike_payout_rate(lp_id, distance)
+		}
+
+		fn get_payout_rate(lp_id: &T::AccountId) -> Option<u32> {
+			Self::liquidity_pools(lp_id).map(|lp| lp.payout_rate as u32)
+		}
+
+		fn get_open_liability(lp_id: &T::AccountId, pair: &Vec<u8>) -> (BalanceOf<T>, BalanceOf<T>) {
+			Self::open_liability(lp_id, pair)
+		}
+
+		fn add_liability(lp_id: &T::AccountId, pair: &Vec<u8>, side: PositionSide, amount: BalanceOf<T>) {
+			Self::mutate_liability(lp_id, pair, side, amount, true)
+		}
+
+		fn release_liability(lp_id: &T::AccountId, pair: &Vec<u8>, side: PositionSide, amount: BalanceOf<T>) {
+			Self::mutate_liability(lp_id, pair, side, amount, false)
+		}
+
+		#[cfg(feature = "runtime-benchmarks")]
+		fn create_pool(
+			admin: T::AccountId,
+			asset_id: AssetIdOf<T>,
+			amount: BalanceOf<T>,
+		) -> Result<T::AccountId, DispatchError> {
+			let origin = frame_system::RawOrigin::Signed(admin).into();
+			Self::create_lp(origin, b"benchmark".to_vec(), 90, asset_id, amount)?;
+			Ok(Self::sub_account_id(Self::lp_count() - 1))
+		}
 	}
 	// End loosely coupling
 }
@@ -0,0 +1,111 @@
+//! Storage migrations of the BoLiquidity pallet
+
+/// Version 1: a `LiquidityPool` records the asset it is funded with
+///
+/// Version 0 pools were funded in the native currency, they are set to the `LegacyAssetId`
+/// asset:
+/// - the native funds of a pool account go back to the pool admin, they can not pay out
+/// 	the orders of the asset
+/// - the pool amount is its balance of the asset, the admin funds it again with `deposit_lp`
+///
+/// Runs after the BoTrading migration, which refunds the open orders from the pools
+pub mod v1 {
+	use crate::{AssetIdOf, BalanceOf, Config, LiquidityPool, LiquidityPools, Pallet};
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{
+			fungibles::Inspect, Currency, ExistenceRequirement, GetStorageVersion,
+			OnRuntimeUpgrade, StorageVersion,
+		},
+		weights::Weight,
+	};
+	use scale_info::prelude::vec::Vec;
+
+	#[cfg(feature = "try-runtime")]
+	use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+
+	/// `LiquidityPool` of version 0
+	#[derive(Decode)]
+	struct OldLiquidityPool<T: Config> {
+		id: T::AccountId,
+		name: Vec<u8>,
+		/// in the native currency, replaced by the balance of the asset
+		_amount: BalanceOf<T>,
+		payout_rate: u8,
+		admin: T::AccountId,
+	}
+
+	pub struct MigrateToV1<T, LegacyAssetId, NativeCurrency>(
+		PhantomData<(T, LegacyAssetId, NativeCurrency)>,
+	);
+
+	impl<T, LegacyAssetId, NativeCurrency> OnRuntimeUpgrade
+		for MigrateToV1<T, LegacyAssetId, NativeCurrency>
+	where
+		T: Config,
+		LegacyAssetId: Get<AssetIdOf<T>>,
+		NativeCurrency: Currency<T::AccountId>,
+	{
+		fn on_runtime_upgrade() -> Weight {
+			let on_chain_version = <Pallet<T>>::on_chain_storage_version();
+			if on_chain_version != 0 {
+				log::info!("BoLiquidity: skip migration to v1, on chain {:?}", on_chain_version);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let asset_id = LegacyAssetId::get();
+			let mut translated = 0u64;
+			<LiquidityPools<T>>::translate::<OldLiquidityPool<T>, _>(|_, old| {
+				translated += 1;
+				let native = NativeCurrency::free_balance(&old.id);
+				let returned = NativeCurrency::transfer(
+					&old.id,
+					&old.admin,
+					native,
+					ExistenceRequirement::AllowDeath,
+				);
+				if returned.is_err() {
+					log::error!("BoLiquidity: native funds of pool {:?} not returned", old.id);
+				}
+				Some(LiquidityPool {
+					asset_id,
+					amount: T::Assets::balance(asset_id, &old.id),
+					id: old.id,
+					name: old.name,
+					payout_rate: old.payout_rate,
+					admin: old.admin,
+				})
+			});
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			log::info!("BoLiquidity: migrated {} pools to v1", translated);
+			T::DbWeight::get().reads_writes(translated * 4 + 1, translated * 3 + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Self::set_temp_storage(<LiquidityPools<T>>::iter_keys().count() as u32, "pools");
+
+			Ok(())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			ensure!(<Pallet<T>>::on_chain_storage_version() == 1, "BoLiquidity: not v1");
+
+			let pools: u32 = Self::get_temp_storage("pools").ok_or("BoLiquidity: no pre upgrade")?;
+			// translate drops the pools it can not decode
+			ensure!(
+				<LiquidityPools<T>>::iter_values().count() as u32 == pools,
+				"BoLiquidity: pools lost"
+			);
+			let asset_id = LegacyAssetId::get();
+			ensure!(
+				<LiquidityPools<T>>::iter_values().all(|pool| pool.asset_id == asset_id),
+				"BoLiquidity: pools not migrated"
+			);
+
+			Ok(())
+		}
+	}
+}
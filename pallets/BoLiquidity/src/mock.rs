@@ -1,5 +1,5 @@
 use crate as pallet_bo_liquidity;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, PalletId};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
@@ -19,6 +19,7 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Config<T>, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
 		BoLiquidityModule: pallet_bo_liquidity::{Pallet, Call, Storage, Event<T>},
 	}
@@ -71,16 +72,65 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = ();
 }
 
+parameter_types! {
+	pub const AssetDeposit: u64 = 1;
+	pub const ApprovalDeposit: u64 = 1;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: u64 = 1;
+	pub const MetadataDepositPerByte: u64 = 1;
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
 impl pallet_randomness_collective_flip::Config for Test {}
 
+parameter_types! {
+	pub const BoLiquidityPalletId: PalletId = PalletId(*b"BrightOp");
+	pub const MaxPools: u32 = 100;
+	pub const MaxCurvePoints: u32 = 8;
+}
+
 impl pallet_bo_liquidity::Config for Test {
 	type Event = Event;
 
-	type Currency = Balances;
+	type Assets = Assets;
 	type MyRandomness = RandomnessCollectiveFlip;
+	type PalletId = BoLiquidityPalletId;
+	type MaxPools = MaxPools;
+	type MaxCurvePoints = MaxCurvePoints;
+	type WeightInfo = ();
 }
 
+/// Dev stable coin of the mock runtime
+pub const USDT: u32 = 1;
+
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 1_000_000), (2, 1_000_000)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	// sufficient: pool accounts can hold USDT without a native balance
+	pallet_assets::GenesisConfig::<Test> {
+		assets: vec![(USDT, 1, true, 1)],
+		metadata: vec![(USDT, b"Tether USD".to_vec(), b"USDT".to_vec(), 6)],
+		accounts: vec![(USDT, 1, 1_000_000), (USDT, 2, 1_000_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	t.into()
 }
@@ -1,5 +1,17 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	mock::*, migrations, BoLiquidityInterface, CurvePoint, Error, LiquidityPools, PositionSide,
+};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::unhashed,
+	traits::{ConstU32, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	BoundedVec,
+};
+
+fn curve(points: Vec<CurvePoint>) -> BoundedVec<CurvePoint, MaxCurvePoints> {
+	BoundedVec::try_from(points).unwrap()
+}
 
 #[test]
 fn it_works_for_default_value() {
@@ -9,13 +21,76 @@ fn it_works_for_default_value() {
 		// // Read pallet storage and assert an expected result.
 		// assert_eq!(TemplateModule::something(), Some(42));
 
-		BoLiquidityModule::create_lp(Origin::signed(1), String::from("Lp 1").as_bytes().to_vec(), 10000, 95);
-		BoLiquidityModule::create_lp(Origin::signed(1), String::from("Lp 2").as_bytes().to_vec(), 600000, 95);
+		BoLiquidityModule::create_lp(Origin::signed(1), String::from("Lp 1").as_bytes().to_vec(), 95, USDT, 10000);
+		BoLiquidityModule::create_lp(Origin::signed(1), String::from("Lp 2").as_bytes().to_vec(), 95, USDT, 600000);
 		
 
 	});
 }
 
+#[test]
+fn pricing_curve_interpolates_payout_rate() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(BoLiquidityModule::create_lp(Origin::signed(1), b"Lp 1".to_vec(), 95, USDT, 10000));
+		let lp_id = BoLiquidityModule::sub_account_id(0);
+
+		assert_noop!(
+			BoLiquidityModule::set_pricing_curve(Origin::signed(2), lp_id, curve(vec![(0, 95)])),
+			Error::<Test>::NotLpAdmin
+		);
+		assert_noop!(
+			BoLiquidityModule::set_pricing_curve(
+				Origin::signed(1),
+				lp_id,
+				curve(vec![(100, 120), (0, 95)])
+			),
+			Error::<Test>::InvalidPricingCurve
+		);
+
+		assert_ok!(BoLiquidityModule::set_pricing_curve(
+			Origin::signed(1),
+			lp_id,
+			curve(vec![(-100, 50), (0, 90), (200, 190)])
+		));
+
+		assert_eq!(BoLiquidityModule::strike_payout_rate(&lp_id, 0), Some(90));
+		assert_eq!(BoLiquidityModule::strike_payout_rate(&lp_id, 100), Some(140));
+		assert_eq!(BoLiquidityModule::strike_payout_rate(&lp_id, -50), Some(70));
+		assert_eq!(BoLiquidityModule::strike_payout_rate(&lp_id, 201), None);
+		assert_eq!(BoLiquidityModule::strike_payout_rate(&lp_id, -101), None);
+	});
+}
+
+#[test]
+fn liability_is_tracked_per_pair_and_side() {
+	new_test_ext().execute_with(|| {
+		let pair = b"BTC_USDT".to_vec();
+
+		BoLiquidityModule::add_liability(&7, &pair, PositionSide::Call, 100);
+		BoLiquidityModule::add_liability(&7, &pair, PositionSide::Put, 40);
+		BoLiquidityModule::add_liability(&7, &pair, PositionSide::Call, 50);
+		assert_eq!(BoLiquidityModule::get_open_liability(&7, &pair), (150, 40));
+		assert_eq!(BoLiquidityModule::get_open_liability(&7, &b"DOT_USDC".to_vec()), (0, 0));
+
+		BoLiquidityModule::release_liability(&7, &pair, PositionSide::Call, 100);
+		BoLiquidityModule::release_liability(&7, &pair, PositionSide::Put, 60);
+		assert_eq!(BoLiquidityModule::get_open_liability(&7, &pair), (50, 0));
+	});
+}
+
+#[test]
+fn suitable_lp_is_funded_with_the_order_asset() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(BoLiquidityModule::create_lp(Origin::signed(1), b"Lp 1".to_vec(), 95, USDT, 10000));
+		let lp_id = BoLiquidityModule::sub_account_id(0);
+		assert_eq!(Assets::balance(USDT, lp_id), 10000);
+
+		// (pool, pools visited)
+		assert_eq!(BoLiquidityModule::get_suitable_lp(USDT, 100), (Some(lp_id), 1));
+		assert_eq!(BoLiquidityModule::get_suitable_lp(USDT + 1, 100), (None, 1));
+	});
+}
+
 // #[test]
 // fn correct_error_for_none_value() {
 // 	new_test_ext().execute_with(|| {
@@ -29,3 +104,31 @@ fn it_works_for_default_value() {
 Test:
 - [ ] same order data at the same timestamp, ... must created different order_id
  */
+
+#[test]
+fn migrates_the_v0_pools_to_v1() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<BoLiquidityModule>();
+		// version 0 `LiquidityPool`: no asset
+		let pool_id = 42u64;
+		let old_pool = (pool_id, b"Lp 1".to_vec(), 10_000u64, 95u8, 1u64);
+		unhashed::put_raw(&<LiquidityPools<Test>>::hashed_key_for(pool_id), &old_pool.encode());
+		// the native funds of the pool, and the USDT already sent to it
+		assert_ok!(Balances::transfer(Origin::signed(1), pool_id, 10_000));
+		assert_ok!(Assets::transfer(Origin::signed(1), USDT, pool_id, 500));
+
+		migrations::v1::MigrateToV1::<Test, ConstU32<USDT>, Balances>::on_runtime_upgrade();
+
+		assert_eq!(BoLiquidityModule::on_chain_storage_version(), 1);
+		let pool = BoLiquidityModule::liquidity_pools(pool_id).unwrap();
+		assert_eq!(pool.id, pool_id);
+		assert_eq!(pool.name, b"Lp 1".to_vec());
+		assert_eq!(pool.asset_id, USDT);
+		// the native funds went back to the admin, the pool only holds USDT
+		assert_eq!(pool.amount, 500);
+		assert_eq!(Balances::free_balance(pool_id), 0);
+		assert_eq!(Balances::free_balance(1), 1_000_000);
+		assert_eq!(pool.payout_rate, 95);
+		assert_eq!(pool.admin, 1);
+	});
+}
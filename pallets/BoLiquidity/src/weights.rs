@@ -0,0 +1,98 @@
+//! Weights for pallet_bo_liquidity
+//!
+//! HAND-WRITTEN PLACEHOLDER: these weights were not measured, they are estimated from the
+//! storage accesses of each call. They must be regenerated on the reference hardware with
+//! `scripts/benchmark.sh` before a production release, which runs:
+
+// ./target/release/node-template
+// benchmark
+// --chain=dev
+// --execution=wasm
+// --wasm-execution=compiled
+// --pallet=pallet_bo_liquidity
+// --extrinsic=*
+// --steps=50
+// --repeat=20
+// --output=./pallets/BoLiquidity/src/weights.rs
+// --template=./.maintain/frame-weight-template.hbs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_bo_liquidity.
+pub trait WeightInfo {
+	fn create_lp(p: u32, ) -> Weight;
+	fn deposit_lp() -> Weight;
+	fn update_lp() -> Weight;
+}
+
+/// Weights for pallet_bo_liquidity using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: BoLiquidityModule LpCount (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	// Storage: Assets Asset (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: BoLiquidityModule LiquidityPoolsOwned (r:0 w:1)
+	// Storage: BoLiquidityModule LiquidityPoolsIndex (r:0 w:1)
+	// Storage: BoLiquidityModule LpItemsRank (r:0 w:1)
+	// Storage: BoLiquidityModule LpItemsRankIndex (r:0 w:1)
+	fn create_lp(p: u32, ) -> Weight {
+		(62_000_000 as Weight)
+			.saturating_add((12_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(10 as Weight))
+	}
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: Assets Asset (r:1 w:1)
+	fn deposit_lp() -> Weight {
+		(48_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	fn update_lp() -> Weight {
+		(21_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: BoLiquidityModule LpCount (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	// Storage: Assets Asset (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: BoLiquidityModule LiquidityPoolsOwned (r:0 w:1)
+	// Storage: BoLiquidityModule LiquidityPoolsIndex (r:0 w:1)
+	// Storage: BoLiquidityModule LpItemsRank (r:0 w:1)
+	// Storage: BoLiquidityModule LpItemsRankIndex (r:0 w:1)
+	fn create_lp(p: u32, ) -> Weight {
+		(62_000_000 as Weight)
+			.saturating_add((12_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(10 as Weight))
+	}
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: Assets Asset (r:1 w:1)
+	fn deposit_lp() -> Weight {
+		(48_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	fn update_lp() -> Weight {
+		(21_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+}
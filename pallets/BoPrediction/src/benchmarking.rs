@@ -0,0 +1,93 @@
+//! Benchmarking setup for pallet-bo-prediction
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as BoPrediction;
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_support::traits::{Currency, EnsureOrigin, Hooks};
+use frame_system::RawOrigin;
+use scale_info::prelude::vec::Vec;
+use sp_runtime::traits::Saturating;
+
+/// The symbol of the benchmarked market
+const SYMBOL: &[u8] = b"BTC_USDT";
+
+fn symbol() -> Vec<u8> {
+	SYMBOL.to_vec()
+}
+
+/// Give `who` enough to bet `MinBet` and stay alive, returns the bet amount
+fn fund<T: Config>(who: &T::AccountId) -> BalanceOf<T> {
+	let amount = T::MinBet::get();
+	let balance = T::Currency::minimum_balance().saturating_add(amount.saturating_add(amount));
+	T::Currency::make_free_balance_be(who, balance);
+	amount
+}
+
+fn start_market<T: Config>() {
+	BoPrediction::<T>::start_market(T::AdminOrigin::successful_origin(), symbol())
+		.expect("the market is not started; qed");
+}
+
+benchmarks! {
+	start_market {
+	}: _<T::Origin>(T::AdminOrigin::successful_origin(), symbol())
+	verify {
+		assert_eq!(CurrentEpoch::<T>::get(symbol()), Some(0));
+	}
+
+	// Worst case: an open and a locked round are cancelled
+	stop_market {
+		start_market::<T>();
+		let lock_block = Rounds::<T>::get(symbol(), 0).unwrap().lock_block;
+		frame_system::Pallet::<T>::set_block_number(lock_block);
+		BoPrediction::<T>::on_initialize(lock_block);
+		Rounds::<T>::mutate(symbol(), 0, |round| {
+			if let Some(round) = round {
+				round.status = RoundStatus::Locked;
+			}
+		});
+	}: _<T::Origin>(T::AdminOrigin::successful_origin(), symbol())
+	verify {
+		assert_eq!(Rounds::<T>::get(symbol(), 0).unwrap().status, RoundStatus::Cancelled);
+		assert_eq!(Rounds::<T>::get(symbol(), 1).unwrap().status, RoundStatus::Cancelled);
+	}
+
+	bet {
+		start_market::<T>();
+		let caller: T::AccountId = whitelisted_caller();
+		let amount = fund::<T>(&caller);
+	}: _(RawOrigin::Signed(caller.clone()), symbol(), Position::Up, amount)
+	verify {
+		assert!(Bets::<T>::contains_key((symbol(), 0), &caller));
+	}
+
+	// Worst case: the reward of a winning bet is computed
+	claim {
+		start_market::<T>();
+		let caller: T::AccountId = whitelisted_caller();
+		let amount = fund::<T>(&caller);
+		BoPrediction::<T>::bet(
+			RawOrigin::Signed(caller.clone()).into(),
+			symbol(),
+			Position::Up,
+			amount,
+		)
+		.expect("the round is open; qed");
+		Rounds::<T>::mutate(symbol(), 0, |round| {
+			if let Some(round) = round {
+				round.lock_price = Some(1);
+				round.close_price = Some(2);
+				round.reward_base = amount;
+				round.reward_amount = amount;
+				round.status = RoundStatus::Closed;
+			}
+		});
+	}: _(RawOrigin::Signed(caller.clone()), symbol(), 0)
+	verify {
+		assert!(Bets::<T>::get((symbol(), 0), &caller).unwrap().claimed);
+	}
+
+	impl_benchmark_test_suite!(BoPrediction, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,513 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Parimutuel prediction rounds
+///
+/// Each market (a symbol of SymbolPrice pallet) runs back to back rounds:
+/// - Open: users bet Up or Down
+/// - Locked: the lock price is recorded, bets are closed
+/// - Closed: the close price is recorded, winners split the pool minus the protocol fee
+/// Round scheduling is done in `on_initialize`, every `RoundDuration` blocks:
+/// lock the open round, close the locked one and open the next one.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		dispatch::DispatchResult,
+		pallet_prelude::*,
+		traits::{
+			Currency,
+			ExistenceRequirement::{AllowDeath, KeepAlive},
+		},
+		PalletId,
+	};
+	use frame_system::pallet_prelude::*;
+	use scale_info::prelude::vec::Vec;
+	use scale_info::TypeInfo;
+	use sp_runtime::{
+		traits::{AccountIdConversion, Saturating, Zero},
+		Permill, SaturatedConversion,
+	};
+
+	#[cfg(feature = "std")]
+	use frame_support::serde::{Deserialize, Serialize};
+	use pallet_symbol_price::{SymbolPrice, SymbolPriceInterface};
+	use crate::weights::WeightInfo;
+
+	pub(crate) type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The Currency handler for the BoPrediction pallet.
+		type Currency: Currency<Self::AccountId>;
+
+		/// Loose coupling with SymbolPrice pallet
+		type SymbolPriceModule: SymbolPriceInterface;
+
+		/// Origin allowed to start and stop markets
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Account holding the bets until they are claimed
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// Account receiving the protocol fee
+		#[pallet::constant]
+		type TreasuryPalletId: Get<PalletId>;
+
+		/// Number of blocks of the Open phase, and of the Locked phase
+		#[pallet::constant]
+		type RoundDuration: Get<Self::BlockNumber>;
+
+		/// Fee taken from the pool of every closed round
+		#[pallet::constant]
+		type ProtocolFee: Get<Permill>;
+
+		/// A price older than this number of blocks is stale, the round is cancelled
+		#[pallet::constant]
+		type MaxPriceAge: Get<Self::BlockNumber>;
+
+		/// Minimum amount of a bet
+		#[pallet::constant]
+		type MinBet: Get<BalanceOf<Self>>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum Position {
+		/// Close price will be higher than the lock price
+		Up,
+		/// Close price will be lower than the lock price
+		Down,
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum RoundStatus {
+		/// Accepting bets until lock_block
+		Open,
+		/// Waiting for the close price at close_block
+		Locked,
+		/// Winners can claim their reward
+		Closed,
+		/// Every bet can be claimed back
+		Cancelled,
+	}
+
+	/// Struct for holding Round information.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Round<T: Config> {
+		pub epoch: u32,
+		pub start_block: T::BlockNumber,
+		pub lock_block: T::BlockNumber,
+		pub close_block: T::BlockNumber,
+		pub lock_price: Option<SymbolPrice>,
+		pub close_price: Option<SymbolPrice>,
+		pub up_amount: BalanceOf<T>,
+		pub down_amount: BalanceOf<T>,
+		/// Total amount of the winning side
+		pub reward_base: BalanceOf<T>,
+		/// Pool shared by the winners: total amount minus protocol fee
+		pub reward_amount: BalanceOf<T>,
+		pub status: RoundStatus,
+	}
+
+	impl<T: Config> Round<T> {
+		/// Winning position of a closed round
+		pub fn winner(&self) -> Option<Position> {
+			match (self.lock_price, self.close_price) {
+				(Some(lock), Some(close)) if close > lock => Some(Position::Up),
+				(Some(lock), Some(close)) if close < lock => Some(Position::Down),
+				_ => None,
+			}
+		}
+	}
+
+	/// Struct for holding Bet information.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Bet<T: Config> {
+		pub position: Position,
+		pub amount: BalanceOf<T>,
+		pub claimed: bool,
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn active_markets)]
+	/// Symbols running rounds
+	pub(super) type ActiveMarkets<T: Config> = StorageValue<_, Vec<Vec<u8>>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn current_epoch)]
+	/// Epoch of the latest round of a symbol, kept when the market is stopped
+	pub(super) type CurrentEpoch<T: Config> = StorageMap<_, Twox64Concat, Vec<u8>, u32>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn rounds)]
+	/// Rounds of a symbol by epoch
+	pub(super) type Rounds<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, Vec<u8>, Twox64Concat, u32, Round<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn bets)]
+	/// Bet of an account in a round (symbol, epoch)
+	pub(super) type Bets<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, (Vec<u8>, u32), Twox64Concat, T::AccountId, Bet<T>>;
+
+	// Pallets use events to inform users when important changes are made.
+	// https://docs.substrate.io/v3/runtime/events-and-errors
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// parameters. [symbol]
+		MarketStarted(Vec<u8>),
+		/// parameters. [symbol]
+		MarketStopped(Vec<u8>),
+		/// parameters. [symbol, epoch]
+		RoundStarted(Vec<u8>, u32),
+		/// parameters. [symbol, epoch, lock_price]
+		RoundLocked(Vec<u8>, u32, SymbolPrice),
+		/// parameters. [symbol, epoch, close_price]
+		RoundClosed(Vec<u8>, u32, SymbolPrice),
+		/// parameters. [symbol, epoch]
+		RoundCancelled(Vec<u8>, u32),
+		BetPlaced {
+			account_id: T::AccountId,
+			symbol: Vec<u8>,
+			epoch: u32,
+			position: Position,
+			amount: BalanceOf<T>,
+		},
+		Claimed {
+			account_id: T::AccountId,
+			symbol: Vec<u8>,
+			epoch: u32,
+			amount: BalanceOf<T>,
+		},
+	}
+
+	// Errors inform users that something went wrong.
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The market is already running rounds
+		MarketAlreadyStarted,
+		/// The market is not running rounds
+		MarketNotStarted,
+		/// The current round does not accept bets anymore
+		RoundNotOpen,
+		/// Bet amount is lower than `MinBet`
+		BetTooSmall,
+		/// Only one bet per account and round
+		AlreadyBet,
+		/// The round is not closed or cancelled yet
+		RoundNotFinished,
+		/// The account did not bet in this round
+		NoBet,
+		/// The bet was already claimed
+		AlreadyClaimed,
+		/// The bet lost
+		NotWinner,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Every `RoundDuration` blocks for each market:
+		/// lock the open round, close the locked round and open the next round
+		fn on_initialize(block_number: T::BlockNumber) -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+
+			for symbol in Self::active_markets() {
+				weight = weight.saturating_add(T::DbWeight::get().reads(2));
+
+				let epoch = match Self::current_epoch(&symbol) {
+					Some(epoch) => epoch,
+					None => continue,
+				};
+				let lock_due = Self::rounds(&symbol, epoch)
+					.map_or(false, |round| round.lock_block <= block_number);
+				if !lock_due {
+					continue
+				}
+
+				Self::lock_round(&symbol, epoch, block_number);
+				if epoch > 0 {
+					Self::close_round(&symbol, epoch - 1, block_number);
+				}
+				Self::start_round(&symbol, epoch + 1, block_number);
+
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(4, 5));
+			}
+
+			weight
+		}
+	}
+
+	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
+	// These functions materialize as "extrinsics", which are often compared to transactions.
+	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Start running rounds for a symbol, the first round is open right away
+		#[pallet::weight(T::WeightInfo::start_market())]
+		pub fn start_market(origin: OriginFor<T>, symbol: Vec<u8>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(!Self::active_markets().contains(&symbol), <Error<T>>::MarketAlreadyStarted);
+
+			// continue the epoch numbering of a restarted market
+			let epoch = Self::current_epoch(&symbol).map_or(0, |epoch| epoch + 1);
+			let current_block = <frame_system::Pallet<T>>::block_number();
+
+			<ActiveMarkets<T>>::append(symbol.clone());
+			Self::start_round(&symbol, epoch, current_block);
+
+			Self::deposit_event(Event::MarketStarted(symbol));
+
+			Ok(())
+		}
+
+		/// Stop running rounds for a symbol, the open and locked rounds are cancelled
+		#[pallet::weight(T::WeightInfo::stop_market())]
+		pub fn stop_market(origin: OriginFor<T>, symbol: Vec<u8>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(Self::active_markets().contains(&symbol), <Error<T>>::MarketNotStarted);
+
+			<ActiveMarkets<T>>::mutate(|symbols| symbols.retain(|s| *s != symbol));
+			if let Some(epoch) = Self::current_epoch(&symbol) {
+				Self::cancel_round(&symbol, epoch);
+				if epoch > 0 {
+					Self::cancel_round(&symbol, epoch - 1);
+				}
+			}
+
+			Self::deposit_event(Event::MarketStopped(symbol));
+
+			Ok(())
+		}
+
+		/// Bet Up or Down on the open round of a symbol
+		#[pallet::weight(T::WeightInfo::bet())]
+		pub fn bet(
+			origin: OriginFor<T>,
+			symbol: Vec<u8>,
+			position: Position,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let epoch = Self::current_epoch(&symbol).ok_or(<Error<T>>::MarketNotStarted)?;
+			let mut round = Self::rounds(&symbol, epoch).ok_or(<Error<T>>::MarketNotStarted)?;
+			let current_block = <frame_system::Pallet<T>>::block_number();
+			ensure!(
+				round.status == RoundStatus::Open && current_block < round.lock_block,
+				<Error<T>>::RoundNotOpen
+			);
+			ensure!(amount >= T::MinBet::get(), <Error<T>>::BetTooSmall);
+			ensure!(
+				!<Bets<T>>::contains_key((&symbol, epoch), &sender),
+				<Error<T>>::AlreadyBet
+			);
+
+			T::Currency::transfer(&sender, &Self::account_id(), amount, KeepAlive)?;
+
+			match position {
+				Position::Up => round.up_amount = round.up_amount.saturating_add(amount),
+				Position::Down => round.down_amount = round.down_amount.saturating_add(amount),
+			}
+			<Rounds<T>>::insert(&symbol, epoch, round);
+			<Bets<T>>::insert((&symbol, epoch), &sender, Bet { position, amount, claimed: false });
+
+			Self::deposit_event(Event::BetPlaced {
+				account_id: sender,
+				symbol,
+				epoch,
+				position,
+				amount,
+			});
+
+			Ok(())
+		}
+
+		/// Claim the reward of a winning bet, or the refund of a cancelled round
+		#[pallet::weight(T::WeightInfo::claim())]
+		pub fn claim(origin: OriginFor<T>, symbol: Vec<u8>, epoch: u32) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let round = Self::rounds(&symbol, epoch).ok_or(<Error<T>>::RoundNotFinished)?;
+			let mut bet = Self::bets((&symbol, epoch), &sender).ok_or(<Error<T>>::NoBet)?;
+			ensure!(!bet.claimed, <Error<T>>::AlreadyClaimed);
+
+			let amount = match round.status {
+				RoundStatus::Cancelled => bet.amount,
+				RoundStatus::Closed => {
+					ensure!(round.winner() == Some(bet.position), <Error<T>>::NotWinner);
+					let reward = bet
+						.amount
+						.saturated_into::<u128>()
+						.saturating_mul(round.reward_amount.saturated_into::<u128>()) /
+						round.reward_base.saturated_into::<u128>().max(1);
+					reward.saturated_into::<BalanceOf<T>>()
+				},
+				_ => return Err(<Error<T>>::RoundNotFinished.into()),
+			};
+
+			// Transfer first, the bet stays unclaimed if it fails
+			T::Currency::transfer(&Self::account_id(), &sender, amount, AllowDeath)?;
+
+			bet.claimed = true;
+			<Bets<T>>::insert((&symbol, epoch), &sender, bet);
+
+			Self::deposit_event(Event::Claimed { account_id: sender, symbol, epoch, amount });
+
+			Ok(())
+		}
+	}
+
+	/// Internal helpers fn
+	impl<T: Config> Pallet<T> {
+		/// The account holding the bets
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account()
+		}
+
+		/// The account receiving the protocol fee
+		pub fn treasury_account_id() -> T::AccountId {
+			T::TreasuryPalletId::get().into_account()
+		}
+
+		/// Oracle price of a symbol, None if it was not updated in the last `MaxPriceAge` blocks
+		fn fresh_price(symbol: &Vec<u8>, block_number: T::BlockNumber) -> Option<SymbolPrice> {
+			let updated_at = T::SymbolPriceModule::price_updated_at(symbol.clone())?;
+			let age = block_number.saturated_into::<u64>().saturating_sub(updated_at);
+			if age > T::MaxPriceAge::get().saturated_into::<u64>() {
+				log::warn!("Stale price for {:?}: {} blocks old", symbol, age);
+				return None
+			}
+
+			T::SymbolPriceModule::get_price(symbol.clone())
+		}
+
+		fn start_round(symbol: &Vec<u8>, epoch: u32, block_number: T::BlockNumber) {
+			let lock_block = block_number + T::RoundDuration::get();
+			let round = Round::<T> {
+				epoch,
+				start_block: block_number,
+				lock_block,
+				close_block: lock_block + T::RoundDuration::get(),
+				lock_price: None,
+				close_price: None,
+				up_amount: Zero::zero(),
+				down_amount: Zero::zero(),
+				reward_base: Zero::zero(),
+				reward_amount: Zero::zero(),
+				status: RoundStatus::Open,
+			};
+
+			<Rounds<T>>::insert(symbol, epoch, round);
+			<CurrentEpoch<T>>::insert(symbol, epoch);
+
+			Self::deposit_event(Event::RoundStarted(symbol.clone(), epoch));
+		}
+
+		/// Record the lock price of an open round
+		/// The round is cancelled if the price is stale or one side has no bet
+		fn lock_round(symbol: &Vec<u8>, epoch: u32, block_number: T::BlockNumber) {
+			let mut round = match Self::rounds(symbol, epoch) {
+				Some(round) if round.status == RoundStatus::Open => round,
+				_ => return,
+			};
+
+			let lock_price = match Self::fresh_price(symbol, block_number) {
+				Some(price) if !round.up_amount.is_zero() && !round.down_amount.is_zero() => price,
+				_ => return Self::cancel_round(symbol, epoch),
+			};
+
+			round.lock_price = Some(lock_price);
+			round.status = RoundStatus::Locked;
+			<Rounds<T>>::insert(symbol, epoch, round);
+
+			Self::deposit_event(Event::RoundLocked(symbol.clone(), epoch, lock_price));
+		}
+
+		/// Record the close price of a locked round and send the protocol fee to the treasury
+		/// The round is cancelled if the price is stale or equals the lock price
+		fn close_round(symbol: &Vec<u8>, epoch: u32, block_number: T::BlockNumber) {
+			let mut round = match Self::rounds(symbol, epoch) {
+				Some(round) if round.status == RoundStatus::Locked => round,
+				_ => return,
+			};
+
+			let close_price = match Self::fresh_price(symbol, block_number) {
+				Some(price) if Some(price) != round.lock_price => price,
+				_ => return Self::cancel_round(symbol, epoch),
+			};
+			round.close_price = Some(close_price);
+
+			let total_amount = round.up_amount.saturating_add(round.down_amount);
+			let fee = T::ProtocolFee::get() * total_amount;
+			round.reward_amount = total_amount.saturating_sub(fee);
+			round.reward_base = match round.winner() {
+				Some(Position::Up) => round.up_amount,
+				_ => round.down_amount,
+			};
+			round.status = RoundStatus::Closed;
+			<Rounds<T>>::insert(symbol, epoch, round);
+
+			if !fee.is_zero() {
+				let res = T::Currency::transfer(
+					&Self::account_id(),
+					&Self::treasury_account_id(),
+					fee,
+					AllowDeath,
+				);
+				if let Err(e) = res {
+					log::error!("Error: can not transfer protocol fee {:?}", e);
+				}
+			}
+
+			Self::deposit_event(Event::RoundClosed(symbol.clone(), epoch, close_price));
+		}
+
+		/// Cancel an open or locked round, every bet can be claimed back
+		fn cancel_round(symbol: &Vec<u8>, epoch: u32) {
+			let cancelled = <Rounds<T>>::mutate(symbol, epoch, |round| match round {
+				Some(round)
+					if round.status == RoundStatus::Open ||
+						round.status == RoundStatus::Locked =>
+				{
+					round.status = RoundStatus::Cancelled;
+					true
+				},
+				_ => false,
+			});
+
+			if cancelled {
+				Self::deposit_event(Event::RoundCancelled(symbol.clone(), epoch));
+			}
+		}
+	}
+}
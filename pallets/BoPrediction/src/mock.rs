@@ -0,0 +1,156 @@
+use crate as pallet_bo_prediction;
+use frame_support::{parameter_types, traits::OnInitialize, PalletId};
+use frame_system as system;
+use pallet_symbol_price::{SymbolPrice, SymbolPriceInterface};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	Permill,
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		BoPredictionModule: pallet_bo_prediction::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type AccountStore = System;
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type WeightInfo = ();
+}
+
+thread_local! {
+	static PRICE: RefCell<Option<(SymbolPrice, u64)>> = RefCell::new(None);
+}
+
+/// Oracle price fed by the tests: (price, block of the update)
+pub struct MockSymbolPrice;
+
+impl SymbolPriceInterface for MockSymbolPrice {
+	fn get_price_at(_symbol: Vec<u8>, _unix_ts: Option<u64>) -> Option<SymbolPrice> {
+		PRICE.with(|p| p.borrow().map(|(price, _)| price))
+	}
+
+	fn get_price(symbol: Vec<u8>) -> Option<SymbolPrice> {
+		Self::get_price_at(symbol, None)
+	}
+
+	fn fetch_live_price(symbol: Vec<u8>) -> Option<SymbolPrice> {
+		Self::get_price_at(symbol, None)
+	}
+
+	fn price_updated_at(_symbol: Vec<u8>) -> Option<u64> {
+		PRICE.with(|p| p.borrow().map(|(_, updated_at)| updated_at))
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn set_price(_symbol: Vec<u8>, price: SymbolPrice) {
+		set_price(price)
+	}
+}
+
+/// Feed a new oracle price at the current block
+pub fn set_price(price: SymbolPrice) {
+	let block_number = System::block_number();
+	PRICE.with(|p| *p.borrow_mut() = Some((price, block_number)));
+}
+
+parameter_types! {
+	pub const BoPredictionPalletId: PalletId = PalletId(*b"BoPredic");
+	pub const TreasuryPalletId: PalletId = PalletId(*b"Treasury");
+	pub const RoundDuration: u64 = 10;
+	pub const ProtocolFee: Permill = Permill::from_percent(10);
+	pub const MaxPriceAge: u64 = 5;
+	pub const MinBet: u64 = 10;
+}
+
+impl pallet_bo_prediction::Config for Test {
+	type Event = Event;
+
+	type Currency = Balances;
+	type SymbolPriceModule = MockSymbolPrice;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type PalletId = BoPredictionPalletId;
+	type TreasuryPalletId = TreasuryPalletId;
+	type RoundDuration = RoundDuration;
+	type ProtocolFee = ProtocolFee;
+	type MaxPriceAge = MaxPriceAge;
+	type MinBet = MinBet;
+	type WeightInfo = ();
+}
+
+/// Move to block `n`, running the rounds scheduling of every block on the way
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		BoPredictionModule::on_initialize(System::block_number());
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 1_000), (2, 1_000)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		PRICE.with(|p| *p.borrow_mut() = None);
+	});
+	ext
+}
@@ -0,0 +1,117 @@
+use crate::{mock::*, Error, Position, RoundStatus};
+use frame_support::{assert_noop, assert_ok, traits::Currency};
+
+const SYMBOL: &[u8] = b"BTC_USDT";
+
+#[test]
+fn winners_share_the_pool_minus_protocol_fee() {
+	new_test_ext().execute_with(|| {
+		set_price(100);
+		assert_ok!(BoPredictionModule::start_market(Origin::root(), SYMBOL.to_vec()));
+
+		assert_ok!(BoPredictionModule::bet(Origin::signed(1), SYMBOL.to_vec(), Position::Up, 100));
+		assert_ok!(BoPredictionModule::bet(Origin::signed(2), SYMBOL.to_vec(), Position::Down, 300));
+
+		// round 0 is locked at block 11 and round 1 is open
+		run_to_block(10);
+		set_price(100);
+		run_to_block(11);
+		let round = BoPredictionModule::rounds(SYMBOL.to_vec(), 0).unwrap();
+		assert_eq!(round.status, RoundStatus::Locked);
+		assert_eq!(round.lock_price, Some(100));
+		assert_eq!(BoPredictionModule::current_epoch(SYMBOL.to_vec()), Some(1));
+
+		// round 0 is closed at block 21, Up wins
+		run_to_block(20);
+		set_price(120);
+		run_to_block(21);
+		let round = BoPredictionModule::rounds(SYMBOL.to_vec(), 0).unwrap();
+		assert_eq!(round.status, RoundStatus::Closed);
+		assert_eq!(round.winner(), Some(Position::Up));
+		assert_eq!(round.reward_amount, 360);
+		assert_eq!(Balances::free_balance(BoPredictionModule::treasury_account_id()), 40);
+
+		// round 1 had no bet
+		let round = BoPredictionModule::rounds(SYMBOL.to_vec(), 1).unwrap();
+		assert_eq!(round.status, RoundStatus::Cancelled);
+
+		assert_ok!(BoPredictionModule::claim(Origin::signed(1), SYMBOL.to_vec(), 0));
+		assert_eq!(Balances::free_balance(1), 1_260);
+		assert_noop!(
+			BoPredictionModule::claim(Origin::signed(1), SYMBOL.to_vec(), 0),
+			Error::<Test>::AlreadyClaimed
+		);
+		assert_noop!(
+			BoPredictionModule::claim(Origin::signed(2), SYMBOL.to_vec(), 0),
+			Error::<Test>::NotWinner
+		);
+	});
+}
+
+#[test]
+fn stale_price_cancels_round_and_refunds_bets() {
+	new_test_ext().execute_with(|| {
+		set_price(100);
+		assert_ok!(BoPredictionModule::start_market(Origin::root(), SYMBOL.to_vec()));
+
+		assert_ok!(BoPredictionModule::bet(Origin::signed(1), SYMBOL.to_vec(), Position::Up, 100));
+		assert_ok!(BoPredictionModule::bet(Origin::signed(2), SYMBOL.to_vec(), Position::Down, 300));
+
+		// the oracle did not update the price for 10 blocks
+		run_to_block(11);
+		let round = BoPredictionModule::rounds(SYMBOL.to_vec(), 0).unwrap();
+		assert_eq!(round.status, RoundStatus::Cancelled);
+
+		assert_ok!(BoPredictionModule::claim(Origin::signed(1), SYMBOL.to_vec(), 0));
+		assert_ok!(BoPredictionModule::claim(Origin::signed(2), SYMBOL.to_vec(), 0));
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_eq!(Balances::free_balance(2), 1_000);
+	});
+}
+
+#[test]
+fn bet_stays_claimable_if_the_transfer_fails() {
+	new_test_ext().execute_with(|| {
+		set_price(100);
+		assert_ok!(BoPredictionModule::start_market(Origin::root(), SYMBOL.to_vec()));
+		assert_ok!(BoPredictionModule::bet(Origin::signed(1), SYMBOL.to_vec(), Position::Up, 100));
+		assert_ok!(BoPredictionModule::stop_market(Origin::root(), SYMBOL.to_vec()));
+
+		let pallet_account = BoPredictionModule::account_id();
+		Balances::make_free_balance_be(&pallet_account, 0);
+		assert_noop!(
+			BoPredictionModule::claim(Origin::signed(1), SYMBOL.to_vec(), 0),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+
+		Balances::make_free_balance_be(&pallet_account, 100);
+		assert_ok!(BoPredictionModule::claim(Origin::signed(1), SYMBOL.to_vec(), 0));
+		assert_eq!(Balances::free_balance(1), 1_000);
+	});
+}
+
+#[test]
+fn bet_is_validated() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			BoPredictionModule::bet(Origin::signed(1), SYMBOL.to_vec(), Position::Up, 100),
+			Error::<Test>::MarketNotStarted
+		);
+
+		assert_ok!(BoPredictionModule::start_market(Origin::root(), SYMBOL.to_vec()));
+		assert_noop!(
+			BoPredictionModule::bet(Origin::signed(1), SYMBOL.to_vec(), Position::Up, 5),
+			Error::<Test>::BetTooSmall
+		);
+
+		assert_ok!(BoPredictionModule::bet(Origin::signed(1), SYMBOL.to_vec(), Position::Up, 100));
+		assert_noop!(
+			BoPredictionModule::bet(Origin::signed(1), SYMBOL.to_vec(), Position::Down, 100),
+			Error::<Test>::AlreadyBet
+		);
+		assert_noop!(
+			BoPredictionModule::claim(Origin::signed(1), SYMBOL.to_vec(), 0),
+			Error::<Test>::RoundNotFinished
+		);
+	});
+}
@@ -0,0 +1,107 @@
+//! Weights for pallet_bo_prediction
+//!
+//! HAND-WRITTEN PLACEHOLDER: these weights were not measured, they are estimated from the
+//! storage accesses of each call. They must be regenerated on the reference hardware with
+//! `scripts/benchmark.sh` before a production release, which runs:
+
+// ./target/release/node-template
+// benchmark
+// --chain=dev
+// --execution=wasm
+// --wasm-execution=compiled
+// --pallet=pallet_bo_prediction
+// --extrinsic=*
+// --steps=50
+// --repeat=20
+// --output=./pallets/BoPrediction/src/weights.rs
+// --template=./.maintain/frame-weight-template.hbs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_bo_prediction.
+pub trait WeightInfo {
+	fn start_market() -> Weight;
+	fn stop_market() -> Weight;
+	fn bet() -> Weight;
+	fn claim() -> Weight;
+}
+
+/// Weights for pallet_bo_prediction using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: BoPredictionModule ActiveMarkets (r:1 w:1)
+	// Storage: BoPredictionModule CurrentEpoch (r:1 w:1)
+	// Storage: BoPredictionModule Rounds (r:0 w:1)
+	fn start_market() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: BoPredictionModule ActiveMarkets (r:1 w:1)
+	// Storage: BoPredictionModule CurrentEpoch (r:1 w:0)
+	// Storage: BoPredictionModule Rounds (r:2 w:2)
+	fn stop_market() -> Weight {
+		(29_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: BoPredictionModule CurrentEpoch (r:1 w:0)
+	// Storage: BoPredictionModule Rounds (r:1 w:1)
+	// Storage: BoPredictionModule Bets (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn bet() -> Weight {
+		(46_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: BoPredictionModule Rounds (r:1 w:0)
+	// Storage: BoPredictionModule Bets (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn claim() -> Weight {
+		(42_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: BoPredictionModule ActiveMarkets (r:1 w:1)
+	// Storage: BoPredictionModule CurrentEpoch (r:1 w:1)
+	// Storage: BoPredictionModule Rounds (r:0 w:1)
+	fn start_market() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: BoPredictionModule ActiveMarkets (r:1 w:1)
+	// Storage: BoPredictionModule CurrentEpoch (r:1 w:0)
+	// Storage: BoPredictionModule Rounds (r:2 w:2)
+	fn stop_market() -> Weight {
+		(29_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: BoPredictionModule CurrentEpoch (r:1 w:0)
+	// Storage: BoPredictionModule Rounds (r:1 w:1)
+	// Storage: BoPredictionModule Bets (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn bet() -> Weight {
+		(46_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: BoPredictionModule Rounds (r:1 w:0)
+	// Storage: BoPredictionModule Bets (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn claim() -> Weight {
+		(42_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+}
@@ -0,0 +1,47 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Runtime API of the BoTrading pallet
+use codec::{Codec, Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::vec::Vec;
+
+/// Pause state of a pair
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct TradingStatus {
+	/// New orders of the pair are rejected
+	pub new_orders_paused: bool,
+	/// Expired orders are not settled
+	pub settlement_paused: bool,
+}
+
+pub use pallet_bo_trading::TraderStats;
+
+sp_api::decl_runtime_apis! {
+	/// - version 2: account_stats, pair_stats and leaderboard
+	#[api_version(2)]
+	pub trait BoTradingApi<AccountId, AssetId, Balance, PairId> where
+		AccountId: Codec,
+		AssetId: Codec,
+		Balance: Codec,
+		PairId: Codec,
+	{
+		/// Protocol fees of an asset collected in each period of [from_period, to_period]
+		/// A period is unix_ts / `FeePeriod`, eg: days since epoch with a 86400 seconds period
+		fn fees_collected(asset_id: AssetId, from_period: u64, to_period: u64) -> Vec<(u64, Balance)>;
+
+		/// Pause state of a pair
+		fn trading_status(pair_id: PairId) -> TradingStatus;
+
+		/// All time stats of an account in an asset
+		fn account_stats(account_id: AccountId, asset_id: AssetId) -> TraderStats<Balance>;
+
+		/// All time stats of the traders of a pair
+		fn pair_stats(pair_id: PairId) -> TraderStats<Balance>;
+
+		/// Top accounts of an asset by net P&L in a leaderboard period, the current one if None
+		/// A period is unix_ts / `LeaderboardPeriod`
+		fn leaderboard(asset_id: AssetId, period: Option<u64>) -> Vec<(AccountId, TraderStats<Balance>)>;
+	}
+}
@@ -0,0 +1,154 @@
+//! RPC interface of the BoTrading pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_bo_trading_rpc_runtime_api::{
+	BoTradingApi as BoTradingRuntimeApi, TraderStats, TradingStatus,
+};
+
+#[rpc]
+pub trait BoTradingApi<BlockHash, AccountId, AssetId, Balance, PairId> {
+	/// Protocol fees of an asset collected in each period of [from_period, to_period]
+	#[rpc(name = "boTrading_feesCollected")]
+	fn fees_collected(
+		&self,
+		asset_id: AssetId,
+		from_period: u64,
+		to_period: u64,
+		at: Option<BlockHash>,
+	) -> Result<Vec<(u64, Balance)>>;
+
+	/// Pause state of a pair
+	#[rpc(name = "boTrading_tradingStatus")]
+	fn trading_status(
+		&self,
+		pair_id: PairId,
+		at: Option<BlockHash>,
+	) -> Result<TradingStatus>;
+
+	/// All time stats of an account in an asset
+	#[rpc(name = "boTrading_accountStats")]
+	fn account_stats(
+		&self,
+		account_id: AccountId,
+		asset_id: AssetId,
+		at: Option<BlockHash>,
+	) -> Result<TraderStats<Balance>>;
+
+	/// All time stats of the traders of a pair
+	#[rpc(name = "boTrading_pairStats")]
+	fn pair_stats(&self, pair_id: PairId, at: Option<BlockHash>) -> Result<TraderStats<Balance>>;
+
+	/// Top accounts of an asset by net P&L in a leaderboard period, the current one if None
+	#[rpc(name = "boTrading_leaderboard")]
+	fn leaderboard(
+		&self,
+		asset_id: AssetId,
+		period: Option<u64>,
+		at: Option<BlockHash>,
+	) -> Result<Vec<(AccountId, TraderStats<Balance>)>>;
+}
+
+/// Implements the BoTradingApi RPC trait by calling into the runtime api.
+pub struct BoTrading<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> BoTrading<C, B> {
+	/// Create new `BoTrading` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error code of a failed runtime api call.
+const RUNTIME_ERROR: i64 = 1;
+
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Runtime error".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+impl<C, Block, AccountId, AssetId, Balance, PairId>
+	BoTradingApi<<Block as BlockT>::Hash, AccountId, AssetId, Balance, PairId>
+	for BoTrading<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: BoTradingRuntimeApi<Block, AccountId, AssetId, Balance, PairId>,
+	AccountId: Codec,
+	AssetId: Codec,
+	Balance: Codec,
+	PairId: Codec,
+{
+	fn fees_collected(
+		&self,
+		asset_id: AssetId,
+		from_period: u64,
+		to_period: u64,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(u64, Balance)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.fees_collected(&at, asset_id, from_period, to_period)
+			.map_err(runtime_error_into_rpc_err)
+	}
+
+	fn trading_status(
+		&self,
+		pair_id: PairId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<TradingStatus> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.trading_status(&at, pair_id).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn account_stats(
+		&self,
+		account_id: AccountId,
+		asset_id: AssetId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<TraderStats<Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.account_stats(&at, account_id, asset_id).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn pair_stats(
+		&self,
+		pair_id: PairId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<TraderStats<Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.pair_stats(&at, pair_id).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn leaderboard(
+		&self,
+		asset_id: AssetId,
+		period: Option<u64>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(AccountId, TraderStats<Balance>)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.leaderboard(&at, asset_id, period).map_err(runtime_error_into_rpc_err)
+	}
+}
@@ -1,20 +1,264 @@
-//! Benchmarking setup for pallet-template
+//! Benchmarking setup for pallet-bo-trading
 
 use super::*;
 
 #[allow(unused)]
-use crate::Pallet as Template;
-use frame_benchmarking::{benchmarks, whitelisted_caller};
+use crate::Pallet as BoTrading;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::{
+	sp_runtime::traits::Hash,
+	traits::{
+		tokens::fungibles::{Create, Mutate},
+		UnixTime,
+	},
+	BoundedVec,
+};
 use frame_system::RawOrigin;
+use pallet_bo_liquidity::BoLiquidityInterface;
+use pallet_symbol_price::{OnNewPrice, SymbolPriceInterface};
+use sp_std::vec;
+
+const SEED: u32 = 0;
+/// Asset of the benchmarked pair
+const ASSET: u32 = 1;
+/// Asset of the pools the benchmarked order can not be placed in
+const OTHER_ASSET: u32 = 2;
+/// Funds of a pool, enough to pay a winning order
+const POOL_FUNDS: u32 = 1_000_000_000;
+/// Stake of the benchmarked orders
+const STAKE: u32 = 1_000;
+/// Maximum number of orders of the sender before the benchmarked call
+const MAX_USER_ORDERS: u32 = 1_000;
+const PAIR: PairId = 0;
+const TIMEFRAME: u64 = 60;
+const OPEN_PRICE: u32 = 40_000;
+/// Price crossing the barriers and the trigger prices of the `on_new_price` benchmark
+const CROSSED_PRICE: u32 = OPEN_PRICE + 100;
+
+/// Mint `amount` of `asset_id` to `who`, the asset is created if missing
+///
+/// The assets of BoTrading and BoLiquidity are the same pallet in the runtime
+fn fund<T: Config>(asset_id: u32, who: &T::AccountId, amount: u32)
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	AssetIdOf<T>: From<u32>,
+{
+	// fails if the asset already exists
+	let _ = T::Assets::create(asset_id.into(), who.clone(), true, 1u32.into());
+	T::Assets::mint_into(asset_id.into(), who, amount.into()).expect("the asset exists; qed");
+}
+
+/// Register an enabled BTC_USDT pair of `ASSET` priced at `OPEN_PRICE`
+fn create_pair<T: Config>()
+where
+	AssetIdOf<T>: From<u32>,
+{
+	<Pairs<T>>::insert(
+		PAIR,
+		TradingPair::<T> {
+			base: b"BTC".to_vec(),
+			quote: b"USDT".to_vec(),
+			oracle_symbol: b"BTC_USDT".to_vec(),
+			asset_id: ASSET.into(),
+			timeframes: BoundedVec::try_from(vec![TIMEFRAME]).expect("one timeframe; qed"),
+			min_stake: 1u32.into(),
+			max_stake: POOL_FUNDS.into(),
+			enabled: true,
+		},
+	);
+	<NextPairId<T>>::put(PAIR + 1);
+	T::SymbolPriceModule::set_price(b"BTC_USDT".to_vec(), OPEN_PRICE.into());
+}
+
+/// Create `count` pools, only the first one is funded with `ASSET`
+///
+/// Picking a pool starts after the last picked one: every pool is visited before the first
+fn create_pools<T: Config>(count: u32)
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	AssetIdOf<T>: From<u32>,
+{
+	for i in 0..count {
+		let admin: T::AccountId = account("admin", i, SEED);
+		let asset_id = if i == 0 { ASSET } else { OTHER_ASSET };
+		fund::<T>(asset_id, &admin, POOL_FUNDS.saturating_mul(2));
+		T::BoLiquidity::create_pool(admin, asset_id.into(), POOL_FUNDS.into())
+			.expect("below MaxPools; qed");
+	}
+}
+
+/// Give `who` `count` orders, only their ids are stored
+fn create_user_orders<T: Config>(who: &T::AccountId, count: u32) {
+	for i in 0..count {
+		<UserOrders<T>>::append(who, T::Hashing::hash_of(&i));
+	}
+}
+
+/// A trader able to place an order of `STAKE`, with `orders` orders already
+fn trader<T: Config>(orders: u32) -> T::AccountId
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	AssetIdOf<T>: From<u32>,
+{
+	let caller: T::AccountId = whitelisted_caller();
+	fund::<T>(ASSET, &caller, STAKE.saturating_mul(10));
+	create_user_orders::<T>(&caller, orders);
+	caller
+}
+
+/// A funded account able to place orders of `STAKE`
+fn funded<T: Config>(name: &'static str, index: u32) -> T::AccountId
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	AssetIdOf<T>: From<u32>,
+{
+	let who: T::AccountId = account(name, index, SEED);
+	fund::<T>(ASSET, &who, STAKE.saturating_mul(10));
+	who
+}
+
+/// The earliest expiry of an order placed now
+fn expiry<T: Config>() -> u64 {
+	BoTrading::<T>::next_expiry(T::TimeProvider::now().as_secs(), TIMEFRAME)
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
-		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+	where_clause {
+		where
+			T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+			AssetIdOf<T>: From<u32>,
+	}
+
+	place_order {
+		let p in 1 .. T::MaxPools::get();
+		let u in 0 .. MAX_USER_ORDERS;
+		create_pools::<T>(p);
+		create_pair::<T>();
+		let caller = trader::<T>(u);
+		let expired_at = expiry::<T>();
+	}: _(RawOrigin::Signed(caller.clone()), PAIR, TradeType::Call, STAKE.into(), expired_at)
+	verify {
+		assert_eq!(<UserOrders<T>>::get(&caller).len() as u32, u + 1);
+	}
+
+	close_order {
+		let u in 0 .. MAX_USER_ORDERS;
+		create_pools::<T>(1);
+		create_pair::<T>();
+		let caller = trader::<T>(u);
+		BoTrading::<T>::place_order(
+			RawOrigin::Signed(caller.clone()).into(),
+			PAIR,
+			TradeType::Call,
+			STAKE.into(),
+			expiry::<T>(),
+		)
+		.expect("the order is valid; qed");
+		let order_id = *<UserOrders<T>>::get(&caller).last().expect("the order is placed; qed");
+		// the clock of the runtime can not be moved: the order expires now instead
+		let now = T::TimeProvider::now().as_secs();
+		<Orders<T>>::mutate(&order_id, |order| {
+			if let Some(order) = order {
+				order.expired_at = now;
+			}
+		});
+		// a winning order pays the stake and the profit out of the pool
+		<SettlementPrices<T>>::insert(PAIR, now, SymbolPrice::from(OPEN_PRICE + 1));
+	}: _(RawOrigin::None, 0u32.into(), order_id)
+	verify {
+		assert_eq!(<Orders<T>>::get(&order_id).unwrap().status, OrderStatus::Win);
+	}
+
+	copy_order {
+		let f in 0 .. T::MaxFollowers::get();
+		create_pools::<T>(1);
+		create_pair::<T>();
+		let leader: T::AccountId = account("leader", 0, SEED);
+		let budget = T::MinCopyBudget::get().max(STAKE.into());
+		for i in 0..f {
+			let follower = funded::<T>("follower", i);
+			BoTrading::<T>::follow(
+				RawOrigin::Signed(follower).into(),
+				leader.clone(),
+				CopyStake::Fixed(STAKE.into()),
+				budget,
+				0,
+			)
+			.expect("the budget is high enough; qed");
+		}
+		let leader_order_id = T::Hashing::hash_of(&leader);
+		let expired_at = expiry::<T>();
+	}: {
+		BoTrading::<T>::copy_order(
+			&leader,
+			leader_order_id,
+			PAIR,
+			TradeType::Call,
+			STAKE.into(),
+			expired_at,
+		);
+	}
+	verify {
+		for i in 0..f {
+			let follower: T::AccountId = account("follower", i, SEED);
+			assert_eq!(<UserOrders<T>>::get(&follower).len(), 1);
+		}
+	}
+
+	// b: watched barrier orders, all hit
+	// c: pending conditional orders not triggered
+	// t: triggered conditional orders
+	// e: expiries waiting for their settlement price, all past
+	on_new_price {
+		let b in 0 .. T::MaxBarrierOrders::get();
+		let c in 0 .. T::MaxConditionalOrders::get().saturating_sub(T::MaxTriggersPerPrice::get());
+		let t in 0 .. T::MaxTriggersPerPrice::get();
+		let e in 0 .. T::MaxPendingExpiries::get();
+		create_pools::<T>(1);
+		create_pair::<T>();
+		for i in 0..b {
+			let trader = funded::<T>("barrier", i);
+			BoTrading::<T>::place_order(
+				RawOrigin::Signed(trader).into(),
+				PAIR,
+				TradeType::Touch { barrier: CROSSED_PRICE.into() },
+				STAKE.into(),
+				expiry::<T>(),
+			)
+			.expect("the order is valid; qed");
+		}
+		let valid_until = T::TimeProvider::now().as_secs().saturating_add(TIMEFRAME);
+		for i in 0..c + t {
+			let trader = funded::<T>("conditional", i);
+			// the first `t` orders are crossed by the new price
+			let trigger_price = if i < t { CROSSED_PRICE } else { OPEN_PRICE.saturating_mul(2) };
+			BoTrading::<T>::place_conditional_order(
+				RawOrigin::Signed(trader).into(),
+				PAIR,
+				TradeType::Call,
+				STAKE.into(),
+				TIMEFRAME,
+				trigger_price.into(),
+				TriggerDirection::Above,
+				valid_until,
+			)
+			.expect("the conditional order is valid; qed");
+		}
+		let now = T::TimeProvider::now().as_secs();
+		<PendingExpiries<T>>::mutate(|expiries| {
+			expiries.extend((0..e).map(|i| (PAIR, now.saturating_sub(u64::from(i)))))
+		});
+		T::SymbolPriceModule::set_price(b"BTC_USDT".to_vec(), CROSSED_PRICE.into());
+	}: {
+		BoTrading::<T>::on_new_price(b"BTC_USDT".to_vec(), CROSSED_PRICE.into());
+	}
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert!(<BarrierOrders<T>>::get().is_empty());
+		assert_eq!(<PendingConditionalOrders<T>>::get().len() as u32, c);
+		if e > 0 {
+			assert_eq!(<SettlementPrices<T>>::get(PAIR, now), Some(CROSSED_PRICE.into()));
+		}
 	}
 
-	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
+	impl_benchmark_test_suite!(BoTrading, crate::mock::new_test_ext(), crate::mock::Test);
 }
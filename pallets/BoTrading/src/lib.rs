@@ -5,14 +5,18 @@
 /// <https://docs.substrate.io/v3/runtime/frame>
 pub use pallet::*;
 
-// #[cfg(test)]
-// mod mock;
-//
-// #[cfg(test)]
-// mod tests;
-//
-// #[cfg(feature = "runtime-benchmarks")]
-// mod benchmarking;
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -25,16 +29,20 @@ pub mod pallet {
 		pallet_prelude::*,
 		sp_runtime::traits::AtLeast32BitUnsigned,
 		sp_runtime::traits::Hash, // support T::Hashing
-		sp_runtime::SaturatedConversion,
+		sp_runtime::traits::{
+			AccountIdConversion, IdentifyAccount, IntegerSquareRoot, MaybeSerializeDeserialize,
+			Saturating, Verify, Zero,
+		},
+		sp_runtime::{PerThing, Permill, SaturatedConversion, TransactionOutcome},
+		storage::with_transaction,
 		traits::{
-			Currency, ExistenceRequirement,
-			ExistenceRequirement::{AllowDeath, KeepAlive},
+			tokens::fungibles::{Inspect, Transfer},
 			Randomness,
 		},
+		PalletId,
 	};
 	use frame_system::pallet_prelude::*;
 	use scale_info::prelude::ops::Add;
-	use scale_info::prelude::string::String; // support String
 	use scale_info::prelude::vec::Vec;
 	use scale_info::TypeInfo; // support Vec
 
@@ -43,8 +51,10 @@ pub mod pallet {
 	#[cfg(feature = "std")]
 	use frame_support::serde::{Deserialize, Serialize};
 	use frame_system::offchain::{CreateSignedTransaction, SubmitTransaction};
-	use pallet_bo_liquidity::BoLiquidityInterface;
-	use pallet_symbol_price::SymbolPriceInterface;
+	use pallet_bo_liquidity::{BoLiquidityInterface, PositionSide};
+	use pallet_symbol_price::{OnNewPrice, SymbolPriceInterface};
+
+	use crate::weights::WeightInfo;
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
@@ -52,11 +62,11 @@ pub mod pallet {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
-		/// The Currency handler for the BoTrading pallet.
-		type Currency: Currency<Self::AccountId>;
+		/// Fungible assets the orders are denominated in, eg: USDT of pallet_assets
+		type Assets: Inspect<Self::AccountId> + Transfer<Self::AccountId>;
 
 		/// Loose coupling with BoLiquidity pallet
-		type BoLiquidity: BoLiquidityInterface<Self::AccountId>;
+		type BoLiquidity: BoLiquidityInterface<Self::AccountId, AssetIdOf<Self>, BalanceOf<Self>>;
 
 		/// Loose coupling with SymbolPrice pallet
 		type SymbolPriceModule: SymbolPriceInterface;
@@ -73,35 +83,374 @@ pub mod pallet {
 		/// blocks.
 		#[pallet::constant]
 		type UnsignedInterval: Get<Self::BlockNumber>;
+
+		/// Final window (in seconds) before `expired_at` in which an order can not be sold back
+		#[pallet::constant]
+		type SellBackLockPeriod: Get<u64>;
+
+		/// Spread kept by the pool on every sell-back quote
+		#[pallet::constant]
+		type SellBackSpread: Get<Permill>;
+
+		/// Expected price move of a symbol over 1 minute, used to estimate the win probability
+		/// of an open order when quoting its sell-back value
+		#[pallet::constant]
+		type PriceVolatility: Get<Permill>;
+
+		/// Number of seconds after created_at in which the order owner can still cancel it
+		#[pallet::constant]
+		type CancelWindow: Get<u64>;
+
+		/// Fee taken from the stake when an order is cancelled, it stays in the pool
+		#[pallet::constant]
+		type CancelFee: Get<Permill>;
+
+		/// Origin allowed to manage trading settings, eg: allowed timeframes of a pair
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Order durations (in seconds) allowed for a pair that has no timeframes configured
+		#[pallet::constant]
+		type DefaultTimeframes: Get<BoundedVec<u64, Self::MaxTimeframes>>;
+
+		/// Number of seconds between two price updates of the oracle
+		#[pallet::constant]
+		type OracleUpdateInterval: Get<u64>;
+
+		/// An order must expire at least `MinLeadIntervals` oracle updates after it was placed
+		#[pallet::constant]
+		type MinLeadIntervals: Get<u64>;
+
+		/// Maximum number of open Touch / No-Touch orders checked on every price update
+		#[pallet::constant]
+		type MaxBarrierOrders: Get<u32>;
+
+		/// Payout rate change (in percent) for a fully one-sided book
+		/// The crowded side get a lower payout and the thin side a higher payout, linearly
+		#[pallet::constant]
+		type ImbalanceSensitivity: Get<u32>;
+
+		/// Lowest payout rate (in percent) an order can get after the imbalance adjustment
+		#[pallet::constant]
+		type MinPayoutRate: Get<u32>;
+
+		/// Highest payout rate (in percent) an order can get after the imbalance adjustment
+		#[pallet::constant]
+		type MaxPayoutRate: Get<u32>;
+
+		/// Account holding the escrowed stakes until they are settled, eg: of the conditional
+		/// orders, the peer-to-peer orders are held by its `p2p` sub-account
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// Maximum number of unmatched peer-to-peer orders waiting on one side of a book
+		#[pallet::constant]
+		type MaxRestingOrders: Get<u32>;
+
+		/// Account receiving the protocol fees
+		#[pallet::constant]
+		type TreasuryPalletId: Get<PalletId>;
+
+		/// Upper bound of each protocol fee, in basis points
+		#[pallet::constant]
+		type MaxProtocolFeeBps: Get<u32>;
+
+		/// Length (in seconds) of a fee accounting period, eg: 86400 for daily reports
+		#[pallet::constant]
+		type FeePeriod: Get<u64>;
+
+		/// Seconds an account can not trade after reaching its `max_loss_streak`
+		#[pallet::constant]
+		type LossStreakCooldown: Get<u64>;
+
+		/// Share (in basis points) of the protocol fees of a settled order paid to its keeper
+		#[pallet::constant]
+		type KeeperRewardBps: Get<u32>;
+
+		/// Maximum number of orders settled by one `settle_expired` call
+		#[pallet::constant]
+		type MaxSettleBatch: Get<u32>;
+
+		/// Maximum number of orders placed by one `place_orders` call
+		#[pallet::constant]
+		type MaxOrdersPerBatch: Get<u32>;
+
+		/// Maximum number of conditional orders waiting for their trigger price
+		#[pallet::constant]
+		type MaxConditionalOrders: Get<u32>;
+
+		/// Maximum number of followers of a leader, bounds the orders copied by one `place_order`
+		#[pallet::constant]
+		type MaxFollowers: Get<u32>;
+
+		/// Upper bound of the fee a leader takes from the profit of a copied order, in basis points
+		#[pallet::constant]
+		type MaxLeaderFeeBps: Get<u32>;
+
+		/// Minimum budget of a copy subscription, a follower can not take a leader slot for free
+		#[pallet::constant]
+		type MinCopyBudget: Get<BalanceOf<Self>>;
+
+		/// Length (in seconds) of a leaderboard period, eg: 604800 for weekly leaderboards
+		#[pallet::constant]
+		type LeaderboardPeriod: Get<u64>;
+
+		/// Number of accounts ranked in a leaderboard
+		#[pallet::constant]
+		type LeaderboardSize: Get<u32>;
+
+		/// Maximum number of participants of a tournament
+		#[pallet::constant]
+		type MaxParticipants: Get<u32>;
+
+		/// Maximum number of prized ranks of a tournament
+		#[pallet::constant]
+		type MaxPrizeRanks: Get<u32>;
+
+		/// Maximum number of pairs of a tournament
+		#[pallet::constant]
+		type MaxTournamentPairs: Get<u32>;
+
+		/// Time (in seconds) after the end of a tournament after which its orders not settled yet
+		/// can be refunded, so that the tournament can always finish
+		#[pallet::constant]
+		type TournamentGracePeriod: Get<u64>;
+
+		/// Signature of the bonus vouchers, eg: MultiSignature
+		type VoucherSignature: Verify<Signer = Self::VoucherSigner> + Parameter;
+
+		/// Signer of the bonus vouchers, identified by an account set with `set_voucher_issuer`
+		type VoucherSigner: IdentifyAccount<AccountId = Self::AccountId>;
+
+		/// Maximum number of bonus credit grants of an account in an asset
+		#[pallet::constant]
+		type MaxCreditGrants: Get<u32>;
+
+		/// Maximum number of referrer tiers sharing the fees of an order
+		#[pallet::constant]
+		type MaxReferralTiers: Get<u32>;
+
+		/// Maximum number of liquidity pools, the `MaxPools` of BoLiquidity
+		/// Picking the pool of an order may visit all of them
+		#[pallet::constant]
+		type MaxPools: Get<u32>;
+
+		/// Maximum number of timeframes of a pair
+		#[pallet::constant]
+		type MaxTimeframes: Get<u32>;
+
+		/// Maximum number of conditional orders triggered by one price update
+		/// The other crossed conditional orders are triggered by the next prices
+		#[pallet::constant]
+		type MaxTriggersPerPrice: Get<u32>;
+
+		/// Maximum number of (pair, expiry) of open orders waiting for their settlement price
+		#[pallet::constant]
+		type MaxPendingExpiries: Get<u32>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
+	/// Base priority of the unsigned `close_order` transactions
+	const UNSIGNED_TXS_PRIORITY: u64 = 1 << 18;
+
+	/// The current storage version
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/*
 	Add Order info
 	 */
-	type AccountOf<T> = <T as frame_system::Config>::AccountId;
-	type BalanceOf<T> =
-		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub(crate) type AccountOf<T> = <T as frame_system::Config>::AccountId;
+	pub(crate) type BalanceOf<T> =
+		<<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+	pub(crate) type AssetIdOf<T> =
+		<<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
 
-	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
 	#[scale_info(skip_type_params(T))]
 	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 	pub enum TradeType {
 		Call,
 		Put,
+		/// Win if the close price is higher than the strike
+		Higher { strike: SymbolPrice },
+		/// Win if the close price is lower than the strike
+		Lower { strike: SymbolPrice },
+		/// Win if the price touch the barrier at any time before expired_at
+		Touch { barrier: SymbolPrice },
+		/// Win if the price never touch the barrier before expired_at
+		NoTouch { barrier: SymbolPrice },
+		/// Win if the close price is inside the band [low, high]
+		InRange { low: SymbolPrice, high: SymbolPrice },
+		/// Win if the close price is outside the band [low, high]
+		OutRange { low: SymbolPrice, high: SymbolPrice },
+	}
+
+	impl TradeType {
+		/// Whether `price` is in the money, compared to the strike or to the open price
+		pub fn is_in_the_money(&self, open_price: SymbolPrice, price: SymbolPrice) -> bool {
+			match *self {
+				TradeType::Call => price > open_price,
+				TradeType::Put => price < open_price,
+				TradeType::Higher { strike } => price > strike,
+				TradeType::Lower { strike } => price < strike,
+				TradeType::Touch { barrier } => Self::touches(barrier, open_price, price),
+				TradeType::NoTouch { barrier } => !Self::touches(barrier, open_price, price),
+				TradeType::InRange { low, high } => low <= price && price <= high,
+				TradeType::OutRange { low, high } => price < low || high < price,
+			}
+		}
+
+		/// Whether `price` reached the barrier, coming from the open price side
+		fn touches(barrier: SymbolPrice, open_price: SymbolPrice, price: SymbolPrice) -> bool {
+			if barrier >= open_price {
+				price >= barrier
+			} else {
+				price <= barrier
+			}
+		}
+
+		/// Side of the pool book this order belongs to, None for non directional orders
+		pub fn side(&self) -> Option<PositionSide> {
+			match self {
+				TradeType::Call | TradeType::Higher { .. } => Some(PositionSide::Call),
+				TradeType::Put | TradeType::Lower { .. } => Some(PositionSide::Put),
+				_ => None,
+			}
+		}
+
+		/// Touch / No-Touch orders must observe every price update until expired_at
+		pub fn is_path_dependent(&self) -> bool {
+			matches!(self, TradeType::Touch { .. } | TradeType::NoTouch { .. })
+		}
+
+		/// Only orders settled on a single reference price can be quoted for a sell-back
+		pub fn can_sell_back(&self) -> bool {
+			matches!(
+				self,
+				TradeType::Call | TradeType::Put | TradeType::Higher { .. } | TradeType::Lower { .. }
+			)
+		}
+
+		/// The barrier must differ from the open price, and the band must not be empty
+		pub fn is_valid(&self, open_price: SymbolPrice) -> bool {
+			match *self {
+				TradeType::Touch { barrier } | TradeType::NoTouch { barrier } =>
+					barrier != open_price,
+				TradeType::InRange { low, high } | TradeType::OutRange { low, high } => low < high,
+				_ => true,
+			}
+		}
+
+		/// Price the order is settled against
+		pub fn reference_price(&self, open_price: SymbolPrice) -> SymbolPrice {
+			match *self {
+				TradeType::Higher { strike } | TradeType::Lower { strike } => strike,
+				_ => open_price,
+			}
+		}
+
+		/// How far the strike is out of the money at `price`, in basis points
+		/// Negative mean the strike is in the money, None if this is not a strike order
+		pub fn strike_distance(&self, price: SymbolPrice) -> Option<i32> {
+			let (strike, otm_direction) = match *self {
+				TradeType::Higher { strike } => (strike, 1i128),
+				TradeType::Lower { strike } => (strike, -1i128),
+				_ => return None,
+			};
+			if price == 0 {
+				return None
+			}
+
+			let distance =
+				(strike as i128 - price as i128).saturating_mul(10_000) / price as i128 * otm_direction;
+			Some(distance.clamp(i32::MIN as i128, i32::MAX as i128) as i32)
+		}
+	}
+
+	/// Id of a pair of the `Pairs` registry
+	pub type PairId = u32;
+
+	/// User will trade on these pair, managed by `AdminOrigin`
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	#[cfg_attr(
+		feature = "std",
+		serde(bound(
+			serialize = "AssetIdOf<T>: Serialize, BalanceOf<T>: Serialize",
+			deserialize = "AssetIdOf<T>: Deserialize<'de>, BalanceOf<T>: Deserialize<'de>"
+		))
+	)]
+	pub struct TradingPair<T: Config> {
+		/// eg: BTC
+		pub base: Vec<u8>,
+		/// eg: USDT
+		pub quote: Vec<u8>,
+		/// The symbol used to query this pair's price from SymbolPrice pallet, eg: BTC_USDT
+		/// It can not be changed once the pair is added: open orders liabilities are keyed by it
+		pub oracle_symbol: Vec<u8>,
+		/// Asset the stakes and payouts of the pair are denominated in
+		pub asset_id: AssetIdOf<T>,
+		/// Allowed order durations (in seconds), eg: [60, 300, 900, 3600]
+		/// Empty mean the pair use `T::DefaultTimeframes`
+		#[cfg_attr(feature = "std", serde(with = "bounded_timeframes"))]
+		pub timeframes: BoundedVec<u64, T::MaxTimeframes>,
+		/// Stake range of an order, in the smallest unit of `asset_id`
+		pub min_stake: BalanceOf<T>,
+		pub max_stake: BalanceOf<T>,
+		/// Disabled pairs reject new orders, their open orders still settle
+		pub enabled: bool,
+	}
+
+	/// (De)serialize the timeframes of a pair as a plain list, eg: in the chain spec
+	#[cfg(feature = "std")]
+	mod bounded_timeframes {
+		use frame_support::{
+			serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer},
+			traits::Get,
+			BoundedVec,
+		};
+
+		pub fn serialize<S: Serializer, B: Get<u32>>(
+			timeframes: &BoundedVec<u64, B>,
+			serializer: S,
+		) -> Result<S::Ok, S::Error> {
+			timeframes.as_slice().serialize(serializer)
+		}
+
+		pub fn deserialize<'de, D: Deserializer<'de>, B: Get<u32>>(
+			deserializer: D,
+		) -> Result<BoundedVec<u64, B>, D::Error> {
+			let timeframes = Vec::<u64>::deserialize(deserializer)?;
+			BoundedVec::try_from(timeframes).map_err(|_| D::Error::custom("too many timeframes"))
+		}
+	}
+
+	/// What happens at expiry to the open orders of a paused pair
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum PausePolicy {
+		/// Settle them as usual against the close price
+		Settle,
+		/// Refund their whole stake, eg: when the oracle price can not be trusted
+		Refund,
 	}
 
-	/// User will trade on these pair
+	/// Who the order is traded against
 	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
 	#[scale_info(skip_type_params(T))]
 	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-	pub enum CurrencyPair {
-		BtcUsdt,
-		DotUsdc,
-		BtcEth,
+	pub enum MarketMode {
+		/// Against a liquidity pool chosen by BoLiquidity pallet
+		Pool,
+		/// Against opposite orders of the same pair and expiry, the pool can take the residual
+		PeerToPeer,
 	}
 
 	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
@@ -118,37 +467,415 @@ pub mod pallet {
 		/// Completed = Win / Lose
 		Win,
 		Lose,
+		/// The order was sold back to the pool before expired_at
+		Sold,
+		/// The order was cancelled by its owner within the cancel window
+		Cancelled,
+		/// Nothing was traded, the stake was refunded at expiry
+		Refunded,
 	}
 
 	pub type SymbolPrice = u128;
 
+	/// Daily counters of `AccountRisk` roll over every `SECS_PER_DAY` seconds
+	pub const SECS_PER_DAY: u64 = 86_400;
+
+	/// Trading limits of an account, None mean no limit
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct RiskLimits<T: Config> {
+		/// Maximum number of open orders
+		pub max_open_orders: Option<u32>,
+		/// Maximum total stake of the open orders
+		pub max_open_stake: Option<BalanceOf<T>>,
+		/// Maximum loss within a day
+		/// The stake of the open orders and of a new order count as a potential loss
+		pub daily_loss_cap: Option<BalanceOf<T>>,
+		/// Number of consecutive losing orders starting a `LossStreakCooldown`
+		pub max_loss_streak: Option<u32>,
+	}
+
+	impl<T: Config> Default for RiskLimits<T> {
+		fn default() -> Self {
+			Self {
+				max_open_orders: None,
+				max_open_stake: None,
+				daily_loss_cap: None,
+				max_loss_streak: None,
+			}
+		}
+	}
+
+	impl<T: Config> RiskLimits<T> {
+		/// The tightest of two limits, field by field
+		pub fn tightest(&self, other: &Self) -> Self {
+			fn min_of<V: Ord + Copy>(a: Option<V>, b: Option<V>) -> Option<V> {
+				match (a, b) {
+					(Some(a), Some(b)) => Some(a.min(b)),
+					(a, None) => a,
+					(None, b) => b,
+				}
+			}
+
+			Self {
+				max_open_orders: min_of(self.max_open_orders, other.max_open_orders),
+				max_open_stake: min_of(self.max_open_stake, other.max_open_stake),
+				daily_loss_cap: min_of(self.daily_loss_cap, other.daily_loss_cap),
+				max_loss_streak: min_of(self.max_loss_streak, other.max_loss_streak),
+			}
+		}
+
+		/// Every limit of `self` is at least as tight as the one of `current`
+		pub fn is_tighter_than(&self, current: &Self) -> bool {
+			fn tighter<V: Ord>(new: &Option<V>, current: &Option<V>) -> bool {
+				match (new, current) {
+					(_, None) => true,
+					(Some(new), Some(current)) => new <= current,
+					(None, Some(_)) => false,
+				}
+			}
+
+			tighter(&self.max_open_orders, &current.max_open_orders) &&
+				tighter(&self.max_open_stake, &current.max_open_stake) &&
+				tighter(&self.daily_loss_cap, &current.daily_loss_cap) &&
+				tighter(&self.max_loss_streak, &current.max_loss_streak)
+		}
+	}
+
+	/// Trading counters of an account checked against its `RiskLimits`
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct AccountRisk<T: Config> {
+		pub open_orders: u32,
+		pub open_stake: BalanceOf<T>,
+		/// Day of `day_loss`: unix_ts / SECS_PER_DAY
+		pub day: u64,
+		/// Gross loss of the orders closed during `day`, the wins do not offset it
+		pub day_loss: BalanceOf<T>,
+		/// Consecutive losing orders
+		pub loss_streak: u32,
+		/// unix_ts until the account can not trade after a loss streak
+		pub cooldown_until: u64,
+		/// unix_ts until the account excluded itself, or was excluded by an operator
+		pub excluded_until: u64,
+	}
+
+	impl<T: Config> Default for AccountRisk<T> {
+		fn default() -> Self {
+			Self {
+				open_orders: 0,
+				open_stake: Zero::zero(),
+				day: 0,
+				day_loss: Zero::zero(),
+				loss_streak: 0,
+				cooldown_until: 0,
+				excluded_until: 0,
+			}
+		}
+	}
+
+	impl<T: Config> AccountRisk<T> {
+		/// Loss of the day `unix_ts` belongs to
+		pub fn loss_on(&self, unix_ts: u64) -> BalanceOf<T> {
+			if self.day == unix_ts / SECS_PER_DAY {
+				self.day_loss
+			} else {
+				Zero::zero()
+			}
+		}
+	}
+
+	/// Protocol fees in basis points, 100 bps = 1%
+	#[derive(Clone, Encode, Decode, Default, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct ProtocolFee {
+		/// Charged to the user on top of the stake of every new order
+		pub stake_fee_bps: u32,
+		/// Charged to the pool on the stake it wins from a losing order
+		pub profit_fee_bps: u32,
+	}
+
+	/// A fill of a peer-to-peer order against an opposite order
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct OrderMatch<T: Config> {
+		/// The opposite order
+		pub counterparty: T::Hash,
+		pub amount: BalanceOf<T>,
+		/// Both sides of the fill are settled against this price: the open price of the later order
+		pub price: SymbolPrice,
+		/// What the fill paid to this order, once settled with the counterparty
+		pub settled: Option<BalanceOf<T>>,
+	}
+
+	/// One leg of a `place_orders` batch, same parameters as `place_order`
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct OrderRequest<T: Config> {
+		pub pair_id: PairId,
+		pub trade_type: TradeType,
+		pub volume_in_unit: BalanceOf<T>,
+		pub expired_at: u64,
+	}
+
+	/// Side of the trigger price a conditional order waits for
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum TriggerDirection {
+		/// price >= trigger_price
+		Above,
+		/// price <= trigger_price
+		Below,
+	}
+
+	impl TriggerDirection {
+		pub fn is_triggered(&self, trigger_price: SymbolPrice, price: SymbolPrice) -> bool {
+			match self {
+				TriggerDirection::Above => price >= trigger_price,
+				TriggerDirection::Below => price <= trigger_price,
+			}
+		}
+	}
+
+	/// An order opened when the price of its pair crosses a level,
+	/// eg: open a 5 minutes Call on BTC if the price drops below X within the next hour
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct ConditionalOrder<T: Config> {
+		pub id: T::Hash,
+		pub user_id: AccountOf<T>,
+		pub pair_id: PairId,
+		pub trade_type: TradeType,
+		pub volume_in_unit: BalanceOf<T>,
+		/// Duration (in seconds) of the order once opened, one of the pair timeframes
+		pub timeframe: u64,
+		pub trigger_price: SymbolPrice,
+		pub direction: TriggerDirection,
+		/// unix_ts after which the condition expires and the escrow is refunded
+		pub valid_until: u64,
+		pub asset_id: AssetIdOf<T>,
+		/// Stake + protocol fee held by the pallet account until triggered or expired
+		pub escrowed: BalanceOf<T>,
+		pub created_at: u64,
+	}
+
+	/// Stake of an order copied from a leader
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub enum CopyStake<T: Config> {
+		/// The same stake for every copied order
+		Fixed(BalanceOf<T>),
+		/// A share of the leader stake
+		Proportional(Permill),
+	}
+
+	impl<T: Config> CopyStake<T> {
+		pub fn stake_of(&self, leader_stake: BalanceOf<T>) -> BalanceOf<T> {
+			match self {
+				CopyStake::Fixed(stake) => *stake,
+				CopyStake::Proportional(share) => *share * leader_stake,
+			}
+		}
+	}
+
+	/// A follower mirroring the orders of a leader
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct CopySubscription<T: Config> {
+		pub leader: AccountOf<T>,
+		pub stake: CopyStake<T>,
+		/// Total stake left for the copied orders, the subscription stops copying when exhausted
+		pub budget: BalanceOf<T>,
+		/// Leader fee accepted when subscribing, a later change by the leader does not apply
+		pub leader_fee_bps: u32,
+	}
+
+	/// Aggregate results of the settled orders of an account or a pair
+	/// Net P&L = payout - volume, it can be negative
+	#[derive(Clone, Encode, Decode, Default, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct TraderStats<Balance> {
+		pub orders: u32,
+		pub wins: u32,
+		pub losses: u32,
+		/// Total stake of the settled orders
+		pub volume: Balance,
+		/// Total amount paid back to the trader, stake included
+		pub payout: Balance,
+	}
+
+	impl<Balance: AtLeast32BitUnsigned + Copy> TraderStats<Balance> {
+		pub fn record(&mut self, status: &OrderStatus, volume: Balance, payout: Balance) {
+			self.orders = self.orders.saturating_add(1);
+			match status {
+				OrderStatus::Win => self.wins = self.wins.saturating_add(1),
+				OrderStatus::Lose => self.losses = self.losses.saturating_add(1),
+				_ => {},
+			}
+			self.volume = self.volume.saturating_add(volume);
+			self.payout = self.payout.saturating_add(payout);
+		}
+
+		/// The net P&L is higher than the one of `other`
+		pub fn ranks_above(&self, other: &Self) -> bool {
+			self.payout.saturating_add(other.volume) > other.payout.saturating_add(self.volume)
+		}
+	}
+
+	/// Id of a tournament
+	pub type TournamentId = u32;
+
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum TournamentStatus {
+		/// Participants can join until ends_at, and trade in [starts_at, ends_at)
+		Open,
+		/// The prizes were distributed
+		Finished,
+	}
+
+	/// A time-boxed trading competition
+	/// Participants trade tournament-only virtual balances, the orders settle through the normal
+	/// engine without touching the liquidity pools. The best final virtual balances win the prizes
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Tournament<T: Config> {
+		pub organizer: AccountOf<T>,
+		/// Asset of the entry fee and the prizes
+		pub asset_id: AssetIdOf<T>,
+		pub entry_fee: BalanceOf<T>,
+		/// Virtual balance of each participant when joining
+		pub starting_balance: BalanceOf<T>,
+		/// unix_ts
+		pub starts_at: u64,
+		/// unix_ts, the orders must expire at or before it
+		pub ends_at: u64,
+		/// Pairs the participants can trade, without duplicates
+		pub pairs: BoundedVec<PairId, T::MaxTournamentPairs>,
+		/// percent: payout rate of the winning orders
+		pub payout_rate: u32,
+		/// Share of the prize pool of each rank, best first, the rest goes back to the organizer
+		pub prize_split: Vec<Permill>,
+		/// Entry fees + organizer sponsorship, held by the pallet account
+		pub prize_pool: BalanceOf<T>,
+		/// Orders not settled yet, the tournament can finish once there are none
+		pub open_orders: u32,
+		pub status: TournamentStatus,
+	}
+
+	/// Bonus credits signed by the voucher issuer, redeemed once with `redeem_voucher`
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Voucher<T: Config> {
+		/// Unique code of the voucher, a redeemed code can not be redeemed again
+		pub code: T::Hash,
+		/// Only this account can redeem the voucher
+		pub beneficiary: AccountOf<T>,
+		pub asset_id: AssetIdOf<T>,
+		pub amount: BalanceOf<T>,
+		/// unix_ts after which the voucher can not be redeemed
+		pub redeem_before: u64,
+		/// unix_ts after which the credits can not fund orders
+		pub credit_expires_at: u64,
+	}
+
+	/// Bonus credits of an account, non-transferable
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct CreditGrant<T: Config> {
+		pub amount: BalanceOf<T>,
+		/// unix_ts
+		pub expires_at: u64,
+	}
+
+	/// What the referral rebates are a share of
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum RebateSource {
+		/// The protocol fees of the order, both the stake fee and the profit fee
+		ProtocolFee,
+		/// The stake the pool wins from a losing order
+		PoolEdge,
+	}
+
+	impl Default for RebateSource {
+		fn default() -> Self {
+			RebateSource::ProtocolFee
+		}
+	}
+
+	/// Referral rebates set by `AdminOrigin`
+	#[derive(Clone, Encode, Decode, Default, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct ReferralProgram {
+		/// Share of the source for each tier, the direct referrer first
+		pub tiers: Vec<Permill>,
+		pub source: RebateSource,
+	}
+
 	/// Struct for holding Order information.
 	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
 	#[scale_info(skip_type_params(T))]
 	pub struct Order<T: Config> {
 		pub id: T::Hash,
 		pub user_id: AccountOf<T>,
-		pub currency_pair: CurrencyPair,
+		pub pair_id: PairId,
 		pub trade_type: TradeType,
-		// TODO: Ask: scale_info::TypeInfo do not support float,
-		// So What is the best approach to working with float data (eg: trading volume) when we wanna store it on-chain? (use u64, use Balances pallet, etc)
-		/// trading volume in unit of the stable coin
+		/// Asset the stake and the payout are denominated in, eg: USDT
+		pub asset_id: AssetIdOf<T>,
+		/// trading volume in the smallest unit of `asset_id`
 		///
-		/// For example: User buy 0.01 XXX token => trading volume is 0.01
-		/// Can I use pallet balance to store trading volume like the bellow code?
-		/// impl pallet_bo_trading::Config for Runtime {
-		/// 	type Event = Event;
-		/// 	type Currency = Balances; // pallet balance
-		/// }
-		/// type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+		/// For example: with USDT 6 decimals, a 10.5 USDT order has volume_in_unit = 10_500_000
 		pub volume_in_unit: BalanceOf<T>,
+		/// Protocol fee charged on top of the stake at the open time
+		pub protocol_fee: BalanceOf<T>,
 		pub expired_at: u64,
 		pub created_at: u64,
 		pub liquidity_pool_id: AccountOf<T>,
-		pub payout_rate: u32, // percent (1-100): the win rate of the LP at the open time
+		/// percent: the payout rate in effect at the open time, after the Call/Put imbalance adjustment
+		pub payout_rate: u32,
 		pub open_price: SymbolPrice,
 		pub close_price: Option<SymbolPrice>,
 		pub status: OrderStatus,
+		/// Touch / No-Touch orders only: the price touched the barrier before expired_at
+		pub barrier_hit: bool,
+		pub market: MarketMode,
+		/// Peer-to-peer orders only: fills against opposite orders
+		pub matches: Vec<OrderMatch<T>>,
+		/// Sum of `matches` amount
+		pub matched_volume: BalanceOf<T>,
+		/// Part of the volume traded against `liquidity_pool_id`
+		/// Pool orders trade their whole volume against the pool
+		pub pool_volume: BalanceOf<T>,
+		/// Virtual orders only: the tournament whose virtual balance the order trades
+		pub tournament_id: Option<TournamentId>,
+		/// Bonus orders only: the stake was funded by the treasury for bonus credits expiring
+		/// at this time. Only the profit of a winning order is paid to the trader
+		pub bonus_expires_at: Option<u64>,
+	}
+
+	impl<T: Config> Order<T> {
+		/// The stake was funded by bonus credits
+		pub fn is_bonus(&self) -> bool {
+			self.bonus_expires_at.is_some()
+		}
+
+		/// Check the order result at `close_price`
+		pub fn is_win(&self, close_price: SymbolPrice) -> bool {
+			let in_the_money = self.trade_type.is_in_the_money(self.open_price, close_price);
+			match self.trade_type {
+				TradeType::Touch { .. } => self.barrier_hit || in_the_money,
+				TradeType::NoTouch { .. } => !self.barrier_hit && in_the_money,
+				_ => in_the_money,
+			}
+		}
+
+		/// Peer-to-peer volume neither matched nor taken by the pool yet
+		pub fn unmatched_volume(&self) -> BalanceOf<T> {
+			self.volume_in_unit
+				.saturating_sub(self.matched_volume)
+				.saturating_sub(self.pool_volume)
+		}
 	}
 
 	/*
@@ -189,26 +916,488 @@ pub mod pallet {
 	#[pallet::getter(fn next_unsigned_at)]
 	pub(super) type NextUnsignedAt<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
 
-	// Pallets use events to inform users when important changes are made.
-	// https://docs.substrate.io/v3/runtime/events-and-errors
-	#[pallet::event]
-	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	pub enum Event<T: Config> {
-		// /// Event documentation should end with an array that provides descriptive names for event
-		// /// parameters. [something, who]
-		// SomethingStored(u32, T::AccountId),
-		/// The order was created
-		/// parameters. [sender, order_id]
-		OrderCreated(T::AccountId, T::Hash),
+	#[pallet::storage]
+	#[pallet::getter(fn barrier_orders)]
+	/// Open Touch / No-Touch orders whose barrier was not hit yet
+	/// They are checked against every new price from SymbolPrice pallet
+	pub(super) type BarrierOrders<T: Config> = StorageValue<_, Vec<T::Hash>, ValueQuery>;
 
-		OrderClosed {
-			account_id: T::AccountId,
-			order_id: T::Hash,
-			close_price: u128,
-			status: OrderStatus,
-			amount_payout: BalanceOf<T>,
-		},
-	}
+	#[pallet::storage]
+	#[pallet::getter(fn conditional_orders)]
+	pub(super) type ConditionalOrders<T: Config> =
+		StorageMap<_, Twox64Concat, T::Hash, ConditionalOrder<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn pending_conditional_orders)]
+	/// Conditional orders waiting for their trigger price
+	/// They are checked against every new price from SymbolPrice pallet
+	pub(super) type PendingConditionalOrders<T: Config> = StorageValue<_, Vec<T::Hash>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn pending_expiries)]
+	/// (pair, expiry) of the open orders whose settlement price is not recorded yet
+	/// They are checked against every new price from SymbolPrice pallet
+	pub(super) type PendingExpiries<T: Config> = StorageValue<_, Vec<(PairId, u64)>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn settlement_price)]
+	/// First price of a pair at or after an expiry, the orders expiring then are settled at it
+	pub(super) type SettlementPrices<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, PairId, Twox64Concat, u64, SymbolPrice, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn copy_subscriptions)]
+	/// The leader followed by an account, an account follows at most one leader
+	pub(super) type CopySubscriptions<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, CopySubscription<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn followers)]
+	pub(super) type Followers<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, Vec<T::AccountId>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn leader_fees)]
+	/// Fee (in basis points) a leader takes from the profit of the orders copied from it
+	pub(super) type LeaderFees<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn copied_orders)]
+	/// Leader and accepted leader fee of the open copied orders
+	pub(super) type CopiedOrders<T: Config> =
+		StorageMap<_, Twox64Concat, T::Hash, (T::AccountId, u32)>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn account_stats)]
+	/// All time stats of an account, by asset as volumes of different assets do not add up
+	pub(super) type AccountStats<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		AssetIdOf<T>,
+		TraderStats<BalanceOf<T>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn pair_stats)]
+	/// All time stats of the traders of a pair
+	pub(super) type PairStats<T: Config> =
+		StorageMap<_, Twox64Concat, PairId, TraderStats<BalanceOf<T>>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn period_stats)]
+	/// Stats of an account in a leaderboard period, by (asset, period)
+	/// A period is unix_ts / `LeaderboardPeriod`
+	pub(super) type PeriodStats<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		(AssetIdOf<T>, u64),
+		Twox64Concat,
+		T::AccountId,
+		TraderStats<BalanceOf<T>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn leaderboards)]
+	/// Top `LeaderboardSize` accounts of a period by net P&L, best first, by (asset, period)
+	pub(super) type Leaderboards<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		(AssetIdOf<T>, u64),
+		Vec<(T::AccountId, TraderStats<BalanceOf<T>>)>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn tournaments)]
+	pub(super) type Tournaments<T: Config> =
+		StorageMap<_, Twox64Concat, TournamentId, Tournament<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_tournament_id)]
+	pub(super) type NextTournamentId<T: Config> = StorageValue<_, TournamentId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn tournament_participants)]
+	/// Participants of a tournament, in joining order
+	pub(super) type TournamentParticipants<T: Config> =
+		StorageMap<_, Twox64Concat, TournamentId, Vec<T::AccountId>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn tournament_balances)]
+	/// Virtual balance of a participant, stakes of the open orders excluded
+	pub(super) type TournamentBalances<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		TournamentId,
+		Twox64Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn voucher_issuer)]
+	/// Account whose signature makes a voucher valid, vouchers are disabled if None
+	pub(super) type VoucherIssuer<T: Config> = StorageValue<_, T::AccountId, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn redeemed_vouchers)]
+	/// Codes of the redeemed vouchers
+	pub(super) type RedeemedVouchers<T: Config> =
+		StorageMap<_, Twox64Concat, T::Hash, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn referrers)]
+	/// Referrer of an account, bound once
+	pub(super) type Referrers<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::AccountId, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn referral_program)]
+	/// Referral rebates, disabled while there is no tier
+	pub(super) type Referral<T: Config> = StorageValue<_, ReferralProgram, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn referral_rebates)]
+	/// Rebates accrued by a referrer in an asset, paid by the treasury when claimed
+	pub(super) type ReferralRebates<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		AssetIdOf<T>,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn bonus_credits)]
+	/// Bonus credits of an account in an asset, soonest expiry first
+	pub(super) type BonusCredits<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		AssetIdOf<T>,
+		Vec<CreditGrant<T>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn resting_orders)]
+	/// Peer-to-peer orders waiting for an opposite order, by book (pair, expired_at) and side
+	/// Orders are matched in FIFO order
+	pub(super) type RestingOrders<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		(PairId, u64),
+		Twox64Concat,
+		PositionSide,
+		Vec<T::Hash>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn pairs)]
+	/// Registry of the tradable pairs
+	pub(super) type Pairs<T: Config> = StorageMap<_, Twox64Concat, PairId, TradingPair<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_pair_id)]
+	pub(super) type NextPairId<T: Config> = StorageValue<_, PairId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn global_pause)]
+	/// New orders of every pair are rejected while set
+	pub(super) type GlobalPause<T: Config> = StorageValue<_, PausePolicy, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn pair_pauses)]
+	/// New orders of a pair are rejected while set
+	pub(super) type PairPauses<T: Config> =
+		StorageMap<_, Twox64Concat, PairId, PausePolicy, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn settlement_paused)]
+	/// Expired orders are not closed while set, they stay open until settlement is resumed
+	pub(super) type SettlementPaused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn self_limits)]
+	/// Limits an account set on itself, they can only be tightened
+	pub(super) type SelfLimits<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, RiskLimits<T>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn operator_limits)]
+	/// Limits set on an account by `AdminOrigin`
+	pub(super) type OperatorLimits<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, RiskLimits<T>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn account_risk)]
+	/// Trading counters of each account
+	pub(super) type AccountRisks<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, AccountRisk<T>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn protocol_fees)]
+	/// Protocol fees set by `AdminOrigin`, none by default
+	pub(super) type ProtocolFees<T: Config> = StorageValue<_, ProtocolFee, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn fees_collected_in)]
+	/// Protocol fees sent to the treasury, by asset and period: unix_ts / `FeePeriod`
+	pub(super) type FeesCollected<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, AssetIdOf<T>, Twox64Concat, u64, BalanceOf<T>, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config>
+	where
+		AssetIdOf<T>: MaybeSerializeDeserialize,
+		BalanceOf<T>: MaybeSerializeDeserialize,
+	{
+		/// Pairs registered at genesis, their ids follow the order of the list
+		pub pairs: Vec<TradingPair<T>>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T>
+	where
+		AssetIdOf<T>: MaybeSerializeDeserialize,
+		BalanceOf<T>: MaybeSerializeDeserialize,
+	{
+		fn default() -> Self {
+			Self { pairs: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T>
+	where
+		AssetIdOf<T>: MaybeSerializeDeserialize,
+		BalanceOf<T>: MaybeSerializeDeserialize,
+	{
+		fn build(&self) {
+			for (pair_id, pair) in self.pairs.iter().enumerate() {
+				Pallet::<T>::ensure_valid_pair(pair).expect("genesis pairs are valid; qed");
+				<Pairs<T>>::insert(pair_id as PairId, pair);
+			}
+			<NextPairId<T>>::put(self.pairs.len() as PairId);
+		}
+	}
+
+	// Pallets use events to inform users when important changes are made.
+	// https://docs.substrate.io/v3/runtime/events-and-errors
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		// /// Event documentation should end with an array that provides descriptive names for event
+		// /// parameters. [something, who]
+		// SomethingStored(u32, T::AccountId),
+		/// The order was created
+		/// parameters. [sender, order_id, stake, protocol_fee]
+		OrderCreated(T::AccountId, T::Hash, BalanceOf<T>, BalanceOf<T>),
+
+		/// protocol_fee: taken from the pool profit of a losing order
+		OrderClosed {
+			account_id: T::AccountId,
+			order_id: T::Hash,
+			close_price: u128,
+			status: OrderStatus,
+			amount_payout: BalanceOf<T>,
+			protocol_fee: BalanceOf<T>,
+		},
+
+		/// The order was sold back to the pool before expiry
+		OrderSold {
+			account_id: T::AccountId,
+			order_id: T::Hash,
+			price: SymbolPrice,
+			amount_payout: BalanceOf<T>,
+		},
+
+		/// The order was cancelled and its stake refunded, minus the cancel fee
+		OrderCancelled {
+			account_id: T::AccountId,
+			order_id: T::Hash,
+			amount_refund: BalanceOf<T>,
+		},
+
+		/// A peer-to-peer order was matched against an opposite order
+		OrderMatched {
+			order_id: T::Hash,
+			counterparty: T::Hash,
+			amount: BalanceOf<T>,
+			price: SymbolPrice,
+		},
+
+		/// The price touched the barrier of a Touch / No-Touch order
+		/// parameters. [order_id, price]
+		BarrierHit(T::Hash, SymbolPrice),
+
+		/// The orders of a pair expiring at a timestamp will be settled at this price
+		/// parameters. [pair_id, expired_at, price]
+		SettlementPriceRecorded(PairId, u64, SymbolPrice),
+
+		/// A keeper settled expired orders
+		/// parameters. [keeper, settled_orders]
+		ExpiredOrdersSettled(T::AccountId, u32),
+
+		/// A batch of orders was placed
+		/// failed: index in the batch and error of the legs not placed, best-effort mode only
+		OrdersPlaced {
+			account_id: T::AccountId,
+			placed: Vec<T::Hash>,
+			failed: Vec<(u32, DispatchError)>,
+		},
+
+		/// A conditional order was created, its stake is escrowed
+		/// parameters. [account_id, conditional_order_id]
+		ConditionalOrderCreated(T::AccountId, T::Hash),
+
+		/// The trigger price of a conditional order was crossed and its order opened
+		ConditionalOrderTriggered {
+			conditional_order_id: T::Hash,
+			order_id: T::Hash,
+			price: SymbolPrice,
+		},
+
+		/// The trigger price was crossed but the order could not be opened, the escrow is refunded
+		/// parameters. [conditional_order_id, error]
+		ConditionalOrderFailed(T::Hash, DispatchError),
+
+		/// The condition expired unfilled, the escrow is refunded
+		/// parameters. [conditional_order_id]
+		ConditionalOrderExpired(T::Hash),
+
+		/// The conditional order was cancelled by its owner, the escrow is refunded
+		/// parameters. [conditional_order_id]
+		ConditionalOrderCancelled(T::Hash),
+
+		/// A leader set the fee taken from the profit of its copied orders
+		/// parameters. [leader, fee_bps]
+		LeaderFeeUpdated(T::AccountId, u32),
+
+		/// An account started copying the orders of a leader
+		/// parameters. [follower, leader]
+		Followed(T::AccountId, T::AccountId),
+
+		/// An account stopped copying the orders of a leader, or was removed by the leader
+		/// parameters. [follower, leader]
+		Unfollowed(T::AccountId, T::AccountId),
+
+		/// An order of a leader was copied for a follower
+		OrderCopied {
+			leader: T::AccountId,
+			follower: T::AccountId,
+			leader_order_id: T::Hash,
+			order_id: T::Hash,
+		},
+
+		/// A leader was paid its fee on the profit of a copied order
+		/// parameters. [leader, follower, order_id, fee]
+		LeaderFeePaid(T::AccountId, T::AccountId, T::Hash, BalanceOf<T>),
+
+		/// A tournament was created
+		/// parameters. [tournament_id, organizer]
+		TournamentCreated(TournamentId, T::AccountId),
+
+		/// An account joined a tournament
+		/// parameters. [tournament_id, account_id]
+		TournamentJoined(TournamentId, T::AccountId),
+
+		/// The prizes of a tournament were distributed
+		/// prizes: winners and their prize, best first
+		TournamentFinished {
+			tournament_id: TournamentId,
+			prizes: Vec<(T::AccountId, BalanceOf<T>)>,
+		},
+
+		/// The voucher issuer was updated
+		/// parameters. [issuer]
+		VoucherIssuerUpdated(Option<T::AccountId>),
+
+		/// A voucher was redeemed for bonus credits
+		VoucherRedeemed {
+			account_id: T::AccountId,
+			code: T::Hash,
+			asset_id: AssetIdOf<T>,
+			amount: BalanceOf<T>,
+			expires_at: u64,
+		},
+
+		/// An account bound itself to a referrer
+		/// parameters. [account_id, referrer]
+		ReferrerSet(T::AccountId, T::AccountId),
+
+		/// The referral program was updated
+		ReferralProgramUpdated(ReferralProgram),
+
+		/// A referrer earned a rebate on an order of a referred account
+		ReferralRebateAccrued {
+			referrer: T::AccountId,
+			account_id: T::AccountId,
+			order_id: T::Hash,
+			/// 1 for the direct referrer
+			tier: u32,
+			asset_id: AssetIdOf<T>,
+			amount: BalanceOf<T>,
+		},
+
+		/// A referrer claimed its rebates
+		/// parameters. [referrer, asset_id, amount]
+		ReferralRebateClaimed(T::AccountId, AssetIdOf<T>, BalanceOf<T>),
+
+		/// A keeper was rewarded for settling an order
+		/// parameters. [keeper, order_id, reward]
+		KeeperRewarded(T::AccountId, T::Hash, BalanceOf<T>),
+
+		/// A pair was added to the registry
+		/// parameters. [pair_id]
+		PairAdded(PairId),
+
+		/// A pair of the registry was updated
+		/// parameters. [pair_id]
+		PairUpdated(PairId),
+
+		/// Protocol fees were updated
+		/// parameters. [stake_fee_bps, profit_fee_bps]
+		ProtocolFeesUpdated(u32, u32),
+
+		/// An account tightened its own limits
+		/// parameters. [account_id]
+		SelfLimitsUpdated(T::AccountId),
+
+		/// An operator set the limits of an account
+		/// parameters. [account_id]
+		OperatorLimitsUpdated(T::AccountId),
+
+		/// An account can not trade until the given unix_ts
+		/// parameters. [account_id, excluded_until]
+		AccountExcluded(T::AccountId, u64),
+
+		/// An account reached its loss streak limit and can not trade until the given unix_ts
+		/// parameters. [account_id, cooldown_until]
+		LossStreakCooldownStarted(T::AccountId, u64),
+
+		/// New orders were paused, globally if no pair
+		/// parameters. [pair_id, policy]
+		Paused(Option<PairId>, PausePolicy),
+
+		/// New orders were resumed, globally if no pair
+		/// parameters. [pair_id]
+		Resumed(Option<PairId>),
+
+		/// Settlement of expired orders was paused or resumed
+		/// parameters. [paused]
+		SettlementPausedUpdated(bool),
+	}
 
 	// Errors inform users that something went wrong.
 	#[pallet::error]
@@ -217,8 +1406,11 @@ pub mod pallet {
 		// NoneValue,
 		// /// Errors should have helpful documentation associated with them.
 		// StorageOverflow,
-		/// ExpiredAt must be a specific point in the future, and if timeframe is 5 minute
+		/// ExpiredAt must be at least `MinLeadIntervals` oracle updates in the future
 		InvalidExpiredAt,
+		/// ExpiredAt must be the first boundary of an allowed timeframe of the pair,
+		/// eg: with 5 minutes timeframe, an order placed at 10:01:30 expires at 10:05:00
+		TimeframeNotAllowed,
 		/// trading vol must be min / max
 		InvalidTradingVolume,
 		/// Not enough balance to place the order
@@ -233,6 +1425,134 @@ pub mod pallet {
 		CannotSaveUserOrders,
 		/// Order Not Exist
 		OrderNotExist,
+		/// The order was already closed, sold or is being checked
+		OrderNotOpen,
+		/// The order is too close to expired_at to be sold back
+		SellBackLocked,
+		/// The liquidity pool can not pay the sell back quote
+		PoolNotEnoughBalance,
+		/// No price available for the order's currency pair
+		PriceNotAvailable,
+		/// The order can not be cancelled after the cancel window
+		CancelWindowPassed,
+		/// The strike is too far from the current price, or the pool does not quote strike orders
+		StrikeOutOfRange,
+		/// The barrier equals the open price, or the range is empty
+		InvalidBarrier,
+		/// Too many open Touch / No-Touch orders are being watched
+		TooManyBarrierOrders,
+		/// Touch / No-Touch and range orders can not be sold back
+		SellBackNotSupported,
+		/// Only Call / Put orders can be traded peer-to-peer
+		P2POrderTypeNotSupported,
+		/// Too many unmatched peer-to-peer orders are waiting in this book
+		TooManyRestingOrders,
+		/// This is only available for orders traded against a pool
+		PoolOrderOnly,
+		/// A protocol fee is higher than `MaxProtocolFeeBps`
+		InvalidProtocolFee,
+		/// The account reached its maximum number of open orders
+		TooManyOpenOrders,
+		/// The order would exceed the maximum total stake of the open orders
+		OpenStakeLimitExceeded,
+		/// The order could exceed the daily loss cap of the account
+		DailyLossCapReached,
+		/// The account lost too many orders in a row and is cooling down
+		LossStreakCooldown,
+		/// The account is excluded from trading
+		AccountExcluded,
+		/// Self limits can only be tightened
+		CanOnlyTightenLimits,
+		/// New orders are paused for this pair
+		TradingPaused,
+		/// Settlement of expired orders is paused
+		SettlementPaused,
+		/// The pair is not in the registry
+		PairNotFound,
+		/// The pair does not accept new orders
+		PairDisabled,
+		/// The pair needs an oracle symbol and min_stake <= max_stake,
+		/// its oracle symbol can not be changed
+		InvalidPair,
+		/// overflow
+		PairIdOverflow,
+		/// More orders than `MaxSettleBatch` in one settlement
+		TooManyOrdersToSettle,
+		/// A batch needs at least one order
+		InvalidBatchSize,
+		/// Too many conditional orders are waiting for their trigger price
+		TooManyConditionalOrders,
+		/// The conditional order does not exist, or was triggered or expired
+		ConditionalOrderNotExist,
+		/// The condition must expire in the future and the trigger price must be positive
+		InvalidCondition,
+		/// An account can not follow itself
+		CannotFollowSelf,
+		/// The leader has `MaxFollowers` followers
+		TooManyFollowers,
+		/// The account does not follow any leader, or not the sender
+		NotFollowing,
+		/// The budget of a copy subscription is lower than `MinCopyBudget`
+		CopyBudgetTooLow,
+		/// The leader fee is higher than `MaxLeaderFeeBps`,
+		/// or than the fee accepted by the follower
+		InvalidLeaderFee,
+		/// The tournament does not exist
+		TournamentNotExist,
+		/// A tournament needs start < end in the future, existing pairs, a starting balance,
+		/// a payout rate in [MinPayoutRate, MaxPayoutRate] and a prize split up to 100%
+		InvalidTournament,
+		/// overflow
+		TournamentIdOverflow,
+		/// The tournament is not running, or is already over for this action
+		TournamentNotLive,
+		/// The account already joined the tournament
+		AlreadyJoined,
+		/// The tournament has `MaxParticipants` participants
+		TooManyParticipants,
+		/// The account did not join the tournament
+		NotParticipant,
+		/// The pair can not be traded in this tournament
+		PairNotInTournament,
+		/// The virtual balance is lower than the stake
+		NotEnoughVirtualBalance,
+		/// The tournament is not ended, or has orders not settled yet,
+		/// or its grace period is not over
+		TournamentNotOver,
+		/// Tournament orders can not be sold back or cancelled, nor be Touch / No-Touch orders
+		TournamentOrderNotSupported,
+		/// The voucher is not signed by the voucher issuer
+		InvalidVoucher,
+		/// The voucher was already redeemed
+		VoucherAlreadyRedeemed,
+		/// The voucher or its credits are expired
+		VoucherExpired,
+		/// The voucher is for another account
+		VoucherNotForYou,
+		/// The account has `MaxCreditGrants` bonus credit grants in this asset
+		TooManyCreditGrants,
+		/// The bonus credits are lower than the stake
+		NotEnoughBonusCredit,
+		/// The treasury can not fund the stake of a bonus order
+		TreasuryNotEnoughBalance,
+		/// Bonus orders can not be sold back or cancelled
+		BonusOrderNotSupported,
+		/// The account is already bound to a referrer
+		AlreadyReferred,
+		/// Only an account without orders can bind itself to a referrer
+		NotNewAccount,
+		/// The referrer is the account itself or one of its referees
+		InvalidReferrer,
+		/// Too many tiers, or the tiers share more than the source left after the keeper reward
+		InvalidReferralProgram,
+		/// Nothing to claim
+		NoReferralRebate,
+		/// The order can not be settled before its expiry
+		OrderNotExpired,
+		/// No price was received at or after the expiry of the order yet
+		SettlementPriceNotAvailable,
+		/// Too many expiries of open orders are waiting for their settlement price
+		TooManyPendingExpiries,
 	}
 
 	#[pallet::validate_unsigned]
@@ -245,8 +1565,10 @@ pub mod pallet {
 				let next_unsigned_at = <NextUnsignedAt<T>>::get();
 				let current_block_number = <frame_system::Pallet<T>>::block_number();
 				ValidTransaction::with_tag_prefix("bo_trading_crond")
-					// set priority to 2^18
-					.priority(1 << 18 + Self::blocknumber_to_u64(current_block_number).unwrap()) // please define `UNSIGNED_TXS_PRIORITY` before this line
+					// set priority to 2^18 + block_number
+					.priority(UNSIGNED_TXS_PRIORITY.saturating_add(
+						Self::blocknumber_to_u64(current_block_number).unwrap_or_default(),
+					))
 					.and_provides([&provide])
 					.longevity(5)
 					.propagate(true)
@@ -254,11 +1576,14 @@ pub mod pallet {
 			};
 
 			match call {
-				Call::close_order {
-					block_number: current_block_number,
-					ref order_id,
-					ref close_price,
-				} => valid_tx(b"close_order".to_vec()),
+				Call::close_order { ref order_id, .. } => {
+					// only an expired open order with a settlement price can be settled, at it
+					let current_ts = T::TimeProvider::now().as_secs();
+					if Self::settlement_price_of(order_id, current_ts).is_none() {
+						return InvalidTransaction::Stale.into()
+					}
+					valid_tx((b"close_order", order_id).encode())
+				},
 				_ => InvalidTransaction::Call.into(),
 			}
 		}
@@ -324,252 +1649,2693 @@ pub mod pallet {
 		///  - volume_in_unit: 2 decimal place, eg: 1000 mean 10.00
 		///  - expired_at: unix timestamp
 		///
-		/// TODO: do benchmark to get this weight
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		/// The order is copied for the followers of the sender, see `follow`
+		///
+		/// `UserOrders` is appended to without being decoded: the weight does not depend on the
+		/// number of orders of the sender
+		///
+		/// Charged upfront for `MaxPools` pools visited by the order and each of `MaxFollowers`
+		/// copies, only the pools and the followers actually visited are charged in the end
+		#[pallet::weight(Self::place_order_weight(
+			T::MaxPools::get(),
+			T::MaxFollowers::get(),
+			T::MaxPools::get().saturating_mul(T::MaxFollowers::get()),
+		))]
 		pub fn place_order(
 			origin: OriginFor<T>,
-			currency_pair: CurrencyPair,
+			pair_id: PairId,
 			trade_type: TradeType,
 			volume_in_unit: BalanceOf<T>,
 			expired_at: u64,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			// Check that the extrinsic was signed and get the signer.
 			// This function will return an error if the extrinsic is not signed.
 			// https://docs.substrate.io/v3/runtime/origins
 			let sender = ensure_signed(origin)?;
 
-			// ----- validation ------
-			// TODO: Ask: how can I get decimal of currency, ie: 1 coin = 100...000 units
-			let CURRENCY_DECIMAL = 12;
-			// TODO: Get this setting from LiquidityPool setting
-			let MIN_TRADING_VOL = 1; // min trading vol is 1 token = (~$1)
-			let MAX_TRADING_VOL = 1000; // max trading vol is 1000 token (~$1000)
+			let (order_id, pools_visited) = Self::do_place_order(
+				sender.clone(),
+				pair_id,
+				trade_type.clone(),
+				volume_in_unit,
+				expired_at,
+				false,
+			)?;
+			let (followers, copy_pools_visited) = Self::copy_order(
+				&sender,
+				order_id,
+				pair_id,
+				trade_type,
+				volume_in_unit,
+				expired_at,
+			);
 
-			let PAYOUT_RATE = 95; // payout rate to user
+			Ok(Some(Self::place_order_weight(pools_visited, followers, copy_pools_visited)).into())
+		}
 
-			let min_vol_in_unit: BalanceOf<T> =
-				Self::u64_to_balance(MIN_TRADING_VOL * 10_u64.pow(CURRENCY_DECIMAL))
-					.ok_or(<Error<T>>::InvalidTradingVolume)?;
-			let max_vol_in_unit: BalanceOf<T> =
-				Self::u64_to_balance(MAX_TRADING_VOL * 10_u64.pow(CURRENCY_DECIMAL))
-					.ok_or(<Error<T>>::InvalidTradingVolume)?;
+		/// Set the fee taken from the profit of the orders copied from the sender
+		/// - Followers keep the fee they accepted when subscribing
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_leader_fee(origin: OriginFor<T>, fee_bps: u32) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(fee_bps <= T::MaxLeaderFeeBps::get(), <Error<T>>::InvalidLeaderFee);
 
-			log::info!("min_vol is {:?} and max_vol is {:?}.", min_vol_in_unit, max_vol_in_unit);
+			<LeaderFees<T>>::insert(&sender, fee_bps);
 
-			ensure!(min_vol_in_unit.le(&volume_in_unit), <Error<T>>::InvalidTradingVolume);
-			ensure!(max_vol_in_unit.ge(&volume_in_unit), <Error<T>>::InvalidTradingVolume);
+			Self::deposit_event(Event::LeaderFeeUpdated(sender, fee_bps));
 
-			let current_ts: u64 = T::TimeProvider::now().as_secs(); // TODO: Get current timestamp
-			log::info!("Order is creating at {:?} and expired at {:?}.", current_ts, expired_at);
+			Ok(())
+		}
+
+		/// Copy the future orders of a leader, replacing the current subscription if any
+		/// - stake: fixed or proportional to the leader stake
+		/// - budget: total stake of the copied orders
+		/// - max_leader_fee_bps: the call fails if the leader fee is higher
+		///
+		/// The budget must be at least `MinCopyBudget`, a leader can also remove its followers
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		pub fn follow(
+			origin: OriginFor<T>,
+			leader: T::AccountId,
+			stake: CopyStake<T>,
+			budget: BalanceOf<T>,
+			max_leader_fee_bps: u32,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(sender != leader, <Error<T>>::CannotFollowSelf);
+			ensure!(budget >= T::MinCopyBudget::get(), <Error<T>>::CopyBudgetTooLow);
 
-			ensure!(current_ts < expired_at, <Error<T>>::InvalidExpiredAt);
+			let leader_fee_bps = Self::leader_fees(&leader);
+			ensure!(leader_fee_bps <= max_leader_fee_bps, <Error<T>>::InvalidLeaderFee);
 
-			// Check the buyer has enough free balance to place this order
-			ensure!(
-				T::Currency::free_balance(&sender) >= volume_in_unit,
-				<Error<T>>::NotEnoughBalance
+			if let Some(current) = Self::copy_subscriptions(&sender) {
+				<Followers<T>>::mutate(&current.leader, |followers| {
+					followers.retain(|follower| *follower != sender)
+				});
+			}
+			<Followers<T>>::try_mutate(&leader, |followers| -> DispatchResult {
+				ensure!(
+					followers.len() < T::MaxFollowers::get() as usize,
+					<Error<T>>::TooManyFollowers
+				);
+				followers.push(sender.clone());
+				Ok(())
+			})?;
+			<CopySubscriptions<T>>::insert(
+				&sender,
+				CopySubscription::<T> { leader: leader.clone(), stake, budget, leader_fee_bps },
 			);
 
-			// Performs this operation first as it may fail
-			let new_cnt: u64 =
-				Self::order_count().checked_add(1).ok_or(<Error<T>>::OrderCountOverflow)?;
-
-			// TODO: Ensure: Allow a specific currency only!
+			Self::deposit_event(Event::Followed(sender, leader));
 
-			// select a pool id for this order
-			let volumn = Self::balance_to_u64(volume_in_unit).unwrap();
-			let suitable_lp_id = T::BoLiquidity::get_suitable_lp(volumn);
-			ensure!(suitable_lp_id.is_some(), <Error<T>>::NoLiquidityPool);
+			Ok(())
+		}
 
-			let open_price =
-				(T::SymbolPriceModule::get_price(String::from("BTC_USDT").into_bytes())).unwrap();
+		/// Stop copying the orders of the followed leader, the copied orders stay open
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn unfollow(origin: OriginFor<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
 
-			// create orders
-			let mut order = Order::<T> {
-				id: T::Hashing::hash_of(&b"N/A"),
-				user_id: sender.clone(),
-				currency_pair,
-				trade_type,
-				volume_in_unit,
-				expired_at,
-				created_at: current_ts,
-				liquidity_pool_id: suitable_lp_id.unwrap(), // unwrap is safe because of `ensure` check above
-				payout_rate: PAYOUT_RATE,                   // fix 95% or must get this from liquidity pool info
-				open_price,
-				close_price: None,
-				status: OrderStatus::Created,
-			};
+			let subscription =
+				<CopySubscriptions<T>>::take(&sender).ok_or(<Error<T>>::NotFollowing)?;
+			<Followers<T>>::mutate(&subscription.leader, |followers| {
+				followers.retain(|follower| *follower != sender)
+			});
 
-			// TODO: Ask: Is this too complex? How can we improve this?
-			// TODO: switch to randomize id because hashing this way do not ensure uniqueness
-			let order_id = T::Hashing::hash_of(&order);
-			order.id = order_id;
+			Self::deposit_event(Event::Unfollowed(sender, subscription.leader));
 
-			// ---- Save to db ------
-			// Performs this operation first because as it may fail
-			// <UserOrders<T>>::try_mutate(&sender, |vec| {
-			// 	vec.push(order_id);
-			// 	Ok(())
-			// }).map_err(|_| <Error<T>>::CannotSaveUserOrders)?;
-			<UserOrders<T>>::append(sender.clone(), order_id);
-			<Orders<T>>::insert(order_id, order.clone());
-			<OrderCount<T>>::put(new_cnt);
+			Ok(())
+		}
 
-			// Deposit balance user
-			T::Currency::transfer(
-				&sender.clone(),
-				&order.liquidity_pool_id,
-				volume_in_unit,
-				ExistenceRequirement::KeepAlive,
-			)?;
+		/// Remove a follower of the sender, the copied orders stay open
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn remove_follower(origin: OriginFor<T>, follower: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
 
-			// Update LP balance
-			T::BoLiquidity::update_lp_balance(order.liquidity_pool_id);
+			<CopySubscriptions<T>>::try_mutate_exists(&follower, |subscription| -> DispatchResult {
+				ensure!(
+					subscription.as_ref().map_or(false, |s| s.leader == sender),
+					<Error<T>>::NotFollowing
+				);
+				*subscription = None;
+				Ok(())
+			})?;
+			<Followers<T>>::mutate(&sender, |followers| {
+				followers.retain(|account| *account != follower)
+			});
 
-			log::info!("Order created: {:?}.", order_id);
-			Self::deposit_event(Event::OrderCreated(sender, order_id));
+			Self::deposit_event(Event::Unfollowed(follower, sender));
 
 			Ok(())
 		}
 
-		/// Validate, finish this order
-		/// - Determine this is win or loose
-		/// - So dome money transfer logic
-		#[pallet::weight(1_000 + T::DbWeight::get().writes(1))]
-		pub fn close_order(
+		/// Create a batch of orders in one transaction, eg: a ladder across pairs and expiries
+		/// - best_effort = false: every leg is validated up front and the batch is placed
+		/// 	atomically, any failing leg fails the whole batch
+		/// - best_effort = true: the valid legs are placed, the failing legs are reported
+		/// 	in the `OrdersPlaced` event
+		///
+		/// The legs are not copied for the followers of the sender, only `place_order` is
+		#[pallet::weight(
+			T::WeightInfo::place_order(T::MaxPools::get(), 0).saturating_mul(orders.len() as u64)
+		)]
+		pub fn place_orders(
 			origin: OriginFor<T>,
-			block_number: T::BlockNumber,
-			order_id: T::Hash,
-			close_price: SymbolPrice,
+			orders: BoundedVec<OrderRequest<T>, T::MaxOrdersPerBatch>,
+			best_effort: bool,
 		) -> DispatchResult {
-			let CURRENCY_DECIMAL = 12;
-			// Get Order
-			let order = Orders::<T>::get(&order_id);
-			let mut status = OrderStatus::Lose;
-			match order {
-				Some(ref order) => {
-					// Check result
-					match order.trade_type {
-						TradeType::Call => {
-							if order.open_price < close_price {
-								status = OrderStatus::Win;
-							}
-						}
-						TradeType::Put => {
-							if order.open_price > close_price {
-								status = OrderStatus::Win;
-							}
+			let sender = ensure_signed(origin)?;
+			ensure!(!orders.is_empty(), <Error<T>>::InvalidBatchSize);
+
+			let mut placed = Vec::new();
+			let mut failed = Vec::new();
+			if best_effort {
+				for (index, request) in orders.into_iter().enumerate() {
+					// A failing leg must not leave a partial state behind
+					let res = with_transaction(|| {
+						match Self::do_place_order(
+							sender.clone(),
+							request.pair_id,
+							request.trade_type,
+							request.volume_in_unit,
+							request.expired_at,
+							false,
+						) {
+							Ok((order_id, _)) => TransactionOutcome::Commit(Ok(order_id)),
+							Err(e) => TransactionOutcome::Rollback(Err(e)),
 						}
+					});
+					match res {
+						Ok(order_id) => placed.push(order_id),
+						Err(e) => failed.push((index as u32, e)),
 					}
 				}
-				None => Err(Error::<T>::OrderNotExist)?,
+			} else {
+				Self::ensure_batch_params(&sender, &orders)?;
+				placed = with_transaction(|| {
+					let mut placed = Vec::new();
+					for request in orders.into_iter() {
+						match Self::do_place_order(
+							sender.clone(),
+							request.pair_id,
+							request.trade_type,
+							request.volume_in_unit,
+							request.expired_at,
+							false,
+						) {
+							Ok((order_id, _)) => placed.push(order_id),
+							Err(e) => return TransactionOutcome::Rollback(Err(e)),
+						}
+					}
+					TransactionOutcome::Commit(Ok(placed))
+				})?;
 			}
 
-			// Set status
-			let mut order = order.clone().unwrap();
-			order.status = status.clone();
-			order.close_price = Some(close_price);
-
-			let mut volumn_payout: BalanceOf<T> = Self::u64_to_balance(0).unwrap();
+			Self::deposit_event(Event::OrdersPlaced { account_id: sender, placed, failed });
 
-			// Update info Orders
-			Orders::<T>::try_mutate_exists(&order_id, |order| -> DispatchResult {
-				let mut order = order.as_mut().ok_or(Error::<T>::OrderNotExist)?;
-				order.status = status.clone();
-				order.close_price = Some(close_price);
-				Ok(())
-			})?;
+			Ok(())
+		}
 
-			match status {
-				OrderStatus::Win => {
-					volumn_payout = (order.volume_in_unit
-						* Self::u64_to_balance(u64::from(100 + order.payout_rate)).unwrap())
-						/ Self::u64_to_balance(100).unwrap();
-
-					log::info!("volumn_payout: {:?}", volumn_payout);
-					// Payout
-					T::Currency::transfer(
-						&order.liquidity_pool_id,
-						&order.user_id,
-						volumn_payout,
-						ExistenceRequirement::KeepAlive,
-					)?;
-
-					// Update LP balance
-					T::BoLiquidity::update_lp_balance(order.liquidity_pool_id);
-				}
-				_ => {
-					log::info!("Lose: order_id, close_price: {:?}, {:?}", order_id, close_price);
-				}
+		/// Create an order opened when the price of the pair crosses `trigger_price`
+		/// - timeframe: duration (in seconds) of the order once opened
+		/// - valid_until: unix timestamp after which the condition expires unfilled
+		/// - The stake and the protocol fee are escrowed until the condition is triggered
+		/// 	or expires
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 3))]
+		pub fn place_conditional_order(
+			origin: OriginFor<T>,
+			pair_id: PairId,
+			trade_type: TradeType,
+			volume_in_unit: BalanceOf<T>,
+			timeframe: u64,
+			trigger_price: SymbolPrice,
+			direction: TriggerDirection,
+			valid_until: u64,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let current_ts: u64 = T::TimeProvider::now().as_secs();
+			ensure!(trigger_price > 0 && valid_until > current_ts, <Error<T>>::InvalidCondition);
+			ensure!(
+				Self::pending_conditional_orders().len() < T::MaxConditionalOrders::get() as usize,
+				<Error<T>>::TooManyConditionalOrders
+			);
+
+			// validate the order as if it was opened now
+			let expired_at = Self::next_expiry(current_ts, timeframe);
+			let (_, pair) =
+				Self::ensure_order_params(&sender, pair_id, volume_in_unit, expired_at)?;
+
+			let new_cnt: u64 =
+				Self::order_count().checked_add(1).ok_or(<Error<T>>::OrderCountOverflow)?;
+			let id = T::Hashing::hash_of(&(
+				b"conditional",
+				&sender,
+				pair_id,
+				&trade_type,
+				volume_in_unit,
+				trigger_price,
+				current_ts,
+				new_cnt,
+			));
+			let escrowed = volume_in_unit.saturating_add(Self::stake_fee_of(volume_in_unit));
+			T::Assets::transfer(pair.asset_id, &sender, &Self::account_id(), escrowed, true)?;
+
+			<ConditionalOrders<T>>::insert(
+				id,
+				ConditionalOrder::<T> {
+					id,
+					user_id: sender.clone(),
+					pair_id,
+					trade_type,
+					volume_in_unit,
+					timeframe,
+					trigger_price,
+					direction,
+					valid_until,
+					asset_id: pair.asset_id,
+					escrowed,
+					created_at: current_ts,
+				},
+			);
+			<PendingConditionalOrders<T>>::append(id);
+			<OrderCount<T>>::put(new_cnt);
+
+			Self::deposit_event(Event::ConditionalOrderCreated(sender, id));
+
+			Ok(())
+		}
+
+		/// Cancel a conditional order not triggered yet, the escrow is refunded
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn cancel_conditional_order(
+			origin: OriginFor<T>,
+			conditional_order_id: T::Hash,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let conditional = Self::conditional_orders(&conditional_order_id)
+				.ok_or(<Error<T>>::ConditionalOrderNotExist)?;
+			ensure!(conditional.user_id == sender, <Error<T>>::OrderNotBelongToUser);
+
+			Self::refund_conditional_order(&conditional)?;
+			<PendingConditionalOrders<T>>::mutate(|ids| {
+				ids.retain(|id| *id != conditional_order_id)
+			});
+
+			Self::deposit_event(Event::ConditionalOrderCancelled(conditional_order_id));
+
+			Ok(())
+		}
+
+		/// Create a tournament, the sender is its organizer
+		/// - sponsorship: added to the prize pool by the organizer
+		/// - prize_split: share of the prize pool of each rank, best first
+		/// - pairs: the duplicates are removed
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2 + pairs.len() as u64, 2))]
+		pub fn create_tournament(
+			origin: OriginFor<T>,
+			asset_id: AssetIdOf<T>,
+			entry_fee: BalanceOf<T>,
+			starting_balance: BalanceOf<T>,
+			starts_at: u64,
+			ends_at: u64,
+			pairs: BoundedVec<PairId, T::MaxTournamentPairs>,
+			payout_rate: u32,
+			prize_split: Vec<Permill>,
+			sponsorship: BalanceOf<T>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let current_ts: u64 = T::TimeProvider::now().as_secs();
+			ensure!(
+				starts_at < ends_at && current_ts < ends_at && !starting_balance.is_zero(),
+				<Error<T>>::InvalidTournament
+			);
+			let mut pair_ids = pairs.into_inner();
+			pair_ids.sort();
+			pair_ids.dedup();
+			// can not fail, there are at most as many pairs as before
+			let pairs = BoundedVec::<PairId, T::MaxTournamentPairs>::try_from(pair_ids)
+				.map_err(|_| <Error<T>>::InvalidTournament)?;
+			ensure!(
+				!pairs.is_empty() && pairs.iter().all(|pair_id| <Pairs<T>>::contains_key(pair_id)),
+				<Error<T>>::InvalidTournament
+			);
+			ensure!(
+				payout_rate >= T::MinPayoutRate::get() && payout_rate <= T::MaxPayoutRate::get(),
+				<Error<T>>::InvalidTournament
+			);
+			let split_parts = prize_split
+				.iter()
+				.fold(0u32, |total, share| total.saturating_add(share.deconstruct()));
+			ensure!(
+				!prize_split.is_empty() &&
+					prize_split.len() <= T::MaxPrizeRanks::get() as usize &&
+					split_parts <= Permill::one().deconstruct(),
+				<Error<T>>::InvalidTournament
+			);
+
+			let tournament_id = Self::next_tournament_id();
+			let next_tournament_id =
+				tournament_id.checked_add(1).ok_or(<Error<T>>::TournamentIdOverflow)?;
+
+			if !sponsorship.is_zero() {
+				T::Assets::transfer(asset_id, &sender, &Self::account_id(), sponsorship, true)?;
+			}
+
+			<Tournaments<T>>::insert(
+				tournament_id,
+				Tournament::<T> {
+					organizer: sender.clone(),
+					asset_id,
+					entry_fee,
+					starting_balance,
+					starts_at,
+					ends_at,
+					pairs,
+					payout_rate,
+					prize_split,
+					prize_pool: sponsorship,
+					open_orders: 0,
+					status: TournamentStatus::Open,
+				},
+			);
+			<NextTournamentId<T>>::put(next_tournament_id);
+
+			Self::deposit_event(Event::TournamentCreated(tournament_id, sender));
+
+			Ok(())
+		}
+
+		/// Join a tournament before it ends, the entry fee goes to the prize pool
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		pub fn join_tournament(
+			origin: OriginFor<T>,
+			tournament_id: TournamentId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let mut tournament =
+				Self::tournaments(tournament_id).ok_or(<Error<T>>::TournamentNotExist)?;
+			let current_ts: u64 = T::TimeProvider::now().as_secs();
+			ensure!(
+				tournament.status == TournamentStatus::Open && current_ts < tournament.ends_at,
+				<Error<T>>::TournamentNotLive
+			);
+			ensure!(
+				Self::tournament_balances(tournament_id, &sender).is_none(),
+				<Error<T>>::AlreadyJoined
+			);
+			ensure!(
+				Self::tournament_participants(tournament_id).len() <
+					T::MaxParticipants::get() as usize,
+				<Error<T>>::TooManyParticipants
+			);
+			// the entry fee is a real stake
+			Self::ensure_risk_limits(&sender, tournament.entry_fee, current_ts)?;
+
+			if !tournament.entry_fee.is_zero() {
+				T::Assets::transfer(
+					tournament.asset_id,
+					&sender,
+					&Self::account_id(),
+					tournament.entry_fee,
+					true,
+				)?;
+				tournament.prize_pool = tournament.prize_pool.saturating_add(tournament.entry_fee);
+			}
+
+			<TournamentBalances<T>>::insert(tournament_id, &sender, tournament.starting_balance);
+			<TournamentParticipants<T>>::append(tournament_id, sender.clone());
+			<Tournaments<T>>::insert(tournament_id, tournament);
+
+			Self::deposit_event(Event::TournamentJoined(tournament_id, sender));
+
+			Ok(())
+		}
+
+		/// Place a virtual order with the tournament balance of the sender
+		/// - Settled as a pool order at the tournament payout rate, without touching the pools
+		/// - expired_at must be at or before the tournament end
+		/// - Touch / No-Touch orders are not supported, they would take `MaxBarrierOrders` slots
+		/// 	from the real orders
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 5))]
+		pub fn place_tournament_order(
+			origin: OriginFor<T>,
+			tournament_id: TournamentId,
+			pair_id: PairId,
+			trade_type: TradeType,
+			volume_in_unit: BalanceOf<T>,
+			expired_at: u64,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let mut tournament =
+				Self::tournaments(tournament_id).ok_or(<Error<T>>::TournamentNotExist)?;
+			let current_ts: u64 = T::TimeProvider::now().as_secs();
+			ensure!(
+				tournament.status == TournamentStatus::Open &&
+					tournament.starts_at <= current_ts &&
+					current_ts < tournament.ends_at,
+				<Error<T>>::TournamentNotLive
+			);
+			ensure!(tournament.pairs.contains(&pair_id), <Error<T>>::PairNotInTournament);
+			ensure!(expired_at <= tournament.ends_at, <Error<T>>::InvalidExpiredAt);
+			ensure!(!volume_in_unit.is_zero(), <Error<T>>::InvalidTradingVolume);
+			ensure!(!trade_type.is_path_dependent(), <Error<T>>::TournamentOrderNotSupported);
+			// excluded or cooling down accounts can not trade, the virtual stake is not at risk
+			Self::ensure_risk_limits(&sender, Zero::zero(), current_ts)?;
+
+			let pair = Self::pairs(pair_id).ok_or(<Error<T>>::PairNotFound)?;
+			ensure!(pair.enabled, <Error<T>>::PairDisabled);
+			ensure!(Self::pause_policy_of(pair_id).is_none(), <Error<T>>::TradingPaused);
+			Self::ensure_valid_expiry(&pair, current_ts, expired_at)?;
+
+			let open_price = T::SymbolPriceModule::get_price(pair.oracle_symbol)
+				.ok_or(<Error<T>>::PriceNotAvailable)?;
+			ensure!(trade_type.is_valid(open_price), <Error<T>>::InvalidBarrier);
+
+			let new_cnt: u64 =
+				Self::order_count().checked_add(1).ok_or(<Error<T>>::OrderCountOverflow)?;
+			let order_id = T::Hashing::hash_of(&(
+				&sender,
+				tournament_id,
+				pair_id,
+				&trade_type,
+				volume_in_unit,
+				expired_at,
+				current_ts,
+				new_cnt,
+			));
+
+			Self::watch_expiry(pair_id, expired_at)?;
+			<TournamentBalances<T>>::try_mutate(
+				tournament_id,
+				&sender,
+				|balance| -> DispatchResult {
+					let balance = balance.as_mut().ok_or(<Error<T>>::NotParticipant)?;
+					ensure!(*balance >= volume_in_unit, <Error<T>>::NotEnoughVirtualBalance);
+					*balance = balance.saturating_sub(volume_in_unit);
+					Ok(())
+				},
+			)?;
+
+			let order = Order::<T> {
+				id: order_id,
+				user_id: sender.clone(),
+				pair_id,
+				trade_type,
+				asset_id: tournament.asset_id,
+				volume_in_unit,
+				protocol_fee: Zero::zero(),
+				expired_at,
+				created_at: current_ts,
+				liquidity_pool_id: Self::account_id(),
+				payout_rate: tournament.payout_rate,
+				open_price,
+				close_price: None,
+				status: OrderStatus::Created,
+				barrier_hit: false,
+				market: MarketMode::Pool,
+				matches: Vec::new(),
+				matched_volume: Zero::zero(),
+				pool_volume: Zero::zero(),
+				tournament_id: Some(tournament_id),
+				bonus_expires_at: None,
+			};
+
+			<UserOrders<T>>::append(sender.clone(), order_id);
+			<Orders<T>>::insert(order_id, order);
+			<OrderCount<T>>::put(new_cnt);
+			tournament.open_orders = tournament.open_orders.saturating_add(1);
+			<Tournaments<T>>::insert(tournament_id, tournament);
+
+			log::info!("Tournament order created: {:?}.", order_id);
+			Self::deposit_event(Event::OrderCreated(sender, order_id, volume_in_unit, Zero::zero()));
+
+			Ok(())
+		}
+
+		/// Distribute the prizes of an ended tournament, callable by anyone
+		/// - Participants are ranked by final virtual balance, ties by joining order
+		/// - Every order of the tournament must be settled first
+		#[pallet::weight(
+			10_000 +
+				T::DbWeight::get()
+					.reads_writes(1, 1)
+					.saturating_mul(T::MaxParticipants::get() as u64)
+		)]
+		pub fn finish_tournament(
+			origin: OriginFor<T>,
+			tournament_id: TournamentId,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let mut tournament =
+				Self::tournaments(tournament_id).ok_or(<Error<T>>::TournamentNotExist)?;
+			ensure!(tournament.status == TournamentStatus::Open, <Error<T>>::TournamentNotLive);
+			let current_ts: u64 = T::TimeProvider::now().as_secs();
+			ensure!(
+				current_ts >= tournament.ends_at && tournament.open_orders == 0,
+				<Error<T>>::TournamentNotOver
+			);
+
+			let mut ranking: Vec<(T::AccountId, BalanceOf<T>)> =
+				Self::tournament_participants(tournament_id)
+					.into_iter()
+					.map(|account_id| {
+						let balance = Self::tournament_balances(tournament_id, &account_id)
+							.unwrap_or_else(Zero::zero);
+						(account_id, balance)
+					})
+					.collect();
+			// stable sort: ties keep the joining order
+			ranking.sort_by(|a, b| b.1.cmp(&a.1));
+
+			let mut prizes = Vec::new();
+			let mut paid: BalanceOf<T> = Zero::zero();
+			for ((account_id, _), share) in ranking.into_iter().zip(tournament.prize_split.iter()) {
+				let prize = *share * tournament.prize_pool;
+				if prize.is_zero() {
+					continue
+				}
+				T::Assets::transfer(
+					tournament.asset_id,
+					&Self::account_id(),
+					&account_id,
+					prize,
+					false,
+				)?;
+				paid = paid.saturating_add(prize);
+				prizes.push((account_id, prize));
+			}
+
+			let rest = tournament.prize_pool.saturating_sub(paid);
+			if !rest.is_zero() {
+				T::Assets::transfer(
+					tournament.asset_id,
+					&Self::account_id(),
+					&tournament.organizer,
+					rest,
+					false,
+				)?;
+			}
+
+			tournament.status = TournamentStatus::Finished;
+			<Tournaments<T>>::insert(tournament_id, tournament);
+
+			Self::deposit_event(Event::TournamentFinished { tournament_id, prizes });
+
+			Ok(())
+		}
+
+		/// Refund a tournament order still not settled `TournamentGracePeriod` after the end of
+		/// its tournament, callable by anyone
+		/// - The stake is credited back to the virtual balance, so that the tournament can finish
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		pub fn refund_tournament_order(origin: OriginFor<T>, order_id: T::Hash) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let order = Self::orders(order_id).ok_or(<Error<T>>::OrderNotExist)?;
+			ensure!(order.status == OrderStatus::Created, <Error<T>>::OrderNotOpen);
+			let tournament_id = order.tournament_id.ok_or(<Error<T>>::TournamentNotExist)?;
+			let tournament =
+				Self::tournaments(tournament_id).ok_or(<Error<T>>::TournamentNotExist)?;
+			let current_ts: u64 = T::TimeProvider::now().as_secs();
+			ensure!(
+				current_ts >= tournament.ends_at.saturating_add(T::TournamentGracePeriod::get()),
+				<Error<T>>::TournamentNotOver
+			);
+
+			let (status, amount_payout) =
+				Self::settle_tournament_order(&order, order.open_price, true)?;
+			<Orders<T>>::mutate(order_id, |order| {
+				if let Some(order) = order {
+					order.status = status.clone();
+				}
+			});
+
+			Self::deposit_event(Event::OrderClosed {
+				account_id: order.user_id,
+				order_id,
+				close_price: order.open_price,
+				status,
+				amount_payout,
+				protocol_fee: Zero::zero(),
+			});
+
+			Ok(())
+		}
+
+		/// Set the account signing the bonus vouchers, None disable the vouchers
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_voucher_issuer(
+			origin: OriginFor<T>,
+			issuer: Option<T::AccountId>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			match &issuer {
+				Some(issuer) => <VoucherIssuer<T>>::put(issuer),
+				None => <VoucherIssuer<T>>::kill(),
+			}
+
+			Self::deposit_event(Event::VoucherIssuerUpdated(issuer));
+
+			Ok(())
+		}
+
+		/// Redeem a voucher signed by the voucher issuer for bonus credits
+		/// - proof: signature of the voucher issuer over `voucher_payload`
+		/// - Only the beneficiary can redeem it, a voucher code can be redeemed once
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+		pub fn redeem_voucher(
+			origin: OriginFor<T>,
+			voucher: Voucher<T>,
+			proof: T::VoucherSignature,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let issuer = Self::voucher_issuer().ok_or(<Error<T>>::InvalidVoucher)?;
+			let payload = Self::voucher_payload(&voucher);
+			ensure!(proof.verify(&payload[..], &issuer), <Error<T>>::InvalidVoucher);
+			ensure!(!Self::redeemed_vouchers(&voucher.code), <Error<T>>::VoucherAlreadyRedeemed);
+			ensure!(voucher.beneficiary == sender, <Error<T>>::VoucherNotForYou);
+			let current_ts: u64 = T::TimeProvider::now().as_secs();
+			ensure!(
+				current_ts <= voucher.redeem_before && current_ts < voucher.credit_expires_at,
+				<Error<T>>::VoucherExpired
+			);
+
+			<BonusCredits<T>>::try_mutate(&sender, voucher.asset_id, |grants| -> DispatchResult {
+				grants.retain(|grant| grant.expires_at > current_ts);
+				ensure!(
+					grants.len() < T::MaxCreditGrants::get() as usize,
+					<Error<T>>::TooManyCreditGrants
+				);
+				let index = grants
+					.iter()
+					.position(|grant| grant.expires_at > voucher.credit_expires_at)
+					.unwrap_or(grants.len());
+				grants.insert(
+					index,
+					CreditGrant { amount: voucher.amount, expires_at: voucher.credit_expires_at },
+				);
+				Ok(())
+			})?;
+			<RedeemedVouchers<T>>::insert(&voucher.code, true);
+
+			Self::deposit_event(Event::VoucherRedeemed {
+				account_id: sender,
+				code: voucher.code,
+				asset_id: voucher.asset_id,
+				amount: voucher.amount,
+				expires_at: voucher.credit_expires_at,
+			});
+
+			Ok(())
+		}
+
+		/// Create an order whose stake is funded by the bonus credits of the sender
+		/// - The treasury provides the stake, the soonest expiring credits are spent first
+		/// - A winning order pays its profit only, the stake goes back to the treasury
+		#[pallet::weight(
+			T::WeightInfo::place_order(T::MaxPools::get(), 0) +
+				T::DbWeight::get().reads_writes(1, 1)
+		)]
+		pub fn place_bonus_order(
+			origin: OriginFor<T>,
+			pair_id: PairId,
+			trade_type: TradeType,
+			volume_in_unit: BalanceOf<T>,
+			expired_at: u64,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// the credits are spent before the treasury transfer
+			with_transaction(|| {
+				match Self::do_place_order(
+					sender,
+					pair_id,
+					trade_type,
+					volume_in_unit,
+					expired_at,
+					true,
+				) {
+					Ok(_) => TransactionOutcome::Commit(Ok(())),
+					Err(e) => TransactionOutcome::Rollback(Err(e)),
+				}
+			})
+		}
+
+		/// Bind the sender to a referrer, once and before its first order
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+			2 + T::MaxReferralTiers::get() as u64,
+			1,
+		))]
+		pub fn set_referrer(origin: OriginFor<T>, referrer: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(!<Referrers<T>>::contains_key(&sender), <Error<T>>::AlreadyReferred);
+			ensure!(Self::user_orders(&sender).is_empty(), <Error<T>>::NotNewAccount);
+			// no cycle within the tiers paid on an order
+			let mut upline = Some(referrer.clone());
+			for _ in 0..T::MaxReferralTiers::get() {
+				match upline {
+					Some(account) => {
+						ensure!(account != sender, <Error<T>>::InvalidReferrer);
+						upline = Self::referrers(&account);
+					},
+					None => break,
+				}
+			}
+
+			<Referrers<T>>::insert(&sender, &referrer);
+
+			Self::deposit_event(Event::ReferrerSet(sender, referrer));
+
+			Ok(())
+		}
+
+		/// Update the referral program
+		/// - tiers: share of the source for each tier, the direct referrer first,
+		/// 	empty to disable the rebates
+		/// - source: what the rebates are a share of
+		///
+		/// The tiers share at most 100% minus `KeeperRewardBps`: the protocol fee of an order
+		/// pays both the keeper reward and the rebates
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_referral_program(
+			origin: OriginFor<T>,
+			tiers: Vec<Permill>,
+			source: RebateSource,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				tiers.len() <= T::MaxReferralTiers::get() as usize,
+				<Error<T>>::InvalidReferralProgram
+			);
+			let total =
+				tiers.iter().fold(0u32, |total, tier| total.saturating_add(tier.deconstruct()));
+			let keeper_parts = T::KeeperRewardBps::get().saturating_mul(100);
+			ensure!(
+				total <= Permill::one().deconstruct().saturating_sub(keeper_parts),
+				<Error<T>>::InvalidReferralProgram
+			);
+
+			let program = ReferralProgram { tiers, source };
+			<Referral<T>>::put(&program);
+
+			Self::deposit_event(Event::ReferralProgramUpdated(program));
+
+			Ok(())
+		}
+
+		/// Claim the referral rebates accrued in an asset, paid by the treasury
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn claim_referral_rebates(
+			origin: OriginFor<T>,
+			asset_id: AssetIdOf<T>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let amount = <ReferralRebates<T>>::take(&sender, asset_id);
+			ensure!(!amount.is_zero(), <Error<T>>::NoReferralRebate);
+			T::Assets::transfer(asset_id, &Self::treasury_account_id(), &sender, amount, false)?;
+
+			Self::deposit_event(Event::ReferralRebateClaimed(sender, asset_id, amount));
+
+			Ok(())
+		}
+
+		/// Validate, finish this order
+		/// - Determine this is win or loose
+		/// - So dome money transfer logic
+		/// - Unsigned, submitted by the offchain worker, the order is settled at the first on-chain
+		/// 	price at or after its expiry
+		#[pallet::weight(T::WeightInfo::close_order(0))]
+		pub fn close_order(
+			origin: OriginFor<T>,
+			block_number: T::BlockNumber,
+			order_id: T::Hash,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			ensure!(!Self::settlement_paused(), <Error<T>>::SettlementPaused);
+
+			let order = Self::orders(&order_id).ok_or(<Error<T>>::OrderNotExist)?;
+			ensure!(order.status == OrderStatus::Created, <Error<T>>::OrderNotOpen);
+			let current_ts = T::TimeProvider::now().as_secs();
+			ensure!(order.expired_at <= current_ts, <Error<T>>::OrderNotExpired);
+			let close_price = Self::settlement_price(order.pair_id, order.expired_at)
+				.ok_or(<Error<T>>::SettlementPriceNotAvailable)?;
+
+			Self::do_close_order(order_id, close_price)?;
+
+			/*
+			let sender = ensure_signed(origin)?;
+			log::info!("close_order: order_id, close_price: {:?}, {:?}", order_id, close_price);
+			Self::deposit_event(Event::OrderClosed {
+				account_id: sender,
+				order_id,
+				close_price,
+				status,
+				amount_payout: volumn_payout,
+			});
+			*/
+
+			<NextUnsignedAt<T>>::put(block_number + T::UnsignedInterval::get());
+
+			// Update info Orders
+			// Orders::<T>::try_mutate_exists(&order_id, |order| -> DispatchResult {
+			// 	let mut order = order.as_mut().ok_or(Error::<T>::OrderNotExist)?;
+			// 	Ok(())
+			// })?;
+			// Orders::<T>::try_mutate_exists(&order_id, |_| -> DispatchResult {
+			// 	let _order = order;
+			// 	Ok(())
+			// })?;
+
+			// TODO: Remove order in Orders -> push to OrderCompleted
+
+			Ok(())
+		}
+
+		/// Settle a batch of expired orders at their settlement price, callable by anyone (keepers)
+		/// - Orders not expired, already closed or without a settlement price yet are skipped
+		/// - The keeper gets `KeeperRewardBps` of the protocol fees of each settled order
+		/// - The call is free if at least one order was settled
+		#[pallet::weight(
+			10_000 + T::DbWeight::get().reads_writes(6, 6).saturating_mul(order_ids.len() as u64)
+		)]
+		pub fn settle_expired(
+			origin: OriginFor<T>,
+			order_ids: Vec<T::Hash>,
+		) -> DispatchResultWithPostInfo {
+			let keeper = ensure_signed(origin)?;
+			ensure!(!Self::settlement_paused(), <Error<T>>::SettlementPaused);
+			ensure!(
+				order_ids.len() <= T::MaxSettleBatch::get() as usize,
+				<Error<T>>::TooManyOrdersToSettle
+			);
+
+			let current_ts = T::TimeProvider::now().as_secs();
+			let mut settled: u32 = 0;
+			for order_id in order_ids.iter() {
+				let close_price = match Self::settlement_price_of(order_id, current_ts) {
+					Some(price) => price,
+					None => continue,
+				};
+
+				// An order failing to settle is rolled back by `do_close_order`
+				let (order, profit_fee) = match Self::do_close_order(*order_id, close_price) {
+					Ok(closed) => closed,
+					Err(e) => {
+						log::warn!("settle_expired: order {:?} failed: {:?}", order_id, e);
+						continue
+					},
+				};
+				settled = settled.saturating_add(1);
+
+				let reward = Self::keeper_reward_of(order.protocol_fee.saturating_add(profit_fee));
+				if !reward.is_zero() &&
+					T::Assets::transfer(
+						order.asset_id,
+						&Self::treasury_account_id(),
+						&keeper,
+						reward,
+						true,
+					)
+					.is_ok()
+				{
+					Self::deposit_event(Event::KeeperRewarded(keeper.clone(), *order_id, reward));
+				}
+			}
+
+			Self::deposit_event(Event::ExpiredOrdersSettled(keeper, settled));
+
+			// Useful work is free, a batch settling nothing pays its fee
+			if settled > 0 {
+				Ok(Pays::No.into())
+			} else {
+				Ok(Pays::Yes.into())
+			}
+		}
+
+		/// Sell an open order back to its liquidity pool before expiry
+		/// - The pool pays the quoted value of the order at the current price
+		/// - Not allowed within `SellBackLockPeriod` seconds before expired_at
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn sell_back_order(origin: OriginFor<T>, order_id: T::Hash) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let order = Orders::<T>::get(&order_id).ok_or(<Error<T>>::OrderNotExist)?;
+			ensure!(order.user_id == sender, <Error<T>>::OrderNotBelongToUser);
+			ensure!(order.status == OrderStatus::Created, <Error<T>>::OrderNotOpen);
+			ensure!(order.market == MarketMode::Pool, <Error<T>>::PoolOrderOnly);
+			ensure!(order.tournament_id.is_none(), <Error<T>>::TournamentOrderNotSupported);
+			ensure!(!order.is_bonus(), <Error<T>>::BonusOrderNotSupported);
+			ensure!(order.trade_type.can_sell_back(), <Error<T>>::SellBackNotSupported);
+
+			let current_ts: u64 = T::TimeProvider::now().as_secs();
+			ensure!(
+				current_ts.saturating_add(T::SellBackLockPeriod::get()) < order.expired_at,
+				<Error<T>>::SellBackLocked
+			);
+
+			let current_price =
+				Self::current_price(order.pair_id).ok_or(<Error<T>>::PriceNotAvailable)?;
+			let amount_payout = Self::quote_sell_back(&order, current_price, current_ts);
+			ensure!(
+				T::Assets::reducible_balance(order.asset_id, &order.liquidity_pool_id, true) >=
+					amount_payout,
+				<Error<T>>::PoolNotEnoughBalance
+			);
+
+			// Transfer first, nothing is written if it fails
+			T::Assets::transfer(order.asset_id, &order.liquidity_pool_id, &sender, amount_payout, true)?;
+
+			Orders::<T>::try_mutate_exists(&order_id, |order| -> DispatchResult {
+				let mut order = order.as_mut().ok_or(Error::<T>::OrderNotExist)?;
+				order.status = OrderStatus::Sold;
+				order.close_price = Some(current_price);
+				Ok(())
+			})?;
+			Self::release_liability(&order);
+			Self::record_order_closed(&order, &OrderStatus::Sold, amount_payout);
+			<CopiedOrders<T>>::remove(&order_id);
+
+			// Update LP balance
+			T::BoLiquidity::update_lp_balance(order.liquidity_pool_id);
+
+			log::info!("Order sold: {:?}, amount_payout: {:?}", order_id, amount_payout);
+			Self::deposit_event(Event::OrderSold {
+				account_id: sender,
+				order_id,
+				price: current_price,
+				amount_payout,
+			});
+
+			Ok(())
+		}
+
+		/// Cancel an order placed by mistake
+		/// - Only allowed within `CancelWindow` seconds after created_at
+		/// - The stake is refunded from the pool, minus `CancelFee`
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn cancel_order(origin: OriginFor<T>, order_id: T::Hash) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let order = Orders::<T>::get(&order_id).ok_or(<Error<T>>::OrderNotExist)?;
+			ensure!(order.user_id == sender, <Error<T>>::OrderNotBelongToUser);
+			ensure!(order.status == OrderStatus::Created, <Error<T>>::OrderNotOpen);
+			ensure!(order.market == MarketMode::Pool, <Error<T>>::PoolOrderOnly);
+			ensure!(order.tournament_id.is_none(), <Error<T>>::TournamentOrderNotSupported);
+			ensure!(!order.is_bonus(), <Error<T>>::BonusOrderNotSupported);
+
+			let current_ts: u64 = T::TimeProvider::now().as_secs();
+			ensure!(
+				current_ts <= order.created_at.saturating_add(T::CancelWindow::get()),
+				<Error<T>>::CancelWindowPassed
+			);
+
+			let fee = T::CancelFee::get() * order.volume_in_unit;
+			let amount_refund = order.volume_in_unit - fee;
+
+			// Transfer first, nothing is written if it fails
+			T::Assets::transfer(order.asset_id, &order.liquidity_pool_id, &sender, amount_refund, true)?;
+
+			// Cancelled orders are skipped by the settlement scan
+			Orders::<T>::try_mutate_exists(&order_id, |order| -> DispatchResult {
+				let mut order = order.as_mut().ok_or(Error::<T>::OrderNotExist)?;
+				order.status = OrderStatus::Cancelled;
+				Ok(())
+			})?;
+			Self::release_liability(&order);
+			Self::record_order_closed(&order, &OrderStatus::Cancelled, amount_refund);
+			<CopiedOrders<T>>::remove(&order_id);
+			<UserOrders<T>>::mutate(&sender, |order_ids| order_ids.retain(|id| *id != order_id));
+
+			// Update LP balance
+			T::BoLiquidity::update_lp_balance(order.liquidity_pool_id);
+
+			log::info!("Order cancelled: {:?}, amount_refund: {:?}", order_id, amount_refund);
+			Self::deposit_event(Event::OrderCancelled { account_id: sender, order_id, amount_refund });
+
+			Ok(())
+		}
+
+		/// Create a Call / Put order traded against opposite orders of the same pair and expiry
+		/// - The stake is held by the pallet account until the order is settled
+		/// - The order is matched against the oldest opposite orders first, partial fills allowed
+		/// - pool_residual: the unmatched volume is traded against a liquidity pool,
+		/// 	otherwise it waits for future opposite orders and is refunded at expiry
+		#[pallet::weight(
+			(10_000 + T::DbWeight::get().reads_writes(4, 4))
+				.saturating_add(Self::pools_visited_weight(T::MaxPools::get()))
+		)]
+		pub fn place_p2p_order(
+			origin: OriginFor<T>,
+			pair_id: PairId,
+			trade_type: TradeType,
+			volume_in_unit: BalanceOf<T>,
+			expired_at: u64,
+			pool_residual: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let (current_ts, pair) =
+				Self::ensure_order_params(&sender, pair_id, volume_in_unit, expired_at)?;
+			let asset_id = pair.asset_id;
+			let side = match trade_type {
+				TradeType::Call | TradeType::Put => trade_type.side(),
+				_ => None,
+			}
+			.ok_or(<Error<T>>::P2POrderTypeNotSupported)?;
+			let counter_side = match side {
+				PositionSide::Call => PositionSide::Put,
+				PositionSide::Put => PositionSide::Call,
+			};
+
+			let book = (pair_id, expired_at);
+			ensure!(
+				Self::resting_orders(&book, &side).len() < T::MaxRestingOrders::get() as usize,
+				<Error<T>>::TooManyRestingOrders
+			);
+
+			let new_cnt: u64 =
+				Self::order_count().checked_add(1).ok_or(<Error<T>>::OrderCountOverflow)?;
+			let open_price = T::SymbolPriceModule::get_price(pair.oracle_symbol.clone())
+				.ok_or(<Error<T>>::PriceNotAvailable)?;
+			let order_id = T::Hashing::hash_of(&(
+				&sender,
+				pair_id,
+				&trade_type,
+				volume_in_unit,
+				expired_at,
+				current_ts,
+				new_cnt,
+			));
+
+			Self::watch_expiry(pair_id, expired_at)?;
+			// The whole stake is escrowed, the pool residual is moved to the pool below
+			T::Assets::transfer(asset_id, &sender, &Self::p2p_account_id(), volume_in_unit, true)?;
+			let protocol_fee = Self::stake_fee_of(volume_in_unit);
+			Self::collect_protocol_fee(asset_id, &sender, protocol_fee, current_ts)?;
+
+			let mut order = Order::<T> {
+				id: order_id,
+				user_id: sender.clone(),
+				pair_id,
+				trade_type,
+				asset_id,
+				volume_in_unit,
+				protocol_fee,
+				expired_at,
+				created_at: current_ts,
+				liquidity_pool_id: Self::p2p_account_id(),
+				payout_rate: 100, // matched volume is paid 1:1 by the counterparty
+				open_price,
+				close_price: None,
+				status: OrderStatus::Created,
+				barrier_hit: false,
+				market: MarketMode::PeerToPeer,
+				matches: Vec::new(),
+				matched_volume: Zero::zero(),
+				pool_volume: Zero::zero(),
+				tournament_id: None,
+				bonus_expires_at: None,
+			};
+
+			// ---- Match against the opposite side, FIFO ------
+			let mut filled = Vec::new();
+			for counter_id in Self::resting_orders(&book, &counter_side).iter() {
+				let remaining = order.unmatched_volume();
+				if remaining.is_zero() {
+					break
+				}
+
+				let mut counter = match Orders::<T>::get(counter_id) {
+					Some(counter) if counter.status == OrderStatus::Created => counter,
+					_ => {
+						filled.push(*counter_id);
+						continue
+					},
+				};
+				let amount = remaining.min(counter.unmatched_volume());
+
+				counter.matches.push(OrderMatch {
+					counterparty: order_id,
+					amount,
+					price: open_price,
+					settled: None,
+				});
+				counter.matched_volume += amount;
+				if counter.unmatched_volume().is_zero() {
+					filled.push(*counter_id);
+				}
+				<Orders<T>>::insert(counter_id, counter);
+
+				order.matches.push(OrderMatch {
+					counterparty: *counter_id,
+					amount,
+					price: open_price,
+					settled: None,
+				});
+				order.matched_volume += amount;
+
+				Self::deposit_event(Event::OrderMatched {
+					order_id,
+					counterparty: *counter_id,
+					amount,
+					price: open_price,
+				});
+			}
+			<RestingOrders<T>>::mutate(&book, &counter_side, |order_ids| {
+				order_ids.retain(|id| !filled.contains(id))
+			});
+
+			// ---- Residual ------
+			let residual = order.unmatched_volume();
+			let residual_lp = if pool_residual && !residual.is_zero() {
+				Self::pool_for(asset_id, residual).0
+			} else {
+				None
+			};
+			match residual_lp {
+				Some(lp_id) => {
+					let base_payout_rate = T::BoLiquidity::get_payout_rate(&lp_id)
+						.ok_or(<Error<T>>::NoLiquidityPool)?;
+					order.payout_rate = Self::imbalance_payout_rate(
+						base_payout_rate,
+						&lp_id,
+						&pair.oracle_symbol,
+						&order.trade_type,
+						residual,
+					);
+					order.pool_volume = residual;
+					order.liquidity_pool_id = lp_id.clone();
+
+					T::Assets::transfer(asset_id, &Self::p2p_account_id(), &lp_id, residual, false)?;
+					T::BoLiquidity::add_liability(
+						&lp_id,
+						&pair.oracle_symbol,
+						side,
+						Self::payout_of(residual, order.payout_rate),
+					);
+					T::BoLiquidity::update_lp_balance(lp_id);
+				},
+				None if !residual.is_zero() => <RestingOrders<T>>::append(&book, &side, order_id),
+				None => {},
+			}
+
+			<UserOrders<T>>::append(sender.clone(), order_id);
+			<Orders<T>>::insert(order_id, order);
+			<OrderCount<T>>::put(new_cnt);
+			Self::record_order_opened(&sender, volume_in_unit);
+
+			log::info!("P2P order created: {:?}.", order_id);
+			Self::deposit_event(Event::OrderCreated(sender, order_id, volume_in_unit, protocol_fee));
+
+			Ok(())
+		}
+
+		/// Add a tradable pair to the registry
+		/// - Empty timeframes mean the pair fallback to `DefaultTimeframes`
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn add_pair(origin: OriginFor<T>, pair: TradingPair<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_valid_pair(&pair)?;
+
+			let pair_id = Self::next_pair_id();
+			let next_pair_id = pair_id.checked_add(1).ok_or(<Error<T>>::PairIdOverflow)?;
+
+			<Pairs<T>>::insert(pair_id, pair);
+			<NextPairId<T>>::put(next_pair_id);
+
+			Self::deposit_event(Event::PairAdded(pair_id));
+
+			Ok(())
+		}
+
+		/// Update a pair of the registry, eg: timeframes, stake range, enabled
+		/// - the oracle symbol can not be changed
+		/// - open orders keep the asset they were placed with
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn update_pair(
+			origin: OriginFor<T>,
+			pair_id: PairId,
+			pair: TradingPair<T>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_valid_pair(&pair)?;
+
+			let current = Self::pairs(pair_id).ok_or(<Error<T>>::PairNotFound)?;
+			ensure!(current.oracle_symbol == pair.oracle_symbol, <Error<T>>::InvalidPair);
+
+			<Pairs<T>>::insert(pair_id, pair);
+
+			Self::deposit_event(Event::PairUpdated(pair_id));
+
+			Ok(())
+		}
+
+		/// Pause new orders of a pair, or of every pair if no pair
+		/// - policy: what happens at expiry to the orders already open
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn pause_trading(
+			origin: OriginFor<T>,
+			pair_id: Option<PairId>,
+			policy: PausePolicy,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			match pair_id {
+				Some(pair_id) => <PairPauses<T>>::insert(pair_id, policy),
+				None => <GlobalPause<T>>::put(policy),
+			}
+
+			Self::deposit_event(Event::Paused(pair_id, policy));
+
+			Ok(())
+		}
+
+		/// Resume new orders of a pair, or lift the global pause if no pair
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn resume_trading(
+			origin: OriginFor<T>,
+			pair_id: Option<PairId>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			match pair_id {
+				Some(pair_id) => <PairPauses<T>>::remove(pair_id),
+				None => <GlobalPause<T>>::kill(),
+			}
+
+			Self::deposit_event(Event::Resumed(pair_id));
+
+			Ok(())
+		}
+
+		/// Pause or resume the settlement of expired orders
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_settlement_paused(origin: OriginFor<T>, paused: bool) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			<SettlementPaused<T>>::put(paused);
+
+			Self::deposit_event(Event::SettlementPausedUpdated(paused));
+
+			Ok(())
+		}
+
+		/// Tighten the trading limits of the sender
+		/// - every limit must be at least as tight as the current one
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_self_limits(origin: OriginFor<T>, limits: RiskLimits<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(
+				limits.is_tighter_than(&Self::self_limits(&sender)),
+				<Error<T>>::CanOnlyTightenLimits
+			);
+
+			<SelfLimits<T>>::insert(&sender, limits);
+
+			Self::deposit_event(Event::SelfLimitsUpdated(sender));
+
+			Ok(())
+		}
+
+		/// Exclude the sender from trading for `duration` seconds
+		/// - an ongoing exclusion can only be extended
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn self_exclude(origin: OriginFor<T>, duration: u64) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let until = T::TimeProvider::now().as_secs().saturating_add(duration);
+			let excluded_until = <AccountRisks<T>>::mutate(&sender, |risk| {
+				risk.excluded_until = risk.excluded_until.max(until);
+				risk.excluded_until
+			});
+
+			Self::deposit_event(Event::AccountExcluded(sender, excluded_until));
+
+			Ok(())
+		}
+
+		/// Set the operator limits of an account, and exclude it until `excluded_until` (unix_ts)
+		/// - the account is bound by the tightest of its self limits and operator limits
+		/// - excluded_until = None keep the current exclusion
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn set_operator_limits(
+			origin: OriginFor<T>,
+			account_id: T::AccountId,
+			limits: RiskLimits<T>,
+			excluded_until: Option<u64>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			<OperatorLimits<T>>::insert(&account_id, limits);
+			Self::deposit_event(Event::OperatorLimitsUpdated(account_id.clone()));
+
+			if let Some(excluded_until) = excluded_until {
+				<AccountRisks<T>>::mutate(&account_id, |risk| risk.excluded_until = excluded_until);
+				Self::deposit_event(Event::AccountExcluded(account_id, excluded_until));
+			}
+
+			Ok(())
+		}
+
+		/// Set the protocol fees, in basis points
+		/// - stake_fee_bps: charged on top of the stake of every new order
+		/// - profit_fee_bps: charged to the pool on the stake of every losing order
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_protocol_fees(
+			origin: OriginFor<T>,
+			stake_fee_bps: u32,
+			profit_fee_bps: u32,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(
+				stake_fee_bps <= T::MaxProtocolFeeBps::get() &&
+					profit_fee_bps <= T::MaxProtocolFeeBps::get(),
+				<Error<T>>::InvalidProtocolFee
+			);
+
+			<ProtocolFees<T>>::put(ProtocolFee { stake_fee_bps, profit_fee_bps });
+
+			Self::deposit_event(Event::ProtocolFeesUpdated(stake_fee_bps, profit_fee_bps));
+
+			Ok(())
+		}
+	}
+
+	// pub const RAW_AMOUNT_SCALE: f64 = 100 as f64;
+
+	/// Internal helpers fn
+	impl<T: Config> Pallet<T> {
+		// pub fn raw_amount_2_amount(raw_amount: u32) -> f64 {
+		// 	(raw_amount / RAW_AMOUNT_SCALE) as f64
+		// }
+		//
+		// pub fn amount_2_raw_amount(amount: f64) -> u32 {
+		// 	amount * RAW_AMOUNT_SCALE
+		// }
+
+		pub fn hash_str<S: Encode>(s: &S) -> T::Hash {
+			T::Hashing::hash_of(s)
+		}
+
+		// How to convert u64 <=> Balance : https://stackoverflow.com/a/56081118/4984888
+		pub fn u64_to_balance(input: u64) -> Option<BalanceOf<T>> {
+			input.try_into().ok()
+		}
+
+		pub fn balance_to_u64(input: BalanceOf<T>) -> Option<u64> {
+			TryInto::<u64>::try_into(input).ok()
+		}
+
+		// How to convert BlockNumber <=> u64 :
+		pub fn blocknumber_to_u64(input: T::BlockNumber) -> Option<u64> {
+			TryInto::<u64>::try_into(input).ok()
+		}
+
+		/// The account holding the escrowed stakes, eg: of the conditional orders
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account()
+		}
+
+		/// The account holding the stakes of the peer-to-peer orders
+		pub fn p2p_account_id() -> T::AccountId {
+			T::PalletId::get().into_sub_account(b"p2p")
+		}
+
+		/// The account receiving the protocol fees
+		pub fn treasury_account_id() -> T::AccountId {
+			T::TreasuryPalletId::get().into_account()
+		}
+
+		/// Bytes signed by the voucher issuer: ("bo_voucher", genesis hash, voucher) SCALE encoded
+		/// - The genesis hash keeps a voucher of one chain from being redeemed on another
+		pub fn voucher_payload(voucher: &Voucher<T>) -> Vec<u8> {
+			let genesis_hash = <frame_system::Pallet<T>>::block_hash(T::BlockNumber::zero());
+			(b"bo_voucher", genesis_hash, voucher).encode()
+		}
+
+		/// Protocol fee charged on top of a stake of `volume`
+		pub fn stake_fee_of(volume: BalanceOf<T>) -> BalanceOf<T> {
+			Permill::from_parts(Self::protocol_fees().stake_fee_bps.saturating_mul(100)) * volume
+		}
+
+		/// Protocol fee charged to the pool when it wins a stake of `volume`
+		pub fn profit_fee_of(volume: BalanceOf<T>) -> BalanceOf<T> {
+			Permill::from_parts(Self::protocol_fees().profit_fee_bps.saturating_mul(100)) * volume
+		}
+
+		/// Send a protocol fee to the treasury and account it in the period of `unix_ts`
+		fn collect_protocol_fee(
+			asset_id: AssetIdOf<T>,
+			from: &T::AccountId,
+			amount: BalanceOf<T>,
+			unix_ts: u64,
+		) -> DispatchResult {
+			if amount.is_zero() {
+				return Ok(())
+			}
+
+			T::Assets::transfer(asset_id, from, &Self::treasury_account_id(), amount, true)?;
+			let period = unix_ts / T::FeePeriod::get().max(1);
+			<FeesCollected<T>>::mutate(asset_id, period, |total| {
+				*total = total.saturating_add(amount)
+			});
+
+			Ok(())
+		}
+
+		/// Protocol fees of an asset collected in each period of [from_period, to_period],
+		/// at most 1000 periods
+		/// Used by the runtime api
+		pub fn fees_collected(
+			asset_id: AssetIdOf<T>,
+			from_period: u64,
+			to_period: u64,
+		) -> Vec<(u64, BalanceOf<T>)> {
+			let to_period = to_period.min(from_period.saturating_add(999));
+			(from_period..=to_period)
+				.map(|period| (period, Self::fees_collected_in(asset_id, period)))
+				.collect()
+		}
+
+		/// Validate every leg of a batch before placing any of them
+		/// - each leg must pass `ensure_order_params`
+		/// - the user must have enough free balance for the stakes and fees of all legs
+		/// - the risk limits must allow all legs together
+		fn ensure_batch_params(
+			sender: &T::AccountId,
+			orders: &[OrderRequest<T>],
+		) -> DispatchResult {
+			// total stake + fee per asset
+			let mut totals: Vec<(AssetIdOf<T>, BalanceOf<T>)> = Vec::new();
+			let mut total_stake: BalanceOf<T> = Zero::zero();
+			for request in orders.iter() {
+				let (_, pair) = Self::ensure_order_params(
+					sender,
+					request.pair_id,
+					request.volume_in_unit,
+					request.expired_at,
+				)?;
+
+				let amount = request
+					.volume_in_unit
+					.saturating_add(Self::stake_fee_of(request.volume_in_unit));
+				match totals.iter_mut().find(|(asset_id, _)| *asset_id == pair.asset_id) {
+					Some((_, total)) => *total = total.saturating_add(amount),
+					None => totals.push((pair.asset_id, amount)),
+				}
+				total_stake = total_stake.saturating_add(request.volume_in_unit);
+			}
+
+			for (asset_id, total) in totals.into_iter() {
+				ensure!(
+					T::Assets::reducible_balance(asset_id, sender, true) >= total,
+					<Error<T>>::NotEnoughBalance
+				);
+			}
+
+			let limits = Self::risk_limits_of(sender);
+			let risk = Self::account_risk(sender);
+			if let Some(max_open_orders) = limits.max_open_orders {
+				ensure!(
+					risk.open_orders.saturating_add(orders.len() as u32) <= max_open_orders,
+					<Error<T>>::TooManyOpenOrders
+				);
+			}
+			if let Some(max_open_stake) = limits.max_open_stake {
+				ensure!(
+					risk.open_stake.saturating_add(total_stake) <= max_open_stake,
+					<Error<T>>::OpenStakeLimitExceeded
+				);
+			}
+			if let Some(daily_loss_cap) = limits.daily_loss_cap {
+				let potential_loss = risk
+					.loss_on(T::TimeProvider::now().as_secs())
+					.saturating_add(risk.open_stake)
+					.saturating_add(total_stake);
+				ensure!(potential_loss <= daily_loss_cap, <Error<T>>::DailyLossCapReached);
+			}
+
+			Ok(())
+		}
+
+		/// Create an order traded against a liquidity pool
+		/// Returns the order id and the number of pools visited to pick its pool
+		/// - bonus: the stake is funded by the treasury for bonus credits of the sender,
+		/// 	no protocol fee is charged
+		fn do_place_order(
+			sender: T::AccountId,
+			pair_id: PairId,
+			trade_type: TradeType,
+			volume_in_unit: BalanceOf<T>,
+			expired_at: u64,
+			bonus: bool,
+		) -> Result<(T::Hash, u32), DispatchError> {
+			// ----- validation ------
+			let (current_ts, pair) = if bonus {
+				Self::ensure_tradable(&sender, pair_id, volume_in_unit, expired_at)?
+			} else {
+				Self::ensure_order_params(&sender, pair_id, volume_in_unit, expired_at)?
+			};
+			let asset_id = pair.asset_id;
+
+			// Performs this operation first as it may fail
+			let new_cnt: u64 =
+				Self::order_count().checked_add(1).ok_or(<Error<T>>::OrderCountOverflow)?;
+
+			// select a pool of the pair asset for this order
+			let (suitable_lp_id, pools_visited) = Self::pool_for(asset_id, volume_in_unit);
+			ensure!(suitable_lp_id.is_some(), <Error<T>>::NoLiquidityPool);
+
+			let open_price = T::SymbolPriceModule::get_price(pair.oracle_symbol.clone())
+				.ok_or(<Error<T>>::PriceNotAvailable)?;
+
+			ensure!(trade_type.is_valid(open_price), <Error<T>>::InvalidBarrier);
+			let is_path_dependent = trade_type.is_path_dependent();
+			if is_path_dependent {
+				ensure!(
+					Self::barrier_orders().len() < T::MaxBarrierOrders::get() as usize,
+					<Error<T>>::TooManyBarrierOrders
+				);
+			}
+
+			// strike orders are quoted by the pool pricing curve
+			let lp_id = suitable_lp_id.as_ref().unwrap();
+			let base_payout_rate = match trade_type.strike_distance(open_price) {
+				Some(distance) => T::BoLiquidity::get_strike_payout_rate(lp_id, distance)
+					.ok_or(<Error<T>>::StrikeOutOfRange)?,
+				None => T::BoLiquidity::get_payout_rate(lp_id).ok_or(<Error<T>>::NoLiquidityPool)?,
+			};
+			let payout_rate = Self::imbalance_payout_rate(
+				base_payout_rate,
+				lp_id,
+				&pair.oracle_symbol,
+				&trade_type,
+				volume_in_unit,
+			);
+
+			Self::watch_expiry(pair_id, expired_at)?;
+			// last check: spending the credits can not be undone
+			let bonus_expires_at = if bonus {
+				ensure!(
+					T::Assets::reducible_balance(asset_id, &Self::treasury_account_id(), true) >=
+						volume_in_unit,
+					<Error<T>>::TreasuryNotEnoughBalance
+				);
+				Some(Self::spend_bonus_credits(&sender, asset_id, volume_in_unit, current_ts)?)
+			} else {
+				None
+			};
+
+			// the order count makes the id unique, eg: for identical legs of a batch
+			let order_id = T::Hashing::hash_of(&(
+				&sender,
+				pair_id,
+				&trade_type,
+				volume_in_unit,
+				expired_at,
+				current_ts,
+				new_cnt,
+			));
+
+			// create orders
+			let order = Order::<T> {
+				id: order_id,
+				user_id: sender.clone(),
+				pair_id,
+				trade_type,
+				asset_id,
+				volume_in_unit,
+				protocol_fee: if bonus { Zero::zero() } else { Self::stake_fee_of(volume_in_unit) },
+				expired_at,
+				created_at: current_ts,
+				liquidity_pool_id: suitable_lp_id.unwrap(), // unwrap is safe because of `ensure` check above
+				payout_rate,
+				open_price,
+				close_price: None,
+				status: OrderStatus::Created,
+				barrier_hit: false,
+				market: MarketMode::Pool,
+				matches: Vec::new(),
+				matched_volume: Zero::zero(),
+				pool_volume: volume_in_unit,
+				tournament_id: None,
+				bonus_expires_at,
+			};
+
+			// ---- Save to db ------
+			// Performs this operation first because as it may fail
+			// <UserOrders<T>>::try_mutate(&sender, |vec| {
+			// 	vec.push(order_id);
+			// 	Ok(())
+			// }).map_err(|_| <Error<T>>::CannotSaveUserOrders)?;
+			<UserOrders<T>>::append(sender.clone(), order_id);
+			<Orders<T>>::insert(order_id, order.clone());
+			<OrderCount<T>>::put(new_cnt);
+			if !bonus {
+				Self::record_order_opened(&sender, volume_in_unit);
+			}
+			if is_path_dependent {
+				<BarrierOrders<T>>::append(order_id);
+			}
+			if let Some(side) = order.trade_type.side() {
+				T::BoLiquidity::add_liability(
+					&order.liquidity_pool_id,
+					&pair.oracle_symbol,
+					side,
+					Self::payout_of(volume_in_unit, payout_rate),
+				);
+			}
+
+			// Deposit balance user, or the treasury for a bonus order
+			let funder = if bonus { Self::treasury_account_id() } else { sender.clone() };
+			T::Assets::transfer(asset_id, &funder, &order.liquidity_pool_id, volume_in_unit, true)?;
+			let protocol_fee = order.protocol_fee;
+			Self::collect_protocol_fee(asset_id, &sender, protocol_fee, current_ts)?;
+			if Self::referral_program().source == RebateSource::ProtocolFee {
+				Self::accrue_referral_rebates(&order, protocol_fee);
+			}
+
+			// Update LP balance
+			T::BoLiquidity::update_lp_balance(order.liquidity_pool_id);
+
+			log::info!("Order created: {:?}.", order_id);
+			Self::deposit_event(Event::OrderCreated(sender, order_id, volume_in_unit, protocol_fee));
+
+			Ok((order_id, pools_visited))
+		}
+
+		/// Update the account, pair and leaderboard stats with a settled order
+		fn record_stats(order: &Order<T>, status: &OrderStatus, amount_payout: BalanceOf<T>) {
+			let volume = order.volume_in_unit;
+			<AccountStats<T>>::mutate(&order.user_id, order.asset_id, |stats| {
+				stats.record(status, volume, amount_payout)
+			});
+			<PairStats<T>>::mutate(order.pair_id, |stats| {
+				stats.record(status, volume, amount_payout)
+			});
+
+			let period = Self::leaderboard_period_of(T::TimeProvider::now().as_secs());
+			let board = (order.asset_id, period);
+			let stats = <PeriodStats<T>>::mutate(&board, &order.user_id, |stats| {
+				stats.record(status, volume, amount_payout);
+				stats.clone()
+			});
+			<Leaderboards<T>>::mutate(&board, |ranking| {
+				ranking.retain(|(account_id, _)| *account_id != order.user_id);
+				let rank = ranking
+					.iter()
+					.position(|(_, other)| stats.ranks_above(other))
+					.unwrap_or(ranking.len());
+				if rank < T::LeaderboardSize::get() as usize {
+					ranking.insert(rank, (order.user_id.clone(), stats));
+					ranking.truncate(T::LeaderboardSize::get() as usize);
+				}
+			});
+		}
+
+		/// Leaderboard period of `unix_ts`
+		pub fn leaderboard_period_of(unix_ts: u64) -> u64 {
+			unix_ts / T::LeaderboardPeriod::get().max(1)
+		}
+
+		/// Leaderboard of an asset in a period, the current period if None
+		/// Used by the runtime api
+		pub fn leaderboard(
+			asset_id: AssetIdOf<T>,
+			period: Option<u64>,
+		) -> Vec<(T::AccountId, TraderStats<BalanceOf<T>>)> {
+			let period = period
+				.unwrap_or_else(|| Self::leaderboard_period_of(T::TimeProvider::now().as_secs()));
+			Self::leaderboards((asset_id, period))
+		}
+
+		/// Settle a virtual order against the tournament balance of its owner
+		/// - Refunded orders and winning orders credit their payout to the virtual balance
+		fn settle_tournament_order(
+			order: &Order<T>,
+			close_price: SymbolPrice,
+			refund: bool,
+		) -> Result<(OrderStatus, BalanceOf<T>), DispatchError> {
+			let tournament_id = order.tournament_id.ok_or(<Error<T>>::TournamentNotExist)?;
+			let (status, payout) = if refund {
+				(OrderStatus::Refunded, order.volume_in_unit)
+			} else if order.is_win(close_price) {
+				(OrderStatus::Win, Self::payout_of(order.volume_in_unit, order.payout_rate))
+			} else {
+				(OrderStatus::Lose, Zero::zero())
+			};
+
+			<TournamentBalances<T>>::mutate(tournament_id, &order.user_id, |balance| {
+				if let Some(balance) = balance {
+					*balance = balance.saturating_add(payout);
+				}
+			});
+			<Tournaments<T>>::mutate(tournament_id, |tournament| {
+				if let Some(tournament) = tournament {
+					tournament.open_orders = tournament.open_orders.saturating_sub(1);
+				}
+			});
+
+			Ok((status, payout))
+		}
+
+		/// Spend bonus credits of an account, the soonest expiring first
+		/// Returns the expiry of the soonest expiring credits spent
+		fn spend_bonus_credits(
+			account_id: &T::AccountId,
+			asset_id: AssetIdOf<T>,
+			amount: BalanceOf<T>,
+			current_ts: u64,
+		) -> Result<u64, DispatchError> {
+			<BonusCredits<T>>::try_mutate(
+				account_id,
+				asset_id,
+				|grants| -> Result<u64, DispatchError> {
+					grants.retain(|grant| grant.expires_at > current_ts);
+					let available =
+						grants.iter().fold(Zero::zero(), |total: BalanceOf<T>, grant| {
+							total.saturating_add(grant.amount)
+						});
+					ensure!(available >= amount, <Error<T>>::NotEnoughBonusCredit);
+
+					let expires_at =
+						grants.first().map(|grant| grant.expires_at).unwrap_or_default();
+					let mut left = amount;
+					for grant in grants.iter_mut() {
+						let spent = left.min(grant.amount);
+						grant.amount = grant.amount.saturating_sub(spent);
+						left = left.saturating_sub(spent);
+						if left.is_zero() {
+							break
+						}
+					}
+					grants.retain(|grant| !grant.amount.is_zero());
+
+					Ok(expires_at)
+				},
+			)
+		}
+
+		/// Give back the bonus credits of a refunded bonus order, if not expired yet
+		fn restore_bonus_credits(order: &Order<T>) {
+			let expires_at = match order.bonus_expires_at {
+				Some(expires_at) if expires_at > T::TimeProvider::now().as_secs() => expires_at,
+				_ => return,
+			};
+			<BonusCredits<T>>::mutate(&order.user_id, order.asset_id, |grants| {
+				let index = grants
+					.iter()
+					.position(|grant| grant.expires_at > expires_at)
+					.unwrap_or(grants.len());
+				grants.insert(index, CreditGrant { amount: order.volume_in_unit, expires_at });
+			});
+		}
+
+		/// Weight of visiting `pools` liquidity pools to pick the pool of an order
+		pub fn pools_visited_weight(pools: u32) -> Weight {
+			T::WeightInfo::place_order(pools, 0).saturating_sub(T::WeightInfo::place_order(0, 0))
+		}
+
+		/// Weight of an order visiting `pools` pools and copied for `followers` followers,
+		/// the copies visiting `copy_pools` pools in total
+		pub fn place_order_weight(pools: u32, followers: u32, copy_pools: u32) -> Weight {
+			T::WeightInfo::place_order(pools, 0)
+				.saturating_add(T::WeightInfo::copy_order(followers))
+				.saturating_add(Self::pools_visited_weight(copy_pools))
+		}
+
+		/// Copy an order of a leader for each of its followers
+		/// Returns the number of followers and the number of pools visited by the copies
+		/// - A follower is skipped if its stake is zero or over its remaining budget,
+		/// 	or if its order can not be placed, eg: risk limits, balance
+		pub(crate) fn copy_order(
+			leader: &T::AccountId,
+			leader_order_id: T::Hash,
+			pair_id: PairId,
+			trade_type: TradeType,
+			leader_stake: BalanceOf<T>,
+			expired_at: u64,
+		) -> (u32, u32) {
+			let followers = Self::followers(leader);
+			let mut pools_visited = 0u32;
+			for follower in followers.iter() {
+				let mut subscription = match Self::copy_subscriptions(follower) {
+					Some(subscription) => subscription,
+					None => continue,
+				};
+				let stake = subscription.stake.stake_of(leader_stake);
+				if stake.is_zero() || stake > subscription.budget {
+					continue
+				}
+
+				// A failing copy must not leave a partial state behind
+				let res = with_transaction(|| {
+					match Self::do_place_order(
+						follower.clone(),
+						pair_id,
+						trade_type.clone(),
+						stake,
+						expired_at,
+						false,
+					) {
+						Ok(placed) => TransactionOutcome::Commit(Ok(placed)),
+						Err(e) => TransactionOutcome::Rollback(Err(e)),
+					}
+				});
+				let order_id = match res {
+					Ok((order_id, pools)) => {
+						pools_visited = pools_visited.saturating_add(pools);
+						order_id
+					},
+					Err(e) => {
+						// the pools visited before the failure are not known
+						pools_visited = pools_visited.saturating_add(T::MaxPools::get());
+						log::info!(
+							"Order {:?} not copied for {:?}: {:?}",
+							leader_order_id,
+							follower,
+							e
+						);
+						continue
+					},
+				};
+
+				subscription.budget = subscription.budget.saturating_sub(stake);
+				<CopiedOrders<T>>::insert(order_id, (leader.clone(), subscription.leader_fee_bps));
+				<CopySubscriptions<T>>::insert(follower, subscription);
+
+				Self::deposit_event(Event::OrderCopied {
+					leader: leader.clone(),
+					follower: follower.clone(),
+					leader_order_id,
+					order_id,
+				});
+			}
+
+			(followers.len() as u32, pools_visited)
+		}
+
+		/// Pay the leader its fee on the profit of a settled copied order
+		fn pay_leader_fee(order: &Order<T>, status: &OrderStatus, amount_payout: BalanceOf<T>) {
+			let (leader, fee_bps) = match <CopiedOrders<T>>::take(&order.id) {
+				Some(copied) => copied,
+				None => return,
+			};
+			if *status != OrderStatus::Win {
+				return
+			}
+
+			let profit = amount_payout.saturating_sub(order.volume_in_unit);
+			let fee = Permill::from_parts(fee_bps.saturating_mul(100)) * profit;
+			if fee.is_zero() {
+				return
+			}
+			if T::Assets::transfer(order.asset_id, &order.user_id, &leader, fee, true).is_ok() {
+				let follower = order.user_id.clone();
+				Self::deposit_event(Event::LeaderFeePaid(leader, follower, order.id, fee));
+			}
+		}
+
+		/// The first `timeframe` boundary an order placed at `current_ts` can expire at
+		pub fn next_expiry(current_ts: u64, timeframe: u64) -> u64 {
+			let min_lead = T::OracleUpdateInterval::get().saturating_mul(T::MinLeadIntervals::get());
+			let earliest_expiry = current_ts.saturating_add(min_lead);
+			if timeframe == 0 {
+				return earliest_expiry
+			}
+			let boundary = earliest_expiry / timeframe * timeframe;
+			if boundary < earliest_expiry {
+				boundary.saturating_add(timeframe)
+			} else {
+				boundary
+			}
+		}
+
+		/// Return the escrow of a conditional order to its owner and forget it
+		/// The caller removes it from `PendingConditionalOrders`
+		fn refund_conditional_order(conditional: &ConditionalOrder<T>) -> DispatchResult {
+			T::Assets::transfer(
+				conditional.asset_id,
+				&Self::account_id(),
+				&conditional.user_id,
+				conditional.escrowed,
+				false,
+			)?;
+			<ConditionalOrders<T>>::remove(&conditional.id);
+
+			Ok(())
+		}
+
+		/// Open the order of a conditional order whose trigger price was crossed
+		/// - The escrow goes back to the owner, then the order is placed as by `place_order`
+		/// - If the order can not be opened (eg: pair paused, risk limits),
+		/// 	the owner keeps the refund
+		/// - Returns the number of pools visited
+		fn trigger_conditional_order(conditional: &ConditionalOrder<T>, price: SymbolPrice) -> u32 {
+			if let Err(e) = Self::refund_conditional_order(conditional) {
+				log::error!("Conditional order {:?} refund failed: {:?}", conditional.id, e);
+				// kept pending, triggered again by the next price
+				<PendingConditionalOrders<T>>::append(conditional.id);
+				return 0
 			}
 
-			/*
-			let sender = ensure_signed(origin)?;
-			log::info!("close_order: order_id, close_price: {:?}, {:?}", order_id, close_price);
-			Self::deposit_event(Event::OrderClosed {
-				account_id: sender,
-				order_id,
-				close_price,
-				status,
-				amount_payout: volumn_payout,
+			let current_ts = T::TimeProvider::now().as_secs();
+			let res = with_transaction(|| {
+				match Self::do_place_order(
+					conditional.user_id.clone(),
+					conditional.pair_id,
+					conditional.trade_type.clone(),
+					conditional.volume_in_unit,
+					Self::next_expiry(current_ts, conditional.timeframe),
+					false,
+				) {
+					Ok(placed) => TransactionOutcome::Commit(Ok(placed)),
+					Err(e) => TransactionOutcome::Rollback(Err(e)),
+				}
 			});
-			*/
+			match res {
+				Ok((order_id, pools_visited)) => {
+					Self::deposit_event(Event::ConditionalOrderTriggered {
+						conditional_order_id: conditional.id,
+						order_id,
+						price,
+					});
+					pools_visited
+				},
+				Err(e) => {
+					Self::deposit_event(Event::ConditionalOrderFailed(conditional.id, e));
+					// the pools visited before the failure are not known
+					T::MaxPools::get()
+				},
+			}
+		}
+
+		/// Settle an expired order at `close_price`
+		/// - Determine this is win or loose, pay out and release the pool liability
+		/// - Returns the order and the protocol fee taken from the pool profit
+		/// - Nothing is written if any transfer fails
+		fn do_close_order(
+			order_id: T::Hash,
+			close_price: SymbolPrice,
+		) -> Result<(Order<T>, BalanceOf<T>), DispatchError> {
+			with_transaction(|| match Self::settle_order(order_id, close_price) {
+				Ok(settled) => TransactionOutcome::Commit(Ok(settled)),
+				Err(e) => TransactionOutcome::Rollback(Err(e)),
+			})
+		}
+
+		/// Body of `do_close_order`, to be run in a storage transaction
+		fn settle_order(
+			order_id: T::Hash,
+			close_price: SymbolPrice,
+		) -> Result<(Order<T>, BalanceOf<T>), DispatchError> {
+			// Get Order
+			let order = Orders::<T>::get(&order_id).ok_or(Error::<T>::OrderNotExist)?;
+			ensure!(order.status == OrderStatus::Created, <Error<T>>::OrderNotOpen);
+			let refund = Self::pause_policy_of(order.pair_id) == Some(PausePolicy::Refund);
+
+			// The pool won the stake of a losing order: the protocol takes its cut of the profit
+			let mut protocol_fee: BalanceOf<T> = Zero::zero();
+			if !refund && !order.pool_volume.is_zero() && !order.is_win(close_price) {
+				protocol_fee = Self::profit_fee_of(order.pool_volume);
+				Self::collect_protocol_fee(
+					order.asset_id,
+					&order.liquidity_pool_id,
+					protocol_fee,
+					T::TimeProvider::now().as_secs(),
+				)?;
+				T::BoLiquidity::update_lp_balance(order.liquidity_pool_id.clone());
+			}
+
+			// Check result and payout
+			let (status, volumn_payout) = match order.market {
+				_ if order.tournament_id.is_some() =>
+					Self::settle_tournament_order(&order, close_price, refund)?,
+				_ if refund => Self::refund_order(&order)?,
+				MarketMode::Pool => Self::settle_pool_order(&order, close_price)?,
+				MarketMode::PeerToPeer => Self::settle_p2p_order(&order, close_price)?,
+			};
+
+			// Update info Orders
+			Orders::<T>::try_mutate_exists(&order_id, |order| -> DispatchResult {
+				let mut order = order.as_mut().ok_or(Error::<T>::OrderNotExist)?;
+				order.status = status.clone();
+				order.close_price = Some(close_price);
+				Ok(())
+			})?;
+
+			Self::release_liability(&order);
+			// virtual and bonus orders do not count for the real trading limits and stats
+			if order.tournament_id.is_none() && !order.is_bonus() {
+				Self::record_order_closed(&order, &status, volumn_payout);
+				Self::pay_leader_fee(&order, &status, volumn_payout);
+				Self::record_stats(&order, &status, volumn_payout);
+			}
+
+			// virtual and bonus orders do not pay rebates either
+			if order.tournament_id.is_none() && !order.is_bonus() {
+				let base = match Self::referral_program().source {
+					// the rebates on the stake fee accrued when the order was placed
+					RebateSource::ProtocolFee => protocol_fee,
+					RebateSource::PoolEdge if !refund && !order.is_win(close_price) =>
+						order.pool_volume,
+					RebateSource::PoolEdge => Zero::zero(),
+				};
+				Self::accrue_referral_rebates(&order, base);
+			}
 
 			log::info!("close_order: order_id, close_price: {:?}, {:?}", order_id, close_price);
 			Self::deposit_event(Event::OrderClosed {
-				account_id: order.user_id,
+				account_id: order.user_id.clone(),
 				order_id,
 				close_price,
 				status,
 				amount_payout: volumn_payout,
+				protocol_fee,
 			});
 
-			<NextUnsignedAt<T>>::put(block_number + T::UnsignedInterval::get());
+			Ok((order, protocol_fee))
+		}
 
-			// Update info Orders
-			// Orders::<T>::try_mutate_exists(&order_id, |order| -> DispatchResult {
-			// 	let mut order = order.as_mut().ok_or(Error::<T>::OrderNotExist)?;
-			// 	Ok(())
-			// })?;
-			// Orders::<T>::try_mutate_exists(&order_id, |_| -> DispatchResult {
-			// 	let _order = order;
-			// 	Ok(())
-			// })?;
+		/// Share of the protocol fees of a settled order paid to its keeper
+		pub fn keeper_reward_of(protocol_fee: BalanceOf<T>) -> BalanceOf<T> {
+			Permill::from_parts(T::KeeperRewardBps::get().saturating_mul(100)) * protocol_fee
+		}
 
-			// TODO: Remove order in Orders -> push to OrderCompleted
+		/// Credit the referrers of the order owner with their tier share of `base`
+		/// - Rebates on the pool edge are moved from the pool to the treasury first,
+		/// 	nothing accrues if the pool can not pay
+		fn accrue_referral_rebates(order: &Order<T>, base: BalanceOf<T>) {
+			if base.is_zero() {
+				return
+			}
+
+			let program = Self::referral_program();
+			let mut rebates = Vec::new();
+			let mut referee = order.user_id.clone();
+			for tier in program.tiers.iter() {
+				let referrer = match Self::referrers(&referee) {
+					Some(referrer) => referrer,
+					None => break,
+				};
+				rebates.push((referrer.clone(), *tier * base));
+				referee = referrer;
+			}
+
+			let total = rebates.iter().fold(Zero::zero(), |total: BalanceOf<T>, (_, amount)| {
+				total.saturating_add(*amount)
+			});
+			if total.is_zero() {
+				return
+			}
+			if program.source == RebateSource::PoolEdge {
+				let paid = T::Assets::transfer(
+					order.asset_id,
+					&order.liquidity_pool_id,
+					&Self::treasury_account_id(),
+					total,
+					false,
+				);
+				if paid.is_err() {
+					log::warn!("Referral rebates of order {:?} not paid by the pool", order.id);
+					return
+				}
+				T::BoLiquidity::update_lp_balance(order.liquidity_pool_id.clone());
+			}
+
+			for (index, (referrer, amount)) in rebates.into_iter().enumerate() {
+				if amount.is_zero() {
+					continue
+				}
+				<ReferralRebates<T>>::mutate(&referrer, order.asset_id, |rebate| {
+					*rebate = rebate.saturating_add(amount)
+				});
+				Self::deposit_event(Event::ReferralRebateAccrued {
+					referrer,
+					account_id: order.user_id.clone(),
+					order_id: order.id,
+					tier: index as u32 + 1,
+					asset_id: order.asset_id,
+					amount,
+				});
+			}
+		}
+
+		/// The price an open order is settled at, if it is expired and the price is recorded
+		fn settlement_price_of(order_id: &T::Hash, current_ts: u64) -> Option<SymbolPrice> {
+			let order = Self::orders(order_id)?;
+			if order.status != OrderStatus::Created || current_ts < order.expired_at {
+				return None
+			}
+
+			Self::settlement_price(order.pair_id, order.expired_at)
+		}
+
+		/// Wait for the settlement price of the orders of a pair expiring at `expired_at`
+		/// - Recorded by `on_new_price` from the first price at or after the expiry
+		fn watch_expiry(pair_id: PairId, expired_at: u64) -> DispatchResult {
+			<PendingExpiries<T>>::try_mutate(|expiries| -> DispatchResult {
+				if expiries.contains(&(pair_id, expired_at)) {
+					return Ok(())
+				}
+				ensure!(
+					expiries.len() < T::MaxPendingExpiries::get() as usize,
+					<Error<T>>::TooManyPendingExpiries
+				);
+				expiries.push((pair_id, expired_at));
+				Ok(())
+			})
+		}
+
+		/// Oracle symbol of a pair, used to key the price and the pools liability
+		pub fn oracle_symbol_of(pair_id: PairId) -> Vec<u8> {
+			Self::pairs(pair_id).map(|pair| pair.oracle_symbol).unwrap_or_default()
+		}
+
+		/// A pair needs an oracle symbol, a non empty stake range and positive timeframes
+		fn ensure_valid_pair(pair: &TradingPair<T>) -> DispatchResult {
+			ensure!(!pair.oracle_symbol.is_empty(), <Error<T>>::InvalidPair);
+			ensure!(
+				!pair.min_stake.is_zero() && pair.min_stake <= pair.max_stake,
+				<Error<T>>::InvalidPair
+			);
+			ensure!(pair.timeframes.iter().all(|tf| *tf > 0), <Error<T>>::TimeframeNotAllowed);
 
 			Ok(())
 		}
-	}
 
-	// pub const RAW_AMOUNT_SCALE: f64 = 100 as f64;
+		/// Pick a liquidity pool funded with `asset_id` for `volume`
+		/// Also returns the number of pools visited
+		fn pool_for(asset_id: AssetIdOf<T>, volume: BalanceOf<T>) -> (Option<T::AccountId>, u32) {
+			match Self::balance_to_u64(volume) {
+				Some(volume) => T::BoLiquidity::get_suitable_lp(asset_id, volume),
+				None => (None, 0),
+			}
+		}
 
-	/// Internal helpers fn
-	impl<T: Config> Pallet<T> {
-		// pub fn raw_amount_2_amount(raw_amount: u32) -> f64 {
-		// 	(raw_amount / RAW_AMOUNT_SCALE) as f64
-		// }
-		//
-		// pub fn amount_2_raw_amount(amount: f64) -> u32 {
-		// 	amount * RAW_AMOUNT_SCALE
-		// }
+		/// Common checks of a new order, returns the current timestamp and the pair
+		/// - the pair must be registered, enabled and not paused
+		/// - trading volume must be in [min_stake, max_stake] of the pair
+		/// - expired_at must match an allowed timeframe of the pair
+		/// - the user must have enough free balance
+		pub fn ensure_order_params(
+			sender: &T::AccountId,
+			pair_id: PairId,
+			volume_in_unit: BalanceOf<T>,
+			expired_at: u64,
+		) -> Result<(u64, TradingPair<T>), DispatchError> {
+			let (current_ts, pair) =
+				Self::ensure_tradable(sender, pair_id, volume_in_unit, expired_at)?;
 
-		pub fn hash_str<S: Encode>(s: &S) -> T::Hash {
-			T::Hashing::hash_of(s)
+			// Check the buyer has enough free balance to place this order and pay the fee
+			ensure!(
+				T::Assets::reducible_balance(pair.asset_id, sender, true) >=
+					volume_in_unit.saturating_add(Self::stake_fee_of(volume_in_unit)),
+				<Error<T>>::NotEnoughBalance
+			);
+
+			Ok((current_ts, pair))
 		}
 
-		// How to convert u64 <=> Balance : https://stackoverflow.com/a/56081118/4984888
-		pub fn u64_to_balance(input: u64) -> Option<BalanceOf<T>> {
-			input.try_into().ok()
+		/// `ensure_order_params` without the balance check, eg: for a stake funded by bonus credits
+		pub fn ensure_tradable(
+			sender: &T::AccountId,
+			pair_id: PairId,
+			volume_in_unit: BalanceOf<T>,
+			expired_at: u64,
+		) -> Result<(u64, TradingPair<T>), DispatchError> {
+			let pair = Self::pairs(pair_id).ok_or(<Error<T>>::PairNotFound)?;
+			ensure!(pair.enabled, <Error<T>>::PairDisabled);
+			ensure!(Self::pause_policy_of(pair_id).is_none(), <Error<T>>::TradingPaused);
+
+			ensure!(pair.min_stake.le(&volume_in_unit), <Error<T>>::InvalidTradingVolume);
+			ensure!(pair.max_stake.ge(&volume_in_unit), <Error<T>>::InvalidTradingVolume);
+
+			let current_ts: u64 = T::TimeProvider::now().as_secs(); // TODO: Get current timestamp
+			log::info!("Order is creating at {:?} and expired at {:?}.", current_ts, expired_at);
+
+			Self::ensure_valid_expiry(&pair, current_ts, expired_at)?;
+			Self::ensure_risk_limits(sender, volume_in_unit, current_ts)?;
+
+			Ok((current_ts, pair))
 		}
 
-		pub fn balance_to_u64(input: BalanceOf<T>) -> Option<u64> {
-			TryInto::<u64>::try_into(input).ok()
+		/// Pause policy in effect for a pair, None if new orders are allowed
+		/// Refund wins if either the global pause or the pair pause refunds
+		pub fn pause_policy_of(pair_id: PairId) -> Option<PausePolicy> {
+			match (Self::global_pause(), Self::pair_pauses(pair_id)) {
+				(Some(PausePolicy::Refund), _) | (_, Some(PausePolicy::Refund)) =>
+					Some(PausePolicy::Refund),
+				(None, None) => None,
+				_ => Some(PausePolicy::Settle),
+			}
 		}
 
-		// How to convert BlockNumber <=> u64 :
-		pub fn blocknumber_to_u64(input: T::BlockNumber) -> Option<u64> {
-			TryInto::<u64>::try_into(input).ok()
+		/// (new orders paused, settlement paused) of a pair
+		/// Used by the runtime api
+		pub fn trading_status(pair_id: PairId) -> (bool, bool) {
+			(Self::pause_policy_of(pair_id).is_some(), Self::settlement_paused())
+		}
+
+		/// Return the whole stake of an order, the pair was paused with the Refund policy
+		/// - the pool part is returned by the pool, the rest by the P2P escrow
+		/// - the fills are refunded to both sides, unless the counterparty settled them already
+		fn refund_order(order: &Order<T>) -> Result<(OrderStatus, BalanceOf<T>), DispatchError> {
+			let from_pool = order.pool_volume;
+			let from_escrow = order.unmatched_volume();
+
+			if !from_escrow.is_zero() {
+				T::Assets::transfer(
+					order.asset_id,
+					&Self::p2p_account_id(),
+					&order.user_id,
+					from_escrow,
+					false,
+				)?;
+			}
+			let from_fills = Self::settle_p2p_fills(order, None)?;
+			if !from_pool.is_zero() {
+				// bonus order: the stake goes back to the treasury, the credits to the trader
+				let refund_to = if order.is_bonus() {
+					Self::restore_bonus_credits(order);
+					Self::treasury_account_id()
+				} else {
+					order.user_id.clone()
+				};
+				T::Assets::transfer(
+					order.asset_id,
+					&order.liquidity_pool_id,
+					&refund_to,
+					from_pool,
+					true,
+				)?;
+				T::BoLiquidity::update_lp_balance(order.liquidity_pool_id.clone());
+			}
+
+			if let (MarketMode::PeerToPeer, Some(side)) = (&order.market, order.trade_type.side()) {
+				<RestingOrders<T>>::mutate(
+					&(order.pair_id, order.expired_at),
+					&side,
+					|order_ids| order_ids.retain(|id| *id != order.id),
+				);
+			}
+
+			Ok((OrderStatus::Refunded, from_escrow + from_fills + from_pool))
+		}
+
+		/// Limits of an account: the tightest of its self limits and operator limits
+		pub fn risk_limits_of(account_id: &T::AccountId) -> RiskLimits<T> {
+			Self::self_limits(account_id).tightest(&Self::operator_limits(account_id))
+		}
+
+		/// Check a new order of `volume` against the limits of the account
+		pub fn ensure_risk_limits(
+			account_id: &T::AccountId,
+			volume: BalanceOf<T>,
+			current_ts: u64,
+		) -> DispatchResult {
+			let risk = Self::account_risk(account_id);
+			ensure!(risk.excluded_until <= current_ts, <Error<T>>::AccountExcluded);
+			ensure!(risk.cooldown_until <= current_ts, <Error<T>>::LossStreakCooldown);
+
+			let limits = Self::risk_limits_of(account_id);
+			if let Some(max_open_orders) = limits.max_open_orders {
+				ensure!(risk.open_orders < max_open_orders, <Error<T>>::TooManyOpenOrders);
+			}
+			if let Some(max_open_stake) = limits.max_open_stake {
+				ensure!(
+					risk.open_stake.saturating_add(volume) <= max_open_stake,
+					<Error<T>>::OpenStakeLimitExceeded
+				);
+			}
+			if let Some(daily_loss_cap) = limits.daily_loss_cap {
+				let potential_loss =
+					risk.loss_on(current_ts).saturating_add(risk.open_stake).saturating_add(volume);
+				ensure!(potential_loss <= daily_loss_cap, <Error<T>>::DailyLossCapReached);
+			}
+
+			Ok(())
+		}
+
+		fn record_order_opened(account_id: &T::AccountId, volume: BalanceOf<T>) {
+			<AccountRisks<T>>::mutate(account_id, |risk| {
+				risk.open_orders = risk.open_orders.saturating_add(1);
+				risk.open_stake = risk.open_stake.saturating_add(volume);
+			});
+		}
+
+		/// Update the counters of the order owner once an order is no longer open
+		/// - the loss of the order (stake - amount returned) counts in the daily loss
+		/// - a Lose extends the loss streak, a Win resets it
+		fn record_order_closed(order: &Order<T>, status: &OrderStatus, amount_returned: BalanceOf<T>) {
+			let current_ts = T::TimeProvider::now().as_secs();
+			let today = current_ts / SECS_PER_DAY;
+			let max_loss_streak = Self::risk_limits_of(&order.user_id).max_loss_streak;
+
+			let cooldown_until = <AccountRisks<T>>::mutate(&order.user_id, |risk| {
+				risk.open_orders = risk.open_orders.saturating_sub(1);
+				risk.open_stake = risk.open_stake.saturating_sub(order.volume_in_unit);
+
+				if risk.day != today {
+					risk.day = today;
+					risk.day_loss = Zero::zero();
+				}
+				risk.day_loss =
+					risk.day_loss.saturating_add(order.volume_in_unit.saturating_sub(amount_returned));
+
+				match status {
+					OrderStatus::Lose => risk.loss_streak = risk.loss_streak.saturating_add(1),
+					OrderStatus::Win => risk.loss_streak = 0,
+					_ => {},
+				}
+				match max_loss_streak {
+					Some(max_loss_streak) if risk.loss_streak >= max_loss_streak => {
+						risk.loss_streak = 0;
+						risk.cooldown_until = current_ts.saturating_add(T::LossStreakCooldown::get());
+						Some(risk.cooldown_until)
+					},
+					_ => None,
+				}
+			});
+
+			if let Some(cooldown_until) = cooldown_until {
+				Self::deposit_event(Event::LossStreakCooldownStarted(
+					order.user_id.clone(),
+					cooldown_until,
+				));
+			}
+		}
+
+		/// Result and payout of an order traded against a pool
+		fn settle_pool_order(
+			order: &Order<T>,
+			close_price: SymbolPrice,
+		) -> Result<(OrderStatus, BalanceOf<T>), DispatchError> {
+			if !order.is_win(close_price) {
+				log::info!("Lose: order_id, close_price: {:?}, {:?}", order.id, close_price);
+				return Ok((OrderStatus::Lose, Zero::zero()))
+			}
+
+			let volumn_payout = Self::payout_of(order.volume_in_unit, order.payout_rate);
+			log::info!("volumn_payout: {:?}", volumn_payout);
+
+			// Bonus order: the trader gets the profit, the treasury gets its stake back
+			if order.is_bonus() {
+				let profit = volumn_payout.saturating_sub(order.volume_in_unit);
+				T::Assets::transfer(
+					order.asset_id,
+					&order.liquidity_pool_id,
+					&order.user_id,
+					profit,
+					true,
+				)?;
+				T::Assets::transfer(
+					order.asset_id,
+					&order.liquidity_pool_id,
+					&Self::treasury_account_id(),
+					order.volume_in_unit,
+					true,
+				)?;
+				T::BoLiquidity::update_lp_balance(order.liquidity_pool_id.clone());
+
+				return Ok((OrderStatus::Win, profit))
+			}
+
+			// Payout
+			T::Assets::transfer(
+				order.asset_id,
+				&order.liquidity_pool_id,
+				&order.user_id,
+				volumn_payout,
+				true,
+			)?;
+
+			// Update LP balance
+			T::BoLiquidity::update_lp_balance(order.liquidity_pool_id.clone());
+
+			Ok((OrderStatus::Win, volumn_payout))
+		}
+
+		/// Result and payout of a peer-to-peer order
+		/// - each fill is won by the side in the money at the fill price, a tie refund both sides
+		/// - the pool residual is paid by the pool like a pool order
+		/// - the volume still unmatched is refunded
+		fn settle_p2p_order(
+			order: &Order<T>,
+			close_price: SymbolPrice,
+		) -> Result<(OrderStatus, BalanceOf<T>), DispatchError> {
+			let from_escrow = order.unmatched_volume();
+			let from_fills = Self::settle_p2p_fills(order, Some(close_price))?;
+
+			let mut from_pool: BalanceOf<T> = Zero::zero();
+			if !order.pool_volume.is_zero() && order.is_win(close_price) {
+				from_pool = Self::payout_of(order.pool_volume, order.payout_rate);
+			}
+
+			if !from_escrow.is_zero() {
+				T::Assets::transfer(
+					order.asset_id,
+					&Self::p2p_account_id(),
+					&order.user_id,
+					from_escrow,
+					false,
+				)?;
+			}
+			if !from_pool.is_zero() {
+				T::Assets::transfer(
+					order.asset_id,
+					&order.liquidity_pool_id,
+					&order.user_id,
+					from_pool,
+					true,
+				)?;
+				T::BoLiquidity::update_lp_balance(order.liquidity_pool_id.clone());
+			}
+
+			if let Some(side) = order.trade_type.side() {
+				<RestingOrders<T>>::mutate(
+					&(order.pair_id, order.expired_at),
+					&side,
+					|order_ids| order_ids.retain(|id| *id != order.id),
+				);
+			}
+
+			let volumn_payout = from_escrow + from_fills + from_pool;
+			let status = if order.matched_volume.is_zero() && order.pool_volume.is_zero() {
+				OrderStatus::Refunded
+			} else if volumn_payout > order.volume_in_unit {
+				OrderStatus::Win
+			} else {
+				OrderStatus::Lose
+			};
+
+			Ok((status, volumn_payout))
+		}
+
+		/// Pay both legs of the fills of a peer-to-peer order from the P2P escrow
+		/// - At `close_price` the stake of the losing side goes to the winning side, a tie or None
+		/// 	refunds both sides
+		/// - A fill is settled once: the counterparty is paid now and records what it got
+		/// - Returns what the fills pay to the order, the ones settled by the counterparty included
+		fn settle_p2p_fills(
+			order: &Order<T>,
+			close_price: Option<SymbolPrice>,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			let mut payout: BalanceOf<T> = Zero::zero();
+			for fill in order.matches.iter() {
+				if let Some(paid) = fill.settled {
+					payout += paid;
+					continue
+				}
+
+				let both = fill.amount + fill.amount;
+				let (own_leg, counter_leg) = match close_price {
+					Some(price) if price == fill.price => (fill.amount, fill.amount),
+					Some(price) if order.trade_type.is_in_the_money(fill.price, price) =>
+						(both, Zero::zero()),
+					Some(_) => (Zero::zero(), both),
+					None => (fill.amount, fill.amount),
+				};
+
+				let counterparty = Orders::<T>::try_mutate(
+					&fill.counterparty,
+					|counter| -> Result<T::AccountId, DispatchError> {
+						let counter = counter.as_mut().ok_or(<Error<T>>::OrderNotExist)?;
+						for counter_fill in counter.matches.iter_mut() {
+							if counter_fill.counterparty == order.id {
+								counter_fill.settled = Some(counter_leg);
+							}
+						}
+						Ok(counter.user_id.clone())
+					},
+				)?;
+				for (who, amount) in [(&order.user_id, own_leg), (&counterparty, counter_leg)] {
+					if !amount.is_zero() {
+						T::Assets::transfer(
+							order.asset_id,
+							&Self::p2p_account_id(),
+							who,
+							amount,
+							false,
+						)?;
+					}
+				}
+				payout += own_leg;
+			}
+
+			Ok(payout)
+		}
+
+		/// Allowed order durations (in seconds) of a pair
+		pub fn timeframes_of(pair: &TradingPair<T>) -> Vec<u64> {
+			if pair.timeframes.is_empty() {
+				T::DefaultTimeframes::get().into_inner()
+			} else {
+				pair.timeframes.to_vec()
+			}
+		}
+
+		/// Check the order expiry against the pair's timeframes
+		/// - expired_at must be at least `OracleUpdateInterval * MinLeadIntervals` seconds away
+		/// - expired_at must be the first boundary of one allowed timeframe after that lead time
+		pub fn ensure_valid_expiry(
+			pair: &TradingPair<T>,
+			current_ts: u64,
+			expired_at: u64,
+		) -> DispatchResult {
+			let min_lead = T::OracleUpdateInterval::get().saturating_mul(T::MinLeadIntervals::get());
+			let earliest_expiry = current_ts.saturating_add(min_lead);
+			ensure!(earliest_expiry <= expired_at, <Error<T>>::InvalidExpiredAt);
+
+			let is_allowed = Self::timeframes_of(pair).iter().any(|timeframe| {
+				*timeframe > 0 &&
+					expired_at % timeframe == 0 &&
+					expired_at - timeframe < earliest_expiry
+			});
+			ensure!(is_allowed, <Error<T>>::TimeframeNotAllowed);
+
+			Ok(())
+		}
+
+		/// Amount paid to the user if an order of `volume` win at `payout_rate`: stake + profit
+		pub fn payout_of(volume: BalanceOf<T>, payout_rate: u32) -> BalanceOf<T> {
+			(volume * Self::u64_to_balance(u64::from(100 + payout_rate)).unwrap()) /
+				Self::u64_to_balance(100).unwrap()
+		}
+
+		/// Adjust the payout rate by the Call/Put imbalance of the pool on this pair
+		///
+		/// 	imbalance = (same side - other side) / (same side + other side), in [-1, 1]
+		/// 	rate = base rate - ImbalanceSensitivity * imbalance
+		/// The liability of the new order is counted in the same side, so a single big order can
+		/// not get the thin side rate. Non directional orders keep the base rate.
+		pub fn imbalance_payout_rate(
+			base_payout_rate: u32,
+			lp_id: &T::AccountId,
+			oracle_symbol: &Vec<u8>,
+			trade_type: &TradeType,
+			volume: BalanceOf<T>,
+		) -> u32 {
+			let side = match trade_type.side() {
+				Some(side) => side,
+				None => return base_payout_rate,
+			};
+
+			let (call, put) = T::BoLiquidity::get_open_liability(lp_id, oracle_symbol);
+			let (same, other) = match side {
+				PositionSide::Call => (call, put),
+				PositionSide::Put => (put, call),
+			};
+			let same: i128 = same
+				.saturating_add(Self::payout_of(volume, base_payout_rate))
+				.saturated_into::<u128>() as i128;
+			let other: i128 = other.saturated_into::<u128>() as i128;
+
+			let total = same + other;
+			if total == 0 {
+				return base_payout_rate
+			}
+			let adjustment = T::ImbalanceSensitivity::get() as i128 * (same - other) / total;
+			let rate = base_payout_rate as i128 - adjustment;
+
+			rate.clamp(T::MinPayoutRate::get() as i128, T::MaxPayoutRate::get() as i128) as u32
+		}
+
+		/// Release the pool liability of an order which is no longer open
+		fn release_liability(order: &Order<T>) {
+			if order.pool_volume.is_zero() {
+				return
+			}
+			if let Some(side) = order.trade_type.side() {
+				T::BoLiquidity::release_liability(
+					&order.liquidity_pool_id,
+					&Self::oracle_symbol_of(order.pair_id),
+					side,
+					Self::payout_of(order.pool_volume, order.payout_rate),
+				);
+			}
+		}
+
+		/// Latest price of a currency pair
+		pub fn current_price(pair_id: PairId) -> Option<SymbolPrice> {
+			T::SymbolPriceModule::get_price(Self::oracle_symbol_of(pair_id))
+		}
+
+		/// Quote the value of an open order at `current_price`
+		///
+		/// value = full payout * win probability * (1 - spread)
+		/// The win probability starts at 50% and moves toward 0% / 100% the further the price is
+		/// from open_price (or the strike), relative to the expected move in the remaining time:
+		/// 	expected_move = PriceVolatility * sqrt(remaining minutes)
+		pub fn quote_sell_back(
+			order: &Order<T>,
+			current_price: SymbolPrice,
+			current_ts: u64,
+		) -> BalanceOf<T> {
+			const HALF: u128 = 500_000;
+
+			let reference_price = order.trade_type.reference_price(order.open_price);
+			let distance = if current_price > reference_price {
+				current_price - reference_price
+			} else {
+				reference_price - current_price
+			};
+			let moneyness: u128 = if reference_price > 0 {
+				distance.saturating_mul(1_000_000) / reference_price
+			} else {
+				0
+			};
+
+			let remaining_minutes = order.expired_at.saturating_sub(current_ts) / 60;
+			let expected_move: u128 = (T::PriceVolatility::get().deconstruct() as u128)
+				.saturating_mul((remaining_minutes + 1).integer_sqrt() as u128)
+				.max(1);
+			let shift = (moneyness.saturating_mul(HALF) / expected_move).min(HALF);
+
+			let in_the_money = order.trade_type.is_in_the_money(order.open_price, current_price);
+			let win_probability =
+				if in_the_money { HALF + shift } else { HALF.saturating_sub(shift) };
+
+			let full_payout: u128 = order
+				.volume_in_unit
+				.saturated_into::<u128>()
+				.saturating_mul(100 + order.payout_rate as u128) /
+				100;
+			let fair_value = full_payout.saturating_mul(win_probability) / 1_000_000;
+			let quote = fair_value.saturating_sub(T::SellBackSpread::get() * fair_value);
+
+			quote.saturated_into::<BalanceOf<T>>()
 		}
 
 		/// This is crond entry point:
@@ -579,6 +4345,11 @@ pub mod pallet {
 		pub fn scan_and_validate_expired_order_raw_unsigned(
 			block_number: T::BlockNumber,
 		) -> Result<(), &'static str> {
+			if Self::settlement_paused() {
+				log::info!("Settlement is paused, skip scanning at block_number: {:?}", block_number);
+				return Ok(())
+			}
+
 			// Get current timestamp
 			let current_ts = T::TimeProvider::now().as_secs();
 			log::info!("scan and validate order: block_number: {:?}", block_number);
@@ -586,14 +4357,11 @@ pub mod pallet {
 			// Scan all Orders
 			for order in Orders::<T>::iter_values().filter(|s| s.status == OrderStatus::Created) {
 				// Check order expired and call close_order
-				if current_ts >= order.expired_at {
-					let close_price =
-						T::SymbolPriceModule::get_price(String::from("BTC_USDT").into_bytes())
-							.unwrap();
-
+				// the settlement price is read again on-chain when the call is executed
+				if Self::settlement_price_of(&order.id, current_ts).is_some() {
 					log::info!("Call close_order in block_number: {:?}", block_number);
 					// Create a call close_order
-					let call = Call::close_order { block_number, order_id: order.id, close_price };
+					let call = Call::close_order { block_number, order_id: order.id };
 
 					// submit the call to on-chain
 					SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
@@ -604,4 +4372,126 @@ pub mod pallet {
 			Ok(())
 		}
 	}
+
+	/// Record the settlement prices of the expired orders and mark the barrier hits of open
+	/// Touch / No-Touch orders on every new price
+	impl<T: Config> OnNewPrice for Pallet<T> {
+		fn on_new_price(symbol: Vec<u8>, price: SymbolPrice) -> Weight {
+			let current_ts = T::TimeProvider::now().as_secs();
+
+			// The first price at or after an expiry settles the orders expiring then
+			let pending_expiries = Self::pending_expiries().len() as u32;
+			<PendingExpiries<T>>::mutate(|expiries| {
+				expiries.retain(|(pair_id, expired_at)| {
+					if current_ts < *expired_at || Self::oracle_symbol_of(*pair_id) != symbol {
+						return true
+					}
+					<SettlementPrices<T>>::insert(pair_id, expired_at, price);
+					Self::deposit_event(Event::SettlementPriceRecorded(
+						*pair_id,
+						*expired_at,
+						price,
+					));
+					false
+				})
+			});
+
+			let barrier_orders = Self::barrier_orders().len() as u32;
+			<BarrierOrders<T>>::mutate(|order_ids| {
+				order_ids.retain(|order_id| {
+					let order = match Orders::<T>::get(order_id) {
+						Some(order) => order,
+						None => return false,
+					};
+					// stop watching closed orders, and orders past their expiry
+					if order.status != OrderStatus::Created || current_ts > order.expired_at {
+						return false
+					}
+					if Self::oracle_symbol_of(order.pair_id) != symbol {
+						return true
+					}
+
+					let barrier_hit = match order.trade_type {
+						TradeType::Touch { barrier } | TradeType::NoTouch { barrier } =>
+							TradeType::touches(barrier, order.open_price, price),
+						_ => false,
+					};
+					if barrier_hit {
+						Orders::<T>::mutate(order_id, |order| {
+							if let Some(order) = order {
+								order.barrier_hit = true;
+							}
+						});
+						log::info!("Barrier hit: order_id, price: {:?}, {:?}", order_id, price);
+						Self::deposit_event(Event::BarrierHit(*order_id, price));
+					}
+
+					!barrier_hit
+				})
+			});
+
+			// Conditional orders: refund the expired ones, trigger the crossed ones
+			let pending = Self::pending_conditional_orders().len() as u32;
+			let max_triggers = T::MaxTriggersPerPrice::get() as usize;
+			let mut triggered = Vec::new();
+			<PendingConditionalOrders<T>>::mutate(|ids| {
+				ids.retain(|id| {
+					let conditional = match Self::conditional_orders(id) {
+						Some(conditional) => conditional,
+						None => return false,
+					};
+					if current_ts > conditional.valid_until {
+						return match Self::refund_conditional_order(&conditional) {
+							Ok(()) => {
+								Self::deposit_event(Event::ConditionalOrderExpired(*id));
+								false
+							},
+							Err(e) => {
+								// kept pending, refunded again on the next price
+								log::error!("Conditional order {:?} refund failed: {:?}", id, e);
+								true
+							},
+						}
+					}
+					if Self::oracle_symbol_of(conditional.pair_id) != symbol ||
+						!conditional.direction.is_triggered(conditional.trigger_price, price) ||
+						triggered.len() >= max_triggers
+					{
+						return true
+					}
+
+					triggered.push(conditional);
+					false
+				})
+			});
+			// placed outside of the mutate above: the new orders may be watched as barrier orders
+			let mut pools_visited = 0u32;
+			for conditional in triggered.iter() {
+				let pools = Self::trigger_conditional_order(conditional, price);
+				pools_visited = pools_visited.saturating_add(pools);
+			}
+
+			let triggers = triggered.len() as u32;
+			T::WeightInfo::on_new_price(
+				barrier_orders,
+				pending.saturating_sub(triggers),
+				triggers,
+				pending_expiries,
+			)
+			.saturating_add(Self::pools_visited_weight(pools_visited))
+		}
+
+		fn max_weight() -> Weight {
+			let max_triggers = T::MaxTriggersPerPrice::get();
+			T::WeightInfo::on_new_price(
+				T::MaxBarrierOrders::get(),
+				T::MaxConditionalOrders::get().saturating_sub(max_triggers),
+				max_triggers,
+				T::MaxPendingExpiries::get(),
+			)
+			.saturating_add(Self::pools_visited_weight(
+				T::MaxPools::get().saturating_mul(max_triggers),
+			))
+		}
+	}
 }
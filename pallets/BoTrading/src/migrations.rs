@@ -0,0 +1,202 @@
+//! Storage migrations of the BoTrading pallet
+
+/// Version 1: the order layout of the pair registry, multi assets and p2p matching
+///
+/// Version 0 orders were placed on a `CurrencyPair` enum in the native currency:
+/// - the pair becomes the registered pair with the same oracle symbol, a disabled pair
+/// 	is registered for the symbols missing from the registry
+/// - an open order is refunded: its pool pays the stake back in `NativeCurrency`, it would
+/// 	otherwise be settled in the `LegacyAssetId` asset it was never staked in.
+/// 	An order its pool can not refund is left `Checking`, it is never settled
+/// - the order is traded against its pool only and paid no protocol fee
+///
+/// Runs before the BoLiquidity migration, which returns the native funds left in the pools
+pub mod v1 {
+	use crate::{
+		AccountOf, AssetIdOf, BalanceOf, Config, MarketMode, NextPairId, Order, OrderStatus,
+		Orders, Pairs, Pallet, PairId, SymbolPrice, TradeType, TradingPair,
+	};
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{
+			Currency, ExistenceRequirement, GetStorageVersion, OnRuntimeUpgrade, StorageVersion,
+		},
+		weights::Weight,
+	};
+	use scale_info::prelude::vec::Vec;
+	use frame_support::sp_runtime::traits::{Bounded, One, Zero};
+
+	#[cfg(feature = "try-runtime")]
+	use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+
+	/// `CurrencyPair` of version 0
+	#[derive(Clone, Copy, Decode)]
+	enum OldCurrencyPair {
+		BtcUsdt,
+		DotUsdc,
+		BtcEth,
+	}
+
+	impl OldCurrencyPair {
+		/// (base, quote, oracle symbol)
+		fn symbols(&self) -> (&'static [u8], &'static [u8], &'static [u8]) {
+			match self {
+				OldCurrencyPair::BtcUsdt => (b"BTC", b"USDT", b"BTC_USDT"),
+				OldCurrencyPair::DotUsdc => (b"DOT", b"USDC", b"DOT_USDC"),
+				OldCurrencyPair::BtcEth => (b"BTC", b"ETH", b"BTC_ETH"),
+			}
+		}
+	}
+
+	/// `Order` of version 0
+	#[derive(Decode)]
+	struct OldOrder<T: Config> {
+		id: T::Hash,
+		user_id: AccountOf<T>,
+		currency_pair: OldCurrencyPair,
+		trade_type: TradeType,
+		volume_in_unit: BalanceOf<T>,
+		expired_at: u64,
+		created_at: u64,
+		liquidity_pool_id: AccountOf<T>,
+		payout_rate: u32,
+		open_price: SymbolPrice,
+		close_price: Option<SymbolPrice>,
+		status: OrderStatus,
+	}
+
+	/// The registered pair of a version 0 pair, registered disabled if missing
+	fn pair_id_of<T: Config>(pair: OldCurrencyPair, asset_id: AssetIdOf<T>) -> PairId {
+		let (base, quote, oracle_symbol) = pair.symbols();
+		if let Some((pair_id, _)) =
+			<Pairs<T>>::iter().find(|(_, registered)| registered.oracle_symbol == oracle_symbol)
+		{
+			return pair_id
+		}
+
+		let pair_id = <NextPairId<T>>::get();
+		<Pairs<T>>::insert(
+			pair_id,
+			TradingPair {
+				base: base.to_vec(),
+				quote: quote.to_vec(),
+				oracle_symbol: oracle_symbol.to_vec(),
+				asset_id,
+				timeframes: Default::default(),
+				min_stake: One::one(),
+				max_stake: Bounded::max_value(),
+				enabled: false,
+			},
+		);
+		<NextPairId<T>>::put(pair_id.saturating_add(1));
+		log::info!("BoTrading: registered the legacy pair {:?} as {}", oracle_symbol, pair_id);
+
+		pair_id
+	}
+
+	pub struct MigrateToV1<T, LegacyAssetId, NativeCurrency>(
+		PhantomData<(T, LegacyAssetId, NativeCurrency)>,
+	);
+
+	impl<T, LegacyAssetId, NativeCurrency> OnRuntimeUpgrade
+		for MigrateToV1<T, LegacyAssetId, NativeCurrency>
+	where
+		T: Config,
+		LegacyAssetId: Get<AssetIdOf<T>>,
+		NativeCurrency: Currency<AccountOf<T>, Balance = BalanceOf<T>>,
+	{
+		fn on_runtime_upgrade() -> Weight {
+			let on_chain_version = <Pallet<T>>::on_chain_storage_version();
+			if on_chain_version != 0 {
+				log::info!("BoTrading: skip migration to v1, on chain {:?}", on_chain_version);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let asset_id = LegacyAssetId::get();
+			// pair ids of the version 0 pairs, in `OldCurrencyPair` order
+			let mut pair_ids: [Option<PairId>; 3] = [None; 3];
+			let mut translated = 0u64;
+			let mut refunded = 0u64;
+			<Orders<T>>::translate::<OldOrder<T>, _>(|_, old| {
+				translated += 1;
+				let pair_id = *pair_ids[old.currency_pair as usize]
+					.get_or_insert_with(|| pair_id_of::<T>(old.currency_pair, asset_id));
+				let status = match old.status {
+					OrderStatus::Created => {
+						refunded += 1;
+						let refund = NativeCurrency::transfer(
+							&old.liquidity_pool_id,
+							&old.user_id,
+							old.volume_in_unit,
+							ExistenceRequirement::AllowDeath,
+						);
+						if refund.is_ok() {
+							OrderStatus::Refunded
+						} else {
+							log::error!("BoTrading: v0 order {:?} not refunded", old.id);
+							OrderStatus::Checking
+						}
+					},
+					status => status,
+				};
+				Some(Order {
+					id: old.id,
+					user_id: old.user_id,
+					pair_id,
+					trade_type: old.trade_type,
+					asset_id,
+					volume_in_unit: old.volume_in_unit,
+					protocol_fee: Zero::zero(),
+					expired_at: old.expired_at,
+					created_at: old.created_at,
+					liquidity_pool_id: old.liquidity_pool_id,
+					payout_rate: old.payout_rate,
+					open_price: old.open_price,
+					close_price: old.close_price,
+					status,
+					barrier_hit: false,
+					market: MarketMode::Pool,
+					matches: Vec::new(),
+					matched_volume: Zero::zero(),
+					pool_volume: old.volume_in_unit,
+					tournament_id: None,
+					bonus_expires_at: None,
+				})
+			});
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			log::info!("BoTrading: migrated {} orders to v1, {} refunded", translated, refunded);
+			let pairs = pair_ids.iter().filter(|pair_id| pair_id.is_some()).count() as u64;
+			T::DbWeight::get().reads_writes(
+				translated + refunded * 2 + pairs * 8 + 1,
+				translated + refunded * 2 + pairs * 2 + 1,
+			)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Self::set_temp_storage(<Orders<T>>::iter_keys().count() as u32, "orders");
+
+			Ok(())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			ensure!(<Pallet<T>>::on_chain_storage_version() == 1, "BoTrading: not v1");
+
+			let orders: u32 = Self::get_temp_storage("orders").ok_or("BoTrading: no pre upgrade")?;
+			// translate drops the orders it can not decode
+			ensure!(<Orders<T>>::iter_values().count() as u32 == orders, "BoTrading: orders lost");
+			ensure!(
+				<Orders<T>>::iter_values().all(|order| <Pairs<T>>::contains_key(order.pair_id)),
+				"BoTrading: order without pair"
+			);
+			ensure!(
+				<Orders<T>>::iter_values().all(|order| order.status != OrderStatus::Created),
+				"BoTrading: v0 order still open"
+			);
+
+			Ok(())
+		}
+	}
+}
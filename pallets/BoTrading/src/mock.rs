@@ -1,11 +1,14 @@
 use crate as pallet_bo_trading;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, traits::UnixTime, BoundedVec, PalletId};
 use frame_system as system;
+use pallet_symbol_price::{SymbolPrice, SymbolPriceInterface};
 use sp_core::H256;
 use sp_runtime::{
-	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentityLookup},
+	Permill,
 };
+use std::{cell::RefCell, time::Duration};
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -18,7 +21,11 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		BoTradingModule: pallet_bo_trading::{Pallet, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Config<T>, Event<T>},
+		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
+		BoLiquidityModule: pallet_bo_liquidity::{Pallet, Call, Storage, Event<T>},
+		BoTradingModule: pallet_bo_trading::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -45,7 +52,7 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -53,11 +60,277 @@ impl system::Config for Test {
 	type OnSetCode = ();
 }
 
+/// Transactions submitted by the offchain worker to the pool
+pub type Extrinsic = TestXt<Call, ()>;
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		_public: UintAuthorityId,
+		_account: u64,
+		nonce: u64,
+	) -> Option<(Call, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type AccountStore = System;
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const AssetDeposit: u64 = 1;
+	pub const ApprovalDeposit: u64 = 1;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: u64 = 1;
+	pub const MetadataDepositPerByte: u64 = 1;
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
+impl pallet_randomness_collective_flip::Config for Test {}
+
+parameter_types! {
+	pub const BoLiquidityPalletId: PalletId = PalletId(*b"BrightOp");
+	pub const MaxPools: u32 = 100;
+	pub const MaxCurvePoints: u32 = 8;
+}
+
+impl pallet_bo_liquidity::Config for Test {
+	type Event = Event;
+
+	type Assets = Assets;
+	type MyRandomness = RandomnessCollectiveFlip;
+	type PalletId = BoLiquidityPalletId;
+	type MaxPools = MaxPools;
+	type MaxCurvePoints = MaxCurvePoints;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	static PRICE: RefCell<Option<(SymbolPrice, u64)>> = RefCell::new(None);
+	static NOW: RefCell<u64> = RefCell::new(START_TS);
+}
+
+/// Oracle price fed by the tests: (price, block of the update)
+pub struct MockSymbolPrice;
+
+impl SymbolPriceInterface for MockSymbolPrice {
+	fn get_price_at(_symbol: Vec<u8>, _unix_ts: Option<u64>) -> Option<SymbolPrice> {
+		PRICE.with(|p| p.borrow().map(|(price, _)| price))
+	}
+
+	fn get_price(symbol: Vec<u8>) -> Option<SymbolPrice> {
+		Self::get_price_at(symbol, None)
+	}
+
+	fn fetch_live_price(symbol: Vec<u8>) -> Option<SymbolPrice> {
+		Self::get_price_at(symbol, None)
+	}
+
+	fn price_updated_at(_symbol: Vec<u8>) -> Option<u64> {
+		PRICE.with(|p| p.borrow().map(|(_, updated_at)| updated_at))
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn set_price(_symbol: Vec<u8>, price: SymbolPrice) {
+		set_price(price)
+	}
+}
+
+/// Feed a new oracle price at the current block
+pub fn set_price(price: SymbolPrice) {
+	let block_number = System::block_number();
+	PRICE.with(|p| *p.borrow_mut() = Some((price, block_number)));
+}
+
+/// Unix time of the genesis of the mock runtime, a whole minute
+pub const START_TS: u64 = 1_650_000_000;
+
+/// Clock set by the tests
+pub struct MockTime;
+
+impl UnixTime for MockTime {
+	fn now() -> Duration {
+		Duration::from_secs(NOW.with(|now| *now.borrow()))
+	}
+}
+
+/// Move the clock to `unix_ts`
+pub fn set_now(unix_ts: u64) {
+	NOW.with(|now| *now.borrow_mut() = unix_ts);
+}
+
+parameter_types! {
+	pub const SellBackLockPeriod: u64 = 30;
+	pub const SellBackSpread: Permill = Permill::from_percent(5);
+	pub const PriceVolatility: Permill = Permill::from_parts(1_000);
+	pub const CancelWindow: u64 = 10;
+	pub const CancelFee: Permill = Permill::from_percent(1);
+	pub const MaxTimeframes: u32 = 4;
+	pub DefaultTimeframes: BoundedVec<u64, MaxTimeframes> =
+		BoundedVec::try_from(vec![60, 300]).unwrap();
+	pub const OracleUpdateInterval: u64 = 2;
+	pub const MinLeadIntervals: u64 = 5;
+	pub const MaxBarrierOrders: u32 = 16;
+	// orders get the base payout rate of their pool
+	pub const ImbalanceSensitivity: u32 = 0;
+	pub const MinPayoutRate: u32 = 50;
+	pub const MaxPayoutRate: u32 = 200;
+	pub const BoTradingPalletId: PalletId = PalletId(*b"BoTradin");
+	pub const MaxRestingOrders: u32 = 16;
+	pub const TreasuryPalletId: PalletId = PalletId(*b"BoTreasu");
+	pub const MaxProtocolFeeBps: u32 = 1_000;
+	pub const FeePeriod: u64 = 86_400;
+	pub const LossStreakCooldown: u64 = 3_600;
+	pub const KeeperRewardBps: u32 = 1_000;
+	pub const MaxSettleBatch: u32 = 10;
+	pub const MaxOrdersPerBatch: u32 = 10;
+	pub const MaxConditionalOrders: u32 = 16;
+	pub const MaxTriggersPerPrice: u32 = 2;
+	pub const MaxPendingExpiries: u32 = 16;
+	pub const MaxFollowers: u32 = 10;
+	pub const MaxLeaderFeeBps: u32 = 2_000;
+	pub const MinCopyBudget: u64 = 100;
+	pub const LeaderboardPeriod: u64 = 604_800;
+	pub const LeaderboardSize: u32 = 10;
+	pub const MaxParticipants: u32 = 10;
+	pub const MaxPrizeRanks: u32 = 3;
+	pub const MaxTournamentPairs: u32 = 4;
+	pub const TournamentGracePeriod: u64 = 3_600;
+	pub const MaxCreditGrants: u32 = 4;
+	pub const MaxReferralTiers: u32 = 3;
+	pub const UnsignedInterval: u64 = 1;
+}
+
 impl pallet_bo_trading::Config for Test {
 	type Event = Event;
+	type Assets = Assets;
+	type BoLiquidity = BoLiquidityModule;
+	type SymbolPriceModule = MockSymbolPrice;
+	type MyRandomness = RandomnessCollectiveFlip;
+	type TimeProvider = MockTime;
+	type UnsignedInterval = UnsignedInterval;
+	type SellBackLockPeriod = SellBackLockPeriod;
+	type SellBackSpread = SellBackSpread;
+	type PriceVolatility = PriceVolatility;
+	type CancelWindow = CancelWindow;
+	type CancelFee = CancelFee;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type DefaultTimeframes = DefaultTimeframes;
+	type OracleUpdateInterval = OracleUpdateInterval;
+	type MinLeadIntervals = MinLeadIntervals;
+	type MaxBarrierOrders = MaxBarrierOrders;
+	type ImbalanceSensitivity = ImbalanceSensitivity;
+	type MinPayoutRate = MinPayoutRate;
+	type MaxPayoutRate = MaxPayoutRate;
+	type PalletId = BoTradingPalletId;
+	type MaxRestingOrders = MaxRestingOrders;
+	type TreasuryPalletId = TreasuryPalletId;
+	type MaxProtocolFeeBps = MaxProtocolFeeBps;
+	type FeePeriod = FeePeriod;
+	type LossStreakCooldown = LossStreakCooldown;
+	type KeeperRewardBps = KeeperRewardBps;
+	type MaxSettleBatch = MaxSettleBatch;
+	type MaxOrdersPerBatch = MaxOrdersPerBatch;
+	type MaxConditionalOrders = MaxConditionalOrders;
+	type MaxFollowers = MaxFollowers;
+	type MaxLeaderFeeBps = MaxLeaderFeeBps;
+	type MinCopyBudget = MinCopyBudget;
+	type LeaderboardPeriod = LeaderboardPeriod;
+	type LeaderboardSize = LeaderboardSize;
+	type MaxParticipants = MaxParticipants;
+	type MaxPrizeRanks = MaxPrizeRanks;
+	type MaxTournamentPairs = MaxTournamentPairs;
+	type TournamentGracePeriod = TournamentGracePeriod;
+	type VoucherSignature = TestSignature;
+	type VoucherSigner = UintAuthorityId;
+	type MaxCreditGrants = MaxCreditGrants;
+	type MaxReferralTiers = MaxReferralTiers;
+	type MaxPools = MaxPools;
+	type MaxTimeframes = MaxTimeframes;
+	type MaxTriggersPerPrice = MaxTriggersPerPrice;
+	type MaxPendingExpiries = MaxPendingExpiries;
+	type WeightInfo = ();
 }
 
+/// Dev stable coin of the mock runtime
+pub const USDT: u32 = 1;
+/// Admin of the liquidity pools
+pub const LP_ADMIN: u64 = 1;
+/// Traders
+pub const ALICE: u64 = 2;
+pub const BOB: u64 = 3;
+/// USDT balance of every account at genesis
+pub const INITIAL_USDT: u64 = 1_000_000;
+
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(LP_ADMIN, 1_000_000), (ALICE, 1_000_000), (BOB, 1_000_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	// sufficient: pool, escrow and treasury accounts can hold USDT without a native balance
+	pallet_assets::GenesisConfig::<Test> {
+		assets: vec![(USDT, LP_ADMIN, true, 1)],
+		metadata: vec![(USDT, b"Tether USD".to_vec(), b"USDT".to_vec(), 6)],
+		accounts: vec![
+			(USDT, LP_ADMIN, INITIAL_USDT),
+			(USDT, ALICE, INITIAL_USDT),
+			(USDT, BOB, INITIAL_USDT),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		PRICE.with(|p| *p.borrow_mut() = None);
+		set_now(START_TS);
+	});
+	ext
 }
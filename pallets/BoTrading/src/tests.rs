@@ -1,26 +1,1412 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	migrations, mock::*, CopyStake, Error, OrderRequest, OrderStatus, Orders, PairId, PausePolicy,
+	RebateSource, RiskLimits, SymbolPrice, TradeType, TraderStats, TradingPair, TriggerDirection,
+	Voucher,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::unhashed,
+	traits::{
+		ConstU32, GenesisBuild, GetStorageVersion, OffchainWorker, OnRuntimeUpgrade,
+		StorageVersion,
+	},
+	unsigned::ValidateUnsigned,
+	BoundedVec,
+};
+use pallet_bo_liquidity::PositionSide;
+use pallet_symbol_price::OnNewPrice;
+use sp_core::{
+	offchain::{testing::TestTransactionPoolExt, TransactionPoolExt},
+	H256,
+};
+use sp_runtime::{
+	testing::TestSignature,
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	Permill,
+};
+
+const PAIR: PairId = 0;
+const TIMEFRAME: u64 = 60;
+const OPEN_PRICE: SymbolPrice = 40_000;
+const STAKE: u64 = 1_000;
+/// Funds of the pool of the tests, the minimum of `create_lp`
+const POOL_FUNDS: u64 = 10_000;
+/// Stake plus profit of a winning order of `STAKE` at the 90% payout rate of the pool
+const PAYOUT: u64 = 1_900;
+
+fn btc_usdt() -> TradingPair<Test> {
+	TradingPair {
+		base: b"BTC".to_vec(),
+		quote: b"USDT".to_vec(),
+		oracle_symbol: b"BTC_USDT".to_vec(),
+		asset_id: USDT,
+		timeframes: BoundedVec::try_from(vec![TIMEFRAME]).unwrap(),
+		min_stake: 10,
+		max_stake: 100_000,
+		enabled: true,
+	}
+}
+
+/// Register the BTC_USDT pair and quote it at `OPEN_PRICE`, without any pool
+fn setup_pair() {
+	assert_ok!(BoTradingModule::add_pair(Origin::root(), btc_usdt()));
+	set_price(OPEN_PRICE);
+}
+
+/// `setup_pair` with a pool of `POOL_FUNDS` paying 90%, returns the pool account
+fn setup_market() -> u64 {
+	setup_pair();
+	assert_ok!(BoLiquidityModule::create_lp(
+		Origin::signed(LP_ADMIN),
+		b"Lp 1".to_vec(),
+		90,
+		USDT,
+		POOL_FUNDS
+	));
+	BoLiquidityModule::sub_account_id(0)
+}
+
+/// The earliest expiry of an order placed now
+fn next_expiry() -> u64 {
+	BoTradingModule::next_expiry(START_TS, TIMEFRAME)
+}
+
+/// Place an order of `stake` expiring at `next_expiry`, returns its id
+fn place(who: u64, trade_type: TradeType, stake: u64) -> H256 {
+	assert_ok!(BoTradingModule::place_order(
+		Origin::signed(who),
+		PAIR,
+		trade_type,
+		stake,
+		next_expiry()
+	));
+	*BoTradingModule::user_orders(who).last().unwrap()
+}
+
+/// Feed a new BTC_USDT price, the trading pallet observes it as it does the SymbolPrice ones
+fn feed_price(price: SymbolPrice) {
+	set_price(price);
+	BoTradingModule::on_new_price(b"BTC_USDT".to_vec(), price);
+}
+
+/// Settle an order at expiry at `close_price`, as the offchain worker does
+fn close(order_id: H256, close_price: SymbolPrice) {
+	set_now(next_expiry());
+	feed_price(close_price);
+	assert_ok!(BoTradingModule::close_order(Origin::none(), 1, order_id));
+}
+
+#[test]
+fn genesis_pools_and_pairs_are_ready_to_trade() {
+	new_test_ext().execute_with(|| {
+		GenesisBuild::<Test>::build(&pallet_bo_liquidity::GenesisConfig::<Test> {
+			pools: vec![(LP_ADMIN, b"Lp 1".to_vec(), 90, USDT, POOL_FUNDS)],
+		});
+		GenesisBuild::<Test>::build(&crate::GenesisConfig::<Test> { pairs: vec![btc_usdt()] });
+		set_price(OPEN_PRICE);
+
+		let lp_id = BoLiquidityModule::sub_account_id(0);
+		assert_eq!(BoLiquidityModule::liquidity_pools(lp_id).unwrap().admin, LP_ADMIN);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS);
+		assert_eq!(Assets::balance(USDT, LP_ADMIN), INITIAL_USDT - POOL_FUNDS);
+		assert_eq!(BoTradingModule::pairs(PAIR), Some(btc_usdt()));
+		assert_eq!(BoTradingModule::next_pair_id(), PAIR + 1);
+
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().liquidity_pool_id, lp_id);
+	});
+}
+
+#[test]
+fn place_order_moves_the_stake_to_the_pool() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+
+		let order = BoTradingModule::orders(order_id).unwrap();
+		assert_eq!(order.user_id, ALICE);
+		assert_eq!(order.liquidity_pool_id, lp_id);
+		assert_eq!(order.open_price, OPEN_PRICE);
+		assert_eq!(order.payout_rate, 90);
+		assert_eq!(order.expired_at, next_expiry());
+		assert_eq!(order.status, OrderStatus::Created);
+		assert_eq!(BoTradingModule::order_count(), 1);
+
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + STAKE);
+		assert_eq!(BoLiquidityModule::liquidity_pools(lp_id).unwrap().amount, POOL_FUNDS + STAKE);
+	});
+}
+
+#[test]
+fn winning_order_is_paid_by_the_pool() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+
+		close(order_id, OPEN_PRICE + 100);
+
+		let order = BoTradingModule::orders(order_id).unwrap();
+		assert_eq!(order.status, OrderStatus::Win);
+		assert_eq!(order.close_price, Some(OPEN_PRICE + 100));
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE + PAYOUT);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + STAKE - PAYOUT);
+		assert_eq!(
+			BoLiquidityModule::liquidity_pools(lp_id).unwrap().amount,
+			POOL_FUNDS + STAKE - PAYOUT
+		);
+		System::assert_last_event(Event::BoTradingModule(crate::Event::OrderClosed {
+			account_id: ALICE,
+			order_id,
+			close_price: OPEN_PRICE + 100,
+			status: OrderStatus::Win,
+			amount_payout: PAYOUT,
+			protocol_fee: 0,
+		}));
+	});
+}
+
+#[test]
+fn losing_order_stake_stays_in_the_pool() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let order_id = place(ALICE, TradeType::Put, STAKE);
+
+		close(order_id, OPEN_PRICE + 100);
+
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Lose);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + STAKE);
+	});
+}
+
+#[test]
+fn tie_is_won_by_the_pool() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let call_id = place(ALICE, TradeType::Call, STAKE);
+		let put_id = place(BOB, TradeType::Put, STAKE);
+
+		close(call_id, OPEN_PRICE);
+		close(put_id, OPEN_PRICE);
+
+		// neither side is in the money at the open price
+		assert_eq!(BoTradingModule::orders(call_id).unwrap().status, OrderStatus::Lose);
+		assert_eq!(BoTradingModule::orders(put_id).unwrap().status, OrderStatus::Lose);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE);
+		assert_eq!(Assets::balance(USDT, BOB), INITIAL_USDT - STAKE);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + 2 * STAKE);
+	});
+}
+
+#[test]
+fn strike_and_range_orders_are_settled_against_their_own_prices() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		// strike orders are quoted by the pricing curve of the pool
+		assert_ok!(BoLiquidityModule::set_pricing_curve(
+			Origin::signed(LP_ADMIN),
+			lp_id,
+			BoundedVec::try_from(vec![(-100, 50), (0, 90), (200, 190)]).unwrap()
+		));
+		// above the open price but below the strike
+		let higher_id = place(ALICE, TradeType::Higher { strike: OPEN_PRICE + 200 }, STAKE);
+		let lower_id = place(ALICE, TradeType::Lower { strike: OPEN_PRICE + 200 }, STAKE);
+		let in_range_id = place(
+			BOB,
+			TradeType::InRange { low: OPEN_PRICE - 100, high: OPEN_PRICE + 100 },
+			STAKE,
+		);
+		let out_range_id = place(
+			BOB,
+			TradeType::OutRange { low: OPEN_PRICE - 100, high: OPEN_PRICE + 100 },
+			STAKE,
+		);
+
+		for order_id in [higher_id, lower_id, in_range_id, out_range_id] {
+			close(order_id, OPEN_PRICE + 100);
+		}
+
+		let status_of = |order_id| BoTradingModule::orders(order_id).unwrap().status;
+		assert_eq!(status_of(higher_id), OrderStatus::Lose);
+		assert_eq!(status_of(lower_id), OrderStatus::Win);
+		// the band bounds are inside the range
+		assert_eq!(status_of(in_range_id), OrderStatus::Win);
+		assert_eq!(status_of(out_range_id), OrderStatus::Lose);
+	});
+}
+
+#[test]
+fn order_needs_a_pool_of_the_pair_asset() {
+	new_test_ext().execute_with(|| {
+		setup_pair();
+
+		assert_noop!(
+			BoTradingModule::place_order(
+				Origin::signed(ALICE),
+				PAIR,
+				TradeType::Call,
+				STAKE,
+				next_expiry()
+			),
+			Error::<Test>::NoLiquidityPool
+		);
+	});
+}
+
+#[test]
+fn winning_order_stays_open_if_the_pool_can_not_pay() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		// the payout of 38_000 is more than the 30_000 held by the pool
+		let order_id = place(ALICE, TradeType::Call, 20_000);
+
+		set_now(next_expiry());
+		feed_price(OPEN_PRICE + 100);
+		let closed = BoTradingModule::close_order(Origin::none(), 1, order_id);
+		assert!(closed.is_err());
+
+		let order = BoTradingModule::orders(order_id).unwrap();
+		assert_eq!(order.status, OrderStatus::Created);
+		assert_eq!(order.close_price, None);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - 20_000);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + 20_000);
+	});
+}
+
+#[test]
+fn order_must_expire_on_a_timeframe_boundary_after_the_lead_time() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let place_expiring_at = |expired_at| {
+			BoTradingModule::place_order(
+				Origin::signed(ALICE),
+				PAIR,
+				TradeType::Call,
+				STAKE,
+				expired_at,
+			)
+		};
+
+		// 10 seconds of lead time: 5 oracle updates of 2 seconds
+		assert_eq!(next_expiry(), START_TS + TIMEFRAME);
+		assert_noop!(place_expiring_at(START_TS + 5), Error::<Test>::InvalidExpiredAt);
+		assert_noop!(place_expiring_at(next_expiry() + 1), Error::<Test>::TimeframeNotAllowed);
+		// only the first boundary after the lead time is open
+		assert_noop!(
+			place_expiring_at(next_expiry() + TIMEFRAME),
+			Error::<Test>::TimeframeNotAllowed
+		);
+
+		assert_ok!(place_expiring_at(next_expiry()));
+	});
+}
+
+#[test]
+fn offchain_worker_submits_unsigned_close_of_expired_orders() {
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+
+		// nothing to settle before the expiry
+		BoTradingModule::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+
+		// nor before the first price at or after the expiry
+		set_now(next_expiry());
+		BoTradingModule::offchain_worker(2);
+		assert!(pool_state.read().transactions.is_empty());
+
+		feed_price(OPEN_PRICE + 100);
+		BoTradingModule::offchain_worker(2);
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, None);
+		let call = crate::Call::close_order { block_number: 2, order_id };
+		assert_eq!(tx.call, Call::BoTradingModule(call.clone()));
+
+		assert_ok!(<BoTradingModule as ValidateUnsigned>::validate_unsigned(
+			TransactionSource::Local,
+			&call
+		));
+		assert_ok!(BoTradingModule::close_order(Origin::none(), 2, order_id));
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Win);
+		assert_eq!(BoTradingModule::next_unsigned_at(), 2 + UnsignedInterval::get());
+	});
+}
+
+#[test]
+fn order_can_not_be_closed_twice() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+		close(order_id, OPEN_PRICE + 100);
+
+		let call = crate::Call::<Test>::close_order { block_number: 2, order_id };
+		assert_eq!(
+			<BoTradingModule as ValidateUnsigned>::validate_unsigned(
+				TransactionSource::External,
+				&call
+			),
+			Err(InvalidTransaction::Stale.into())
+		);
+		assert_noop!(
+			BoTradingModule::close_order(Origin::none(), 2, order_id),
+			Error::<Test>::OrderNotOpen
+		);
+		// nor settled again by a keeper
+		assert_ok!(BoTradingModule::settle_expired(Origin::signed(BOB), vec![order_id]));
+
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE + PAYOUT);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + STAKE - PAYOUT);
+		assert_eq!(BoTradingModule::account_risk(ALICE).open_orders, 0);
+	});
+}
+
+#[test]
+fn keeper_is_rewarded_out_of_the_protocol_fees() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		// 1% of the stake, 10% of the stake won by the pool
+		assert_ok!(BoTradingModule::set_protocol_fees(Origin::root(), 100, 1_000));
+		let order_id = place(ALICE, TradeType::Put, STAKE);
+		let treasury = BoTradingModule::treasury_account_id();
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE - 10);
+		assert_eq!(Assets::balance(USDT, treasury), 10);
+
+		// not expired yet: nothing settled
+		assert_ok!(BoTradingModule::settle_expired(Origin::signed(BOB), vec![order_id]));
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Created);
+
+		set_now(next_expiry());
+		feed_price(OPEN_PRICE + 100);
+		assert_ok!(BoTradingModule::settle_expired(Origin::signed(BOB), vec![order_id]));
+
+		// 10% of the 10 + 100 protocol fees
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Lose);
+		assert_eq!(Assets::balance(USDT, BOB), INITIAL_USDT + 11);
+		assert_eq!(Assets::balance(USDT, treasury), 110 - 11);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + STAKE - 100);
+		System::assert_has_event(Event::BoTradingModule(crate::Event::KeeperRewarded(
+			BOB, order_id, 11,
+		)));
+	});
+}
+
+#[test]
+fn protocol_fees_are_capped_and_collected_per_period() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_noop!(
+			BoTradingModule::set_protocol_fees(Origin::root(), MaxProtocolFeeBps::get() + 1, 0),
+			Error::<Test>::InvalidProtocolFee
+		);
+
+		// 1% of the stake
+		assert_ok!(BoTradingModule::set_protocol_fees(Origin::root(), 100, 0));
+		place(ALICE, TradeType::Call, STAKE);
+		place(BOB, TradeType::Call, STAKE);
+
+		let period = START_TS / FeePeriod::get();
+		assert_eq!(
+			BoTradingModule::fees_collected(USDT, period, period + 1),
+			vec![(period, 20), (period + 1, 0)]
+		);
+	});
+}
+
+#[test]
+fn self_limits_can_only_be_tightened() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let one_order = RiskLimits::<Test> { max_open_orders: Some(1), ..Default::default() };
+		assert_ok!(BoTradingModule::set_self_limits(Origin::signed(ALICE), one_order));
+		assert_noop!(
+			BoTradingModule::set_self_limits(Origin::signed(ALICE), RiskLimits::default()),
+			Error::<Test>::CanOnlyTightenLimits
+		);
+
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+		assert_noop!(
+			BoTradingModule::place_order(
+				Origin::signed(ALICE),
+				PAIR,
+				TradeType::Call,
+				STAKE,
+				next_expiry()
+			),
+			Error::<Test>::TooManyOpenOrders
+		);
+
+		// a closed order frees its slot
+		close(order_id, OPEN_PRICE + 100);
+		assert_eq!(BoTradingModule::account_risk(ALICE).open_orders, 0);
+	});
+}
+
+#[test]
+fn excluded_account_can_not_trade() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(BoTradingModule::self_exclude(Origin::signed(ALICE), 600));
+		assert_noop!(
+			BoTradingModule::place_order(
+				Origin::signed(ALICE),
+				PAIR,
+				TradeType::Call,
+				STAKE,
+				next_expiry()
+			),
+			Error::<Test>::AccountExcluded
+		);
+
+		set_now(START_TS + 600);
+		assert_ok!(BoTradingModule::place_order(
+			Origin::signed(ALICE),
+			PAIR,
+			TradeType::Call,
+			STAKE,
+			BoTradingModule::next_expiry(START_TS + 600, TIMEFRAME)
+		));
+	});
+}
+
+#[test]
+fn order_is_settled_at_the_first_price_at_or_after_its_expiry() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+		let call = crate::Call::<Test>::close_order { block_number: 1, order_id };
+		let stale = || {
+			<BoTradingModule as ValidateUnsigned>::validate_unsigned(
+				TransactionSource::External,
+				&call,
+			)
+		};
+
+		// not expired yet
+		assert_eq!(stale(), Err(InvalidTransaction::Stale.into()));
+		assert_noop!(
+			BoTradingModule::close_order(Origin::none(), 1, order_id),
+			Error::<Test>::OrderNotExpired
+		);
+		assert_noop!(
+			BoTradingModule::close_order(Origin::signed(ALICE), 1, order_id),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		// a price before the expiry does not settle the order
+		feed_price(OPEN_PRICE + 100);
+		set_now(next_expiry());
+		assert_eq!(stale(), Err(InvalidTransaction::Stale.into()));
+		assert_noop!(
+			BoTradingModule::close_order(Origin::none(), 1, order_id),
+			Error::<Test>::SettlementPriceNotAvailable
+		);
+
+		// the order loses at the first price after its expiry, the later ones are ignored
+		feed_price(OPEN_PRICE - 100);
+		set_now(next_expiry() + 2);
+		feed_price(OPEN_PRICE + 200);
+		assert_eq!(BoTradingModule::settlement_price(PAIR, next_expiry()), Some(OPEN_PRICE - 100));
+		assert_ok!(BoTradingModule::close_order(Origin::none(), 1, order_id));
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Lose);
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().close_price, Some(OPEN_PRICE - 100));
+	});
+}
+
+#[test]
+fn only_close_order_is_accepted_unsigned() {
+	new_test_ext().execute_with(|| {
+		let call = crate::Call::<Test>::cancel_order { order_id: H256::zero() };
+
+		assert_eq!(
+			<BoTradingModule as ValidateUnsigned>::validate_unsigned(
+				TransactionSource::External,
+				&call
+			),
+			Err(InvalidTransaction::Call.into())
+		);
+	});
+}
+
+#[test]
+fn order_is_sold_back_at_its_quoted_value() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+
+		set_price(OPEN_PRICE + 100);
+		let order = BoTradingModule::orders(order_id).unwrap();
+		let quote = BoTradingModule::quote_sell_back(&order, OPEN_PRICE + 100, START_TS);
+		assert!(quote > 0 && quote < PAYOUT);
+		assert_ok!(BoTradingModule::sell_back_order(Origin::signed(ALICE), order_id));
+
+		let order = BoTradingModule::orders(order_id).unwrap();
+		assert_eq!(order.status, OrderStatus::Sold);
+		assert_eq!(order.close_price, Some(OPEN_PRICE + 100));
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE + quote);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + STAKE - quote);
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn sell_back_needs_a_pool_able_to_pay_the_quote() {
 	new_test_ext().execute_with(|| {
-		// Dispatch a signed extrinsic.
-		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
-		// Read pallet storage and assert an expected result.
-		assert_eq!(TemplateModule::something(), Some(42));
+		let lp_id = setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+
+		set_price(OPEN_PRICE + 100);
+		let order = BoTradingModule::orders(order_id).unwrap();
+		let quote = BoTradingModule::quote_sell_back(&order, OPEN_PRICE + 100, START_TS);
+		let drained = Assets::balance(USDT, lp_id) - quote;
+		assert_ok!(Assets::transfer(Origin::signed(lp_id), USDT, BOB, drained));
+
+		assert_noop!(
+			BoTradingModule::sell_back_order(Origin::signed(ALICE), order_id),
+			Error::<Test>::PoolNotEnoughBalance
+		);
 	});
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn sell_back_is_locked_before_expiry() {
 	new_test_ext().execute_with(|| {
-		// Ensure the expected error is thrown when no value is present.
-		assert_noop!(TemplateModule::cause_error(Origin::signed(1)), Error::<Test>::NoneValue);
+		setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+
+		set_now(next_expiry() - SellBackLockPeriod::get());
+		assert_noop!(
+			BoTradingModule::sell_back_order(Origin::signed(ALICE), order_id),
+			Error::<Test>::SellBackLocked
+		);
 	});
 }
 
+#[test]
+fn order_is_cancelled_within_the_window_minus_the_fee() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+		let fee = CancelFee::get() * STAKE;
+
+		set_now(START_TS + CancelWindow::get());
+		assert_ok!(BoTradingModule::cancel_order(Origin::signed(ALICE), order_id));
+
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Cancelled);
+		assert!(!BoTradingModule::user_orders(ALICE).contains(&order_id));
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - fee);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + fee);
+		System::assert_last_event(Event::BoTradingModule(crate::Event::OrderCancelled {
+			account_id: ALICE,
+			order_id,
+			amount_refund: STAKE - fee,
+		}));
+	});
+}
+
+#[test]
+fn order_can_not_be_cancelled_after_the_window() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+
+		set_now(START_TS + CancelWindow::get() + 1);
+		assert_noop!(
+			BoTradingModule::cancel_order(Origin::signed(ALICE), order_id),
+			Error::<Test>::CancelWindowPassed
+		);
+	});
+}
 
-/*
-Test:
-- [ ] same order data at the same timestamp, ... must created different order_id
- */
+#[test]
+fn settled_orders_update_the_stats_and_the_leaderboard() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let win_id = place(ALICE, TradeType::Call, STAKE);
+		let lose_id = place(BOB, TradeType::Put, STAKE);
+		close(win_id, OPEN_PRICE + 100);
+		close(lose_id, OPEN_PRICE + 100);
+
+		let winner = TraderStats { orders: 1, wins: 1, losses: 0, volume: STAKE, payout: PAYOUT };
+		let loser = TraderStats { orders: 1, wins: 0, losses: 1, volume: STAKE, payout: 0 };
+		assert_eq!(BoTradingModule::account_stats(ALICE, USDT), winner);
+		assert_eq!(BoTradingModule::account_stats(BOB, USDT), loser);
+		assert_eq!(
+			BoTradingModule::pair_stats(PAIR),
+			TraderStats { orders: 2, wins: 1, losses: 1, volume: 2 * STAKE, payout: PAYOUT }
+		);
+		assert_eq!(BoTradingModule::leaderboard(USDT, None), vec![(ALICE, winner), (BOB, loser)]);
+	});
+}
+
+/// Place a peer-to-peer order of `STAKE` expiring at `next_expiry`, returns its id
+fn place_p2p(who: u64, trade_type: TradeType, pool_residual: bool) -> H256 {
+	assert_ok!(BoTradingModule::place_p2p_order(
+		Origin::signed(who),
+		PAIR,
+		trade_type,
+		STAKE,
+		next_expiry(),
+		pool_residual
+	));
+	*BoTradingModule::user_orders(who).last().unwrap()
+}
+
+#[test]
+fn p2p_order_is_matched_and_paid_by_the_counterparty() {
+	new_test_ext().execute_with(|| {
+		setup_pair();
+		let escrow = BoTradingModule::p2p_account_id();
+		let call_id = place_p2p(ALICE, TradeType::Call, false);
+		let put_id = place_p2p(BOB, TradeType::Put, false);
+
+		assert_eq!(BoTradingModule::orders(call_id).unwrap().matched_volume, STAKE);
+		assert_eq!(BoTradingModule::orders(put_id).unwrap().matched_volume, STAKE);
+		assert_eq!(Assets::balance(USDT, escrow), 2 * STAKE);
+		System::assert_has_event(Event::BoTradingModule(crate::Event::OrderMatched {
+			order_id: put_id,
+			counterparty: call_id,
+			amount: STAKE,
+			price: OPEN_PRICE,
+		}));
+
+		// the match is settled once, with both legs
+		close(call_id, OPEN_PRICE + 100);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT + STAKE);
+		assert_eq!(Assets::balance(USDT, escrow), 0);
+		assert_eq!(BoTradingModule::orders(put_id).unwrap().matches[0].settled, Some(0));
+
+		close(put_id, OPEN_PRICE + 100);
+		assert_eq!(BoTradingModule::orders(call_id).unwrap().status, OrderStatus::Win);
+		assert_eq!(BoTradingModule::orders(put_id).unwrap().status, OrderStatus::Lose);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT + STAKE);
+		assert_eq!(Assets::balance(USDT, BOB), INITIAL_USDT - STAKE);
+	});
+}
+
+#[test]
+fn p2p_tie_refunds_both_sides() {
+	new_test_ext().execute_with(|| {
+		setup_pair();
+		let call_id = place_p2p(ALICE, TradeType::Call, false);
+		let put_id = place_p2p(BOB, TradeType::Put, false);
+
+		close(call_id, OPEN_PRICE);
+		close(put_id, OPEN_PRICE);
+
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT);
+		assert_eq!(Assets::balance(USDT, BOB), INITIAL_USDT);
+		assert_eq!(Assets::balance(USDT, BoTradingModule::p2p_account_id()), 0);
+	});
+}
+
+#[test]
+fn unmatched_p2p_order_is_refunded_at_expiry() {
+	new_test_ext().execute_with(|| {
+		setup_pair();
+		let order_id = place_p2p(ALICE, TradeType::Call, false);
+		let book = (PAIR, next_expiry());
+		assert_eq!(BoTradingModule::resting_orders(book, PositionSide::Call), vec![order_id]);
+
+		close(order_id, OPEN_PRICE + 100);
+
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Refunded);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT);
+		assert!(BoTradingModule::resting_orders(book, PositionSide::Call).is_empty());
+	});
+}
+
+#[test]
+fn paused_pair_refunds_its_open_orders() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let order_id = place(ALICE, TradeType::Call, STAKE);
+
+		assert_ok!(BoTradingModule::pause_trading(Origin::root(), Some(PAIR), PausePolicy::Refund));
+		assert_noop!(
+			BoTradingModule::place_order(
+				Origin::signed(BOB),
+				PAIR,
+				TradeType::Call,
+				STAKE,
+				next_expiry()
+			),
+			Error::<Test>::TradingPaused
+		);
+
+		// the price moved in the money, the whole stake is returned anyway
+		close(order_id, OPEN_PRICE + 100);
+
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Refunded);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS);
+
+		assert_ok!(BoTradingModule::resume_trading(Origin::root(), Some(PAIR)));
+		place(BOB, TradeType::Call, STAKE);
+	});
+}
+
+#[test]
+fn pair_is_updated_without_changing_its_oracle_symbol() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let disabled = TradingPair { enabled: false, ..btc_usdt() };
+		assert_ok!(BoTradingModule::update_pair(Origin::root(), PAIR, disabled.clone()));
+		assert_eq!(BoTradingModule::pairs(PAIR), Some(disabled));
+		assert_noop!(
+			BoTradingModule::place_order(
+				Origin::signed(ALICE),
+				PAIR,
+				TradeType::Call,
+				STAKE,
+				next_expiry()
+			),
+			Error::<Test>::PairDisabled
+		);
+
+		let eth_usdt = TradingPair { oracle_symbol: b"ETH_USDT".to_vec(), ..btc_usdt() };
+		assert_noop!(
+			BoTradingModule::update_pair(Origin::root(), PAIR, eth_usdt),
+			Error::<Test>::InvalidPair
+		);
+		assert_noop!(
+			BoTradingModule::update_pair(Origin::root(), PAIR + 1, btc_usdt()),
+			Error::<Test>::PairNotFound
+		);
+	});
+}
+
+/// A leg of a batch expiring at `next_expiry`
+fn leg(trade_type: TradeType, stake: u64) -> OrderRequest<Test> {
+	OrderRequest { pair_id: PAIR, trade_type, volume_in_unit: stake, expired_at: next_expiry() }
+}
+
+fn batch(legs: Vec<OrderRequest<Test>>) -> BoundedVec<OrderRequest<Test>, MaxOrdersPerBatch> {
+	BoundedVec::try_from(legs).unwrap()
+}
+
+#[test]
+fn identical_batch_legs_are_distinct_orders() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let legs = batch(vec![leg(TradeType::Call, STAKE), leg(TradeType::Call, STAKE)]);
+
+		assert_ok!(BoTradingModule::place_orders(Origin::signed(ALICE), legs, false));
+
+		let order_ids = BoTradingModule::user_orders(ALICE);
+		assert_eq!(order_ids.len(), 2);
+		assert_ne!(order_ids[0], order_ids[1]);
+		for order_id in order_ids.iter() {
+			assert_eq!(BoTradingModule::orders(order_id).unwrap().id, *order_id);
+		}
+		assert_eq!(BoTradingModule::order_count(), 2);
+		assert_eq!(BoTradingModule::account_risk(ALICE).open_orders, 2);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + 2 * STAKE);
+	});
+}
+
+#[test]
+fn atomic_batch_is_rolled_back_by_a_failing_leg() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		// the barrier of the second leg is the open price
+		let legs = batch(vec![
+			leg(TradeType::Call, STAKE),
+			leg(TradeType::Touch { barrier: OPEN_PRICE }, STAKE),
+		]);
+
+		assert_noop!(
+			BoTradingModule::place_orders(Origin::signed(ALICE), legs.clone(), false),
+			Error::<Test>::InvalidBarrier
+		);
+		assert!(BoTradingModule::user_orders(ALICE).is_empty());
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS);
+
+		// best effort: the valid leg is placed, the failing one is reported
+		assert_ok!(BoTradingModule::place_orders(Origin::signed(ALICE), legs, true));
+		let placed = BoTradingModule::user_orders(ALICE);
+		assert_eq!(placed.len(), 1);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE);
+		System::assert_last_event(Event::BoTradingModule(crate::Event::OrdersPlaced {
+			account_id: ALICE,
+			placed,
+			failed: vec![(1, Error::<Test>::InvalidBarrier.into())],
+		}));
+	});
+}
+
+/// Place a conditional Call of `STAKE` on `pair_id`, triggered above `trigger_price`
+/// Returns its id
+fn place_conditional(pair_id: PairId, trigger_price: SymbolPrice) -> H256 {
+	assert_ok!(BoTradingModule::place_conditional_order(
+		Origin::signed(ALICE),
+		pair_id,
+		TradeType::Call,
+		STAKE,
+		TIMEFRAME,
+		trigger_price,
+		TriggerDirection::Above,
+		START_TS + 600,
+	));
+	*BoTradingModule::pending_conditional_orders().last().unwrap()
+}
+
+#[test]
+fn conditional_order_is_opened_when_the_price_crosses_its_trigger() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let id = place_conditional(PAIR, OPEN_PRICE + 100);
+		let escrow = BoTradingModule::account_id();
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE);
+		assert_eq!(Assets::balance(USDT, escrow), STAKE);
+
+		BoTradingModule::on_new_price(b"BTC_USDT".to_vec(), OPEN_PRICE + 50);
+		assert_eq!(BoTradingModule::pending_conditional_orders(), vec![id]);
+
+		set_price(OPEN_PRICE + 100);
+		BoTradingModule::on_new_price(b"BTC_USDT".to_vec(), OPEN_PRICE + 100);
+
+		assert!(BoTradingModule::pending_conditional_orders().is_empty());
+		assert!(BoTradingModule::conditional_orders(id).is_none());
+		let order_id = *BoTradingModule::user_orders(ALICE).last().unwrap();
+		let order = BoTradingModule::orders(order_id).unwrap();
+		assert_eq!(order.open_price, OPEN_PRICE + 100);
+		assert_eq!(order.expired_at, next_expiry());
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE);
+		assert_eq!(Assets::balance(USDT, escrow), 0);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + STAKE);
+		System::assert_has_event(Event::BoTradingModule(
+			crate::Event::ConditionalOrderTriggered {
+				conditional_order_id: id,
+				order_id,
+				price: OPEN_PRICE + 100,
+			},
+		));
+	});
+}
+
+#[test]
+fn conditional_order_is_triggered_by_the_price_of_its_pair() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let eth_usdt = TradingPair { oracle_symbol: b"ETH_USDT".to_vec(), ..btc_usdt() };
+		assert_ok!(BoTradingModule::add_pair(Origin::root(), eth_usdt));
+		let id = place_conditional(PAIR + 1, OPEN_PRICE + 100);
+
+		BoTradingModule::on_new_price(b"BTC_USDT".to_vec(), OPEN_PRICE + 100);
+		assert_eq!(BoTradingModule::pending_conditional_orders(), vec![id]);
+
+		BoTradingModule::on_new_price(b"ETH_USDT".to_vec(), OPEN_PRICE + 100);
+		assert!(BoTradingModule::pending_conditional_orders().is_empty());
+		let order_id = *BoTradingModule::user_orders(ALICE).last().unwrap();
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().pair_id, PAIR + 1);
+	});
+}
+
+#[test]
+fn conditional_triggers_are_bounded_per_price() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		for _ in 0..3 {
+			place_conditional(PAIR, OPEN_PRICE);
+		}
+
+		// MaxTriggersPerPrice orders are opened by each price
+		BoTradingModule::on_new_price(b"BTC_USDT".to_vec(), OPEN_PRICE);
+		assert_eq!(BoTradingModule::user_orders(ALICE).len(), 2);
+		assert_eq!(BoTradingModule::pending_conditional_orders().len(), 1);
+
+		BoTradingModule::on_new_price(b"BTC_USDT".to_vec(), OPEN_PRICE);
+		assert_eq!(BoTradingModule::user_orders(ALICE).len(), 3);
+		assert!(BoTradingModule::pending_conditional_orders().is_empty());
+	});
+}
+
+#[test]
+fn expired_conditional_order_is_refunded() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		let id = place_conditional(PAIR, OPEN_PRICE + 100);
+		let escrow = BoTradingModule::account_id();
+
+		set_now(START_TS + 601);
+		// the escrow is missing: the order stays pending until it can be refunded
+		assert_ok!(Assets::transfer(Origin::signed(escrow), USDT, BOB, STAKE));
+		BoTradingModule::on_new_price(b"BTC_USDT".to_vec(), OPEN_PRICE + 100);
+		assert_eq!(BoTradingModule::pending_conditional_orders(), vec![id]);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - STAKE);
+
+		assert_ok!(Assets::transfer(Origin::signed(BOB), USDT, escrow, STAKE));
+		// expired: refunded instead of triggered
+		BoTradingModule::on_new_price(b"BTC_USDT".to_vec(), OPEN_PRICE + 100);
+		assert!(BoTradingModule::pending_conditional_orders().is_empty());
+		assert!(BoTradingModule::conditional_orders(id).is_none());
+		assert!(BoTradingModule::user_orders(ALICE).is_empty());
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT);
+		System::assert_has_event(Event::BoTradingModule(crate::Event::ConditionalOrderExpired(
+			id,
+		)));
+	});
+}
+
+/// BOB copies the orders of ALICE with a fixed stake of `STAKE / 2`
+fn follow_alice(budget: u64) {
+	assert_ok!(BoTradingModule::follow(
+		Origin::signed(BOB),
+		ALICE,
+		CopyStake::Fixed(STAKE / 2),
+		budget,
+		0
+	));
+}
+
+#[test]
+fn leader_order_is_copied_within_the_follower_budget() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		follow_alice(STAKE);
+
+		let leader_order_id = place(ALICE, TradeType::Call, STAKE);
+		let order_id = *BoTradingModule::user_orders(BOB).last().unwrap();
+		let order = BoTradingModule::orders(order_id).unwrap();
+		assert_eq!(order.volume_in_unit, STAKE / 2);
+		assert_eq!(order.trade_type, TradeType::Call);
+		assert_eq!(BoTradingModule::copy_subscriptions(BOB).unwrap().budget, STAKE / 2);
+		System::assert_has_event(Event::BoTradingModule(crate::Event::OrderCopied {
+			leader: ALICE,
+			follower: BOB,
+			leader_order_id,
+			order_id,
+		}));
+
+		place(ALICE, TradeType::Put, STAKE);
+		// the budget is exhausted by the second copy
+		place(ALICE, TradeType::Put, STAKE);
+		assert_eq!(BoTradingModule::user_orders(BOB).len(), 2);
+	});
+}
+
+#[test]
+fn follow_requires_the_minimum_budget() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			BoTradingModule::follow(
+				Origin::signed(BOB),
+				ALICE,
+				CopyStake::Fixed(STAKE / 2),
+				MinCopyBudget::get() - 1,
+				0
+			),
+			Error::<Test>::CopyBudgetTooLow
+		);
+		follow_alice(MinCopyBudget::get());
+		assert_eq!(BoTradingModule::followers(ALICE), vec![BOB]);
+	});
+}
+
+#[test]
+fn leader_can_remove_a_follower() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		follow_alice(STAKE);
+
+		assert_noop!(
+			BoTradingModule::remove_follower(Origin::signed(LP_ADMIN), BOB),
+			Error::<Test>::NotFollowing
+		);
+		assert_ok!(BoTradingModule::remove_follower(Origin::signed(ALICE), BOB));
+		assert!(BoTradingModule::copy_subscriptions(BOB).is_none());
+		assert!(BoTradingModule::followers(ALICE).is_empty());
+		System::assert_last_event(Event::BoTradingModule(crate::Event::Unfollowed(BOB, ALICE)));
+
+		place(ALICE, TradeType::Call, STAKE);
+		assert!(BoTradingModule::user_orders(BOB).is_empty());
+	});
+}
+
+#[test]
+fn batch_legs_are_not_copied() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		follow_alice(STAKE);
+
+		let legs = batch(vec![leg(TradeType::Call, STAKE)]);
+		assert_ok!(BoTradingModule::place_orders(Origin::signed(ALICE), legs, false));
+		assert!(BoTradingModule::user_orders(BOB).is_empty());
+	});
+}
+
+const ENTRY_FEE: u64 = 100;
+const SPONSORSHIP: u64 = 1_000;
+const VIRTUAL_BALANCE: u64 = 1_000;
+
+/// LP_ADMIN organizes a tournament ending at `next_expiry()`, ALICE and BOB join it
+/// - PAIR is listed twice, the duplicate is removed
+fn setup_tournament() -> u32 {
+	setup_pair();
+	assert_ok!(BoTradingModule::create_tournament(
+		Origin::signed(LP_ADMIN),
+		USDT,
+		ENTRY_FEE,
+		VIRTUAL_BALANCE,
+		START_TS,
+		next_expiry(),
+		BoundedVec::try_from(vec![PAIR, PAIR]).unwrap(),
+		90,
+		vec![Permill::from_percent(70), Permill::from_percent(30)],
+		SPONSORSHIP,
+	));
+	assert_ok!(BoTradingModule::join_tournament(Origin::signed(ALICE), 0));
+	assert_ok!(BoTradingModule::join_tournament(Origin::signed(BOB), 0));
+	0
+}
+
+fn place_virtual(who: u64, tournament_id: u32, trade_type: TradeType) -> H256 {
+	assert_ok!(BoTradingModule::place_tournament_order(
+		Origin::signed(who),
+		tournament_id,
+		PAIR,
+		trade_type,
+		STAKE / 2,
+		next_expiry()
+	));
+	*BoTradingModule::user_orders(who).last().unwrap()
+}
+
+#[test]
+fn tournament_prizes_are_split_by_final_virtual_balance() {
+	new_test_ext().execute_with(|| {
+		let tournament_id = setup_tournament();
+		let alice_order = place_virtual(ALICE, tournament_id, TradeType::Call);
+		let bob_order = place_virtual(BOB, tournament_id, TradeType::Put);
+
+		close(alice_order, OPEN_PRICE + 1);
+		close(bob_order, OPEN_PRICE + 1);
+		assert_eq!(
+			BoTradingModule::tournament_balances(tournament_id, ALICE),
+			Some(VIRTUAL_BALANCE - STAKE / 2 + PAYOUT / 2)
+		);
+		assert_eq!(
+			BoTradingModule::tournament_balances(tournament_id, BOB),
+			Some(VIRTUAL_BALANCE - STAKE / 2)
+		);
+
+		assert_ok!(BoTradingModule::finish_tournament(Origin::signed(BOB), tournament_id));
+		// prize pool: 2 entry fees + sponsorship = 1_200
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT - ENTRY_FEE + 840);
+		assert_eq!(Assets::balance(USDT, BOB), INITIAL_USDT - ENTRY_FEE + 360);
+		assert_eq!(Assets::balance(USDT, LP_ADMIN), INITIAL_USDT - SPONSORSHIP);
+		System::assert_last_event(Event::BoTradingModule(crate::Event::TournamentFinished {
+			tournament_id,
+			prizes: vec![(ALICE, 840), (BOB, 360)],
+		}));
+	});
+}
+
+#[test]
+fn tournament_barrier_orders_are_not_supported() {
+	new_test_ext().execute_with(|| {
+		let tournament_id = setup_tournament();
+		assert_noop!(
+			BoTradingModule::place_tournament_order(
+				Origin::signed(ALICE),
+				tournament_id,
+				PAIR,
+				TradeType::Touch { barrier: OPEN_PRICE + 100 },
+				STAKE / 2,
+				next_expiry()
+			),
+			Error::<Test>::TournamentOrderNotSupported
+		);
+	});
+}
+
+#[test]
+fn excluded_account_can_not_play_tournaments() {
+	new_test_ext().execute_with(|| {
+		let tournament_id = setup_tournament();
+		let tournament = BoTradingModule::tournaments(tournament_id).unwrap();
+		assert_eq!(tournament.pairs.into_inner(), vec![PAIR]);
+
+		assert_ok!(BoTradingModule::self_exclude(Origin::signed(ALICE), 600));
+		assert_noop!(
+			BoTradingModule::place_tournament_order(
+				Origin::signed(ALICE),
+				tournament_id,
+				PAIR,
+				TradeType::Call,
+				STAKE / 2,
+				next_expiry()
+			),
+			Error::<Test>::AccountExcluded
+		);
+		// the organizer has not joined yet
+		assert_ok!(BoTradingModule::self_exclude(Origin::signed(LP_ADMIN), 600));
+		assert_noop!(
+			BoTradingModule::join_tournament(Origin::signed(LP_ADMIN), tournament_id),
+			Error::<Test>::AccountExcluded
+		);
+	});
+}
+
+#[test]
+fn unsettled_tournament_order_is_refunded_after_the_grace_period() {
+	new_test_ext().execute_with(|| {
+		let tournament_id = setup_tournament();
+		let order_id = place_virtual(ALICE, tournament_id, TradeType::Call);
+
+		set_now(next_expiry());
+		assert_noop!(
+			BoTradingModule::finish_tournament(Origin::signed(BOB), tournament_id),
+			Error::<Test>::TournamentNotOver
+		);
+		assert_noop!(
+			BoTradingModule::refund_tournament_order(Origin::signed(BOB), order_id),
+			Error::<Test>::TournamentNotOver
+		);
+
+		set_now(next_expiry() + TournamentGracePeriod::get());
+		assert_ok!(BoTradingModule::refund_tournament_order(Origin::signed(BOB), order_id));
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Refunded);
+		assert_eq!(
+			BoTradingModule::tournament_balances(tournament_id, ALICE),
+			Some(VIRTUAL_BALANCE)
+		);
+		assert_ok!(BoTradingModule::finish_tournament(Origin::signed(BOB), tournament_id));
+	});
+}
+
+/// Give `who` `amount` bonus credits through a voucher of `LP_ADMIN`
+fn redeem_credits(who: u64, amount: u64) {
+	assert_ok!(BoTradingModule::set_voucher_issuer(Origin::root(), Some(LP_ADMIN)));
+	let voucher = Voucher::<Test> {
+		code: H256::repeat_byte(1),
+		beneficiary: who,
+		asset_id: USDT,
+		amount,
+		redeem_before: START_TS + 600,
+		credit_expires_at: START_TS + 3_600,
+	};
+	let proof = TestSignature(LP_ADMIN, BoTradingModule::voucher_payload(&voucher));
+	assert_ok!(BoTradingModule::redeem_voucher(Origin::signed(who), voucher, proof));
+}
+
+#[test]
+fn voucher_is_redeemed_by_its_beneficiary_on_this_chain_only() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(BoTradingModule::set_voucher_issuer(Origin::root(), Some(LP_ADMIN)));
+		let voucher = Voucher::<Test> {
+			code: H256::repeat_byte(1),
+			beneficiary: ALICE,
+			asset_id: USDT,
+			amount: STAKE,
+			redeem_before: START_TS + 600,
+			credit_expires_at: START_TS + 3_600,
+		};
+
+		// signed without the genesis hash
+		let proof = TestSignature(LP_ADMIN, (b"bo_voucher", &voucher).encode());
+		assert_noop!(
+			BoTradingModule::redeem_voucher(Origin::signed(ALICE), voucher.clone(), proof),
+			Error::<Test>::InvalidVoucher
+		);
+
+		let proof = TestSignature(LP_ADMIN, BoTradingModule::voucher_payload(&voucher));
+		assert_noop!(
+			BoTradingModule::redeem_voucher(Origin::signed(BOB), voucher.clone(), proof.clone()),
+			Error::<Test>::VoucherNotForYou
+		);
+		assert_ok!(BoTradingModule::redeem_voucher(Origin::signed(ALICE), voucher, proof));
+	});
+}
+
+#[test]
+fn winning_bonus_order_pays_only_the_profit() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let treasury = BoTradingModule::treasury_account_id();
+		// the treasury is kept alive by the transfer of the stake
+		assert_ok!(Assets::transfer(Origin::signed(LP_ADMIN), USDT, treasury, 2 * STAKE));
+		redeem_credits(ALICE, STAKE);
+
+		assert_ok!(BoTradingModule::place_bonus_order(
+			Origin::signed(ALICE),
+			PAIR,
+			TradeType::Call,
+			STAKE,
+			next_expiry()
+		));
+		let order_id = *BoTradingModule::user_orders(ALICE).last().unwrap();
+		assert!(BoTradingModule::bonus_credits(ALICE, USDT).is_empty());
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT);
+		assert_eq!(Assets::balance(USDT, treasury), STAKE);
+
+		close(order_id, OPEN_PRICE + 100);
+
+		// the stake goes back to the treasury, the trader keeps the profit
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Win);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT + PAYOUT - STAKE);
+		assert_eq!(Assets::balance(USDT, treasury), 2 * STAKE);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + STAKE - PAYOUT);
+	});
+}
+
+#[test]
+fn losing_bonus_order_costs_the_trader_nothing() {
+	new_test_ext().execute_with(|| {
+		let lp_id = setup_market();
+		let treasury = BoTradingModule::treasury_account_id();
+		// the treasury is kept alive by the transfer of the stake
+		assert_ok!(Assets::transfer(Origin::signed(LP_ADMIN), USDT, treasury, 2 * STAKE));
+		redeem_credits(ALICE, STAKE);
+
+		assert_ok!(BoTradingModule::place_bonus_order(
+			Origin::signed(ALICE),
+			PAIR,
+			TradeType::Put,
+			STAKE,
+			next_expiry()
+		));
+		let order_id = *BoTradingModule::user_orders(ALICE).last().unwrap();
+		close(order_id, OPEN_PRICE + 100);
+
+		assert_eq!(BoTradingModule::orders(order_id).unwrap().status, OrderStatus::Lose);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT);
+		assert_eq!(Assets::balance(USDT, treasury), STAKE);
+		assert_eq!(Assets::balance(USDT, lp_id), POOL_FUNDS + STAKE);
+	});
+}
+
+#[test]
+fn bonus_order_needs_a_funded_treasury() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		redeem_credits(ALICE, STAKE);
+
+		assert_noop!(
+			BoTradingModule::place_bonus_order(
+				Origin::signed(ALICE),
+				PAIR,
+				TradeType::Call,
+				STAKE,
+				next_expiry()
+			),
+			Error::<Test>::TreasuryNotEnoughBalance
+		);
+		assert_eq!(BoTradingModule::bonus_credits(ALICE, USDT).len(), 1);
+	});
+}
+
+#[test]
+fn referral_tiers_leave_room_for_the_keeper_reward() {
+	new_test_ext().execute_with(|| {
+		// the keeper takes 10% of the protocol fees
+		assert_noop!(
+			BoTradingModule::set_referral_program(
+				Origin::root(),
+				vec![Permill::from_percent(60), Permill::from_percent(31)],
+				RebateSource::ProtocolFee
+			),
+			Error::<Test>::InvalidReferralProgram
+		);
+		assert_ok!(BoTradingModule::set_referral_program(
+			Origin::root(),
+			vec![Permill::from_percent(60), Permill::from_percent(30)],
+			RebateSource::ProtocolFee
+		));
+	});
+}
+
+#[test]
+fn referrer_claims_its_share_of_the_protocol_fees() {
+	new_test_ext().execute_with(|| {
+		setup_market();
+		assert_ok!(BoTradingModule::set_protocol_fees(Origin::root(), 100, 1_000));
+		assert_ok!(BoTradingModule::set_referral_program(
+			Origin::root(),
+			vec![Permill::from_percent(50)],
+			RebateSource::ProtocolFee
+		));
+		assert_ok!(BoTradingModule::set_referrer(Origin::signed(ALICE), BOB));
+		let order_id = place(ALICE, TradeType::Put, STAKE);
+
+		set_now(next_expiry());
+		feed_price(OPEN_PRICE + 100);
+		assert_ok!(BoTradingModule::settle_expired(Origin::signed(LP_ADMIN), vec![order_id]));
+
+		// half of the 10 + 100 protocol fees, the stake fee share accrued when placing
+		assert_eq!(BoTradingModule::referral_rebates(BOB, USDT), 55);
+		assert_ok!(BoTradingModule::claim_referral_rebates(Origin::signed(BOB), USDT));
+		assert_eq!(Assets::balance(USDT, BOB), INITIAL_USDT + 55);
+		// minus the keeper reward and the rebate
+		assert_eq!(Assets::balance(USDT, BoTradingModule::treasury_account_id()), 110 - 11 - 55);
+		assert_noop!(
+			BoTradingModule::claim_referral_rebates(Origin::signed(BOB), USDT),
+			Error::<Test>::NoReferralRebate
+		);
+	});
+}
+
+#[test]
+fn migrates_the_v0_orders_to_v1() {
+	new_test_ext().execute_with(|| {
+		setup_pair();
+		StorageVersion::new(0).put::<BoTradingModule>();
+		// version 0 `Order`: a `CurrencyPair` index instead of the pair, no asset nor fee
+		// the pool of the orders is LP_ADMIN, which holds their native stakes
+		let put_old_order = |id: H256, currency_pair: u8, status: OrderStatus| {
+			let old_order = (
+				(id, ALICE, currency_pair, TradeType::Put, STAKE, next_expiry(), START_TS),
+				(LP_ADMIN, 90u32, OPEN_PRICE, None::<SymbolPrice>, status),
+			);
+			unhashed::put_raw(&<Orders<Test>>::hashed_key_for(id), &old_order.encode());
+		};
+		let btc_usdt_order = H256::repeat_byte(1);
+		let dot_usdc_order = H256::repeat_byte(2);
+		let settled_order = H256::repeat_byte(3);
+		put_old_order(btc_usdt_order, 0, OrderStatus::Created);
+		put_old_order(dot_usdc_order, 1, OrderStatus::Created);
+		put_old_order(settled_order, 0, OrderStatus::Lose);
+		let native_of_alice = Balances::free_balance(ALICE);
+		let native_of_pool = Balances::free_balance(LP_ADMIN);
+
+		migrations::v1::MigrateToV1::<Test, ConstU32<USDT>, Balances>::on_runtime_upgrade();
+
+		assert_eq!(BoTradingModule::on_chain_storage_version(), 1);
+		let order = BoTradingModule::orders(btc_usdt_order).unwrap();
+		assert_eq!(order.user_id, ALICE);
+		assert_eq!(order.pair_id, PAIR);
+		assert_eq!(order.trade_type, TradeType::Put);
+		assert_eq!(order.asset_id, USDT);
+		assert_eq!(order.volume_in_unit, STAKE);
+		assert_eq!(order.pool_volume, STAKE);
+		assert_eq!(order.protocol_fee, 0);
+		assert_eq!(order.expired_at, next_expiry());
+		assert_eq!(order.liquidity_pool_id, LP_ADMIN);
+		assert_eq!(order.open_price, OPEN_PRICE);
+
+		// the open orders are refunded in the native currency they were staked in
+		assert_eq!(order.status, OrderStatus::Refunded);
+		assert_eq!(BoTradingModule::orders(dot_usdc_order).unwrap().status, OrderStatus::Refunded);
+		assert_eq!(BoTradingModule::orders(settled_order).unwrap().status, OrderStatus::Lose);
+		assert_eq!(Balances::free_balance(ALICE), native_of_alice + 2 * STAKE);
+		assert_eq!(Balances::free_balance(LP_ADMIN), native_of_pool - 2 * STAKE);
+		assert_eq!(Assets::balance(USDT, ALICE), INITIAL_USDT);
+
+		// DOT_USDC is not registered: it is registered disabled
+		assert_eq!(BoTradingModule::orders(dot_usdc_order).unwrap().pair_id, PAIR + 1);
+		let pair = BoTradingModule::pairs(PAIR + 1).unwrap();
+		assert_eq!(pair.oracle_symbol, b"DOT_USDC".to_vec());
+		assert_eq!(pair.asset_id, USDT);
+		assert!(!pair.enabled);
+	});
+}
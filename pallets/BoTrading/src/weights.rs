@@ -0,0 +1,215 @@
+//! Weights for pallet_bo_trading
+//!
+//! HAND-WRITTEN PLACEHOLDER: these weights were not measured, they are estimated from the
+//! storage accesses of each call. They must be regenerated on the reference hardware with
+//! `scripts/benchmark.sh` before a production release, which runs:
+
+// ./target/release/node-template
+// benchmark
+// --chain=dev
+// --execution=wasm
+// --wasm-execution=compiled
+// --pallet=pallet_bo_trading
+// --extrinsic=*
+// --steps=50
+// --repeat=20
+// --output=./pallets/BoTrading/src/weights.rs
+// --template=./.maintain/frame-weight-template.hbs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_bo_trading.
+pub trait WeightInfo {
+	fn place_order(p: u32, u: u32, ) -> Weight;
+	fn close_order(u: u32, ) -> Weight;
+	fn copy_order(f: u32, ) -> Weight;
+	fn on_new_price(b: u32, c: u32, t: u32, e: u32, ) -> Weight;
+}
+
+/// Weights for pallet_bo_trading using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: BoTradingModule Pairs (r:1 w:0)
+	// Storage: BoTradingModule GlobalPause (r:1 w:0)
+	// Storage: BoTradingModule PairPauses (r:1 w:0)
+	// Storage: BoTradingModule AccountRisks (r:1 w:1)
+	// Storage: BoTradingModule SelfLimits (r:1 w:0)
+	// Storage: BoTradingModule OperatorLimits (r:1 w:0)
+	// Storage: BoTradingModule ProtocolFees (r:1 w:0)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: BoTradingModule OrderCount (r:1 w:1)
+	// Storage: BoLiquidityModule LpCount (r:1 w:0)
+	// Storage: BoLiquidityModule LpRandomIndex (r:1 w:1)
+	// Storage: BoLiquidityModule LiquidityPoolsIndex (r:1 w:0)
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	// Storage: SymbolPriceModule NextPredictedPrice (r:1 w:0)
+	// Storage: BoTradingModule PendingExpiries (r:1 w:1)
+	// Storage: BoLiquidityModule OpenLiability (r:1 w:1)
+	// Storage: BoTradingModule Referral (r:1 w:0)
+	// Storage: BoTradingModule UserOrders (r:0 w:1)
+	// Storage: BoTradingModule Orders (r:0 w:1)
+	fn place_order(p: u32, u: u32, ) -> Weight {
+		(84_000_000 as Weight)
+			.saturating_add((1_450_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(u as Weight))
+			.saturating_add(T::DbWeight::get().reads(18 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(T::DbWeight::get().writes(10 as Weight))
+	}
+	// Storage: BoTradingModule Orders (r:1 w:1)
+	// Storage: BoTradingModule GlobalPause (r:1 w:0)
+	// Storage: BoTradingModule PairPauses (r:1 w:0)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	// Storage: BoTradingModule Pairs (r:1 w:0)
+	// Storage: BoTradingModule SettlementPrices (r:1 w:0)
+	// Storage: BoLiquidityModule OpenLiability (r:1 w:1)
+	// Storage: BoTradingModule AccountRisks (r:1 w:1)
+	// Storage: BoTradingModule CopiedOrders (r:1 w:0)
+	// Storage: BoTradingModule AccountStats (r:1 w:1)
+	// Storage: BoTradingModule PairStats (r:1 w:1)
+	// Storage: BoTradingModule PeriodStats (r:1 w:1)
+	// Storage: BoTradingModule Leaderboards (r:1 w:1)
+	// Storage: BoTradingModule ProtocolFees (r:1 w:0)
+	// Storage: BoTradingModule Referral (r:1 w:0)
+	// Storage: BoTradingModule NextUnsignedAt (r:0 w:1)
+	fn close_order(u: u32, ) -> Weight {
+		(97_000_000 as Weight)
+			.saturating_add((3_000 as Weight).saturating_mul(u as Weight))
+			.saturating_add(T::DbWeight::get().reads(18 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
+	}
+	// Storage: BoTradingModule Followers (r:1 w:0)
+	// Storage: BoTradingModule CopySubscriptions (r:1 w:1)
+	// Storage: BoTradingModule CopiedOrders (r:0 w:1)
+	// Storage: the storage of place_order for each copy, its pools excluded
+	fn copy_order(f: u32, ) -> Weight {
+		(2_000_000 as Weight)
+			.saturating_add((90_000_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((18 as Weight).saturating_mul(f as Weight)))
+			.saturating_add(T::DbWeight::get().writes((11 as Weight).saturating_mul(f as Weight)))
+	}
+	// Storage: BoTradingModule PendingExpiries (r:1 w:1)
+	// Storage: BoTradingModule SettlementPrices (r:0 w:1)
+	// Storage: BoTradingModule BarrierOrders (r:1 w:1)
+	// Storage: BoTradingModule Orders (r:1 w:1)
+	// Storage: BoTradingModule Pairs (r:1 w:0)
+	// Storage: BoTradingModule PendingConditionalOrders (r:1 w:1)
+	// Storage: BoTradingModule ConditionalOrders (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: the storage of place_order for each triggered order, its pools excluded
+	fn on_new_price(b: u32, c: u32, t: u32, e: u32, ) -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add((3_000_000 as Weight).saturating_mul(b as Weight))
+			.saturating_add((4_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((95_000_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add((2_000_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(b as Weight)))
+			.saturating_add(T::DbWeight::get().reads((4 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(T::DbWeight::get().reads((22 as Weight).saturating_mul(t as Weight)))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(e as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(b as Weight)))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(T::DbWeight::get().writes((12 as Weight).saturating_mul(t as Weight)))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(e as Weight)))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: BoTradingModule Pairs (r:1 w:0)
+	// Storage: BoTradingModule GlobalPause (r:1 w:0)
+	// Storage: BoTradingModule PairPauses (r:1 w:0)
+	// Storage: BoTradingModule AccountRisks (r:1 w:1)
+	// Storage: BoTradingModule SelfLimits (r:1 w:0)
+	// Storage: BoTradingModule OperatorLimits (r:1 w:0)
+	// Storage: BoTradingModule ProtocolFees (r:1 w:0)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: BoTradingModule OrderCount (r:1 w:1)
+	// Storage: BoLiquidityModule LpCount (r:1 w:0)
+	// Storage: BoLiquidityModule LpRandomIndex (r:1 w:1)
+	// Storage: BoLiquidityModule LiquidityPoolsIndex (r:1 w:0)
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	// Storage: SymbolPriceModule NextPredictedPrice (r:1 w:0)
+	// Storage: BoTradingModule PendingExpiries (r:1 w:1)
+	// Storage: BoLiquidityModule OpenLiability (r:1 w:1)
+	// Storage: BoTradingModule Referral (r:1 w:0)
+	// Storage: BoTradingModule UserOrders (r:0 w:1)
+	// Storage: BoTradingModule Orders (r:0 w:1)
+	fn place_order(p: u32, u: u32, ) -> Weight {
+		(84_000_000 as Weight)
+			.saturating_add((1_450_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(u as Weight))
+			.saturating_add(RocksDbWeight::get().reads(18 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(10 as Weight))
+	}
+	// Storage: BoTradingModule Orders (r:1 w:1)
+	// Storage: BoTradingModule GlobalPause (r:1 w:0)
+	// Storage: BoTradingModule PairPauses (r:1 w:0)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: BoLiquidityModule LiquidityPools (r:1 w:1)
+	// Storage: BoTradingModule Pairs (r:1 w:0)
+	// Storage: BoTradingModule SettlementPrices (r:1 w:0)
+	// Storage: BoLiquidityModule OpenLiability (r:1 w:1)
+	// Storage: BoTradingModule AccountRisks (r:1 w:1)
+	// Storage: BoTradingModule CopiedOrders (r:1 w:0)
+	// Storage: BoTradingModule AccountStats (r:1 w:1)
+	// Storage: BoTradingModule PairStats (r:1 w:1)
+	// Storage: BoTradingModule PeriodStats (r:1 w:1)
+	// Storage: BoTradingModule Leaderboards (r:1 w:1)
+	// Storage: BoTradingModule ProtocolFees (r:1 w:0)
+	// Storage: BoTradingModule Referral (r:1 w:0)
+	// Storage: BoTradingModule NextUnsignedAt (r:0 w:1)
+	fn close_order(u: u32, ) -> Weight {
+		(97_000_000 as Weight)
+			.saturating_add((3_000 as Weight).saturating_mul(u as Weight))
+			.saturating_add(RocksDbWeight::get().reads(18 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
+	}
+	// Storage: BoTradingModule Followers (r:1 w:0)
+	// Storage: BoTradingModule CopySubscriptions (r:1 w:1)
+	// Storage: BoTradingModule CopiedOrders (r:0 w:1)
+	// Storage: the storage of place_order for each copy, its pools excluded
+	fn copy_order(f: u32, ) -> Weight {
+		(2_000_000 as Weight)
+			.saturating_add((90_000_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((18 as Weight).saturating_mul(f as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((11 as Weight).saturating_mul(f as Weight)))
+	}
+	// Storage: BoTradingModule PendingExpiries (r:1 w:1)
+	// Storage: BoTradingModule SettlementPrices (r:0 w:1)
+	// Storage: BoTradingModule BarrierOrders (r:1 w:1)
+	// Storage: BoTradingModule Orders (r:1 w:1)
+	// Storage: BoTradingModule Pairs (r:1 w:0)
+	// Storage: BoTradingModule PendingConditionalOrders (r:1 w:1)
+	// Storage: BoTradingModule ConditionalOrders (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	// Storage: the storage of place_order for each triggered order, its pools excluded
+	fn on_new_price(b: u32, c: u32, t: u32, e: u32, ) -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add((3_000_000 as Weight).saturating_mul(b as Weight))
+			.saturating_add((4_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((95_000_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add((2_000_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(b as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((4 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((22 as Weight).saturating_mul(t as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(e as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(b as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((12 as Weight).saturating_mul(t as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(e as Weight)))
+	}
+}
@@ -1,20 +1,87 @@
-//! Benchmarking setup for pallet-template
+//! Benchmarking setup for pallet-symbol-price
 
 use super::*;
 
 #[allow(unused)]
-use crate::Pallet as Template;
-use frame_benchmarking::{benchmarks, whitelisted_caller};
+use crate::Pallet as SymbolPrice;
+use codec::Decode;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::{traits::EnsureOrigin, BoundedVec};
 use frame_system::RawOrigin;
+use sp_runtime::traits::TrailingZeroInput;
+use sp_std::vec::Vec;
 
+/// The symbol fed by the offchain worker
+const SYMBOL: &[u8] = b"BTC_USDT";
+
+fn symbol<T: Config>() -> SymbolOf<T> {
+	SymbolOf::<T>::try_from(SYMBOL.to_vec()).expect("at most MaxSymbolLength; qed")
+}
+
+/// Store `count` rising prices of the symbol, the EMA of the next price goes through all of them
+fn set_prices<T: Config>(count: u32) {
+	let prices: Vec<u32> = (1..=count).collect();
+	let prices = BoundedVec::<u32, T::MaxPrices>::try_from(prices).expect("at most MaxPrices; qed");
+	Prices::<T>::insert(symbol::<T>(), prices);
+}
+
+/// Make `count` accounts oracles
+fn set_oracles<T: Config>(count: u32) {
+	let oracles: Vec<T::AccountId> = (0..count).map(|i| account("oracle", i, 0)).collect();
+	let oracles = BoundedVec::<T::AccountId, T::MaxOracles>::try_from(oracles)
+		.expect("at most MaxOracles; qed");
+	Oracles::<T>::put(oracles);
+}
+
+// `OnNewPrice` is left out of these benchmarks: the calls add the weight returned by the hook,
+// the hook of the runtime is measured by the `on_new_price` benchmark of pallet-bo-trading
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	submit_price {
+		let s in 0 .. T::MaxPrices::get();
+		let o in 1 .. T::MaxOracles::get();
+		set_prices::<T>(s);
+		// the caller is the last oracle, found after all the others
+		set_oracles::<T>(o - 1);
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		Oracles::<T>::try_append(caller.clone()).expect("below MaxOracles; qed");
+	}: _(RawOrigin::Signed(caller), SYMBOL.to_vec(), s + 1)
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert_eq!(Prices::<T>::get(symbol::<T>()).last(), Some(&(s + 1)));
 	}
 
-	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
+	submit_price_unsigned_with_signed_payload {
+		let s in 0 .. T::MaxPrices::get();
+		set_prices::<T>(s);
+		// the signature is checked by `validate_unsigned`, not by the call
+		let public = T::Public::decode(&mut TrailingZeroInput::new(&[][..]))
+			.expect("decodes from zeroes; qed");
+		let signature = T::Signature::decode(&mut TrailingZeroInput::new(&[][..]))
+			.expect("decodes from zeroes; qed");
+		let price_payload =
+			PricePayload { block_number: 0u32.into(), symbol: SYMBOL.to_vec(), price: s + 1, public };
+	}: _(RawOrigin::None, price_payload, signature)
+	verify {
+		assert_eq!(Prices::<T>::get(symbol::<T>()).last(), Some(&(s + 1)));
+	}
+
+	add_oracle {
+		let o in 0 .. T::MaxOracles::get() - 1;
+		set_oracles::<T>(o);
+		let who: T::AccountId = whitelisted_caller();
+		let origin = T::AdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, who.clone())
+	verify {
+		assert!(Oracles::<T>::get().contains(&who));
+	}
+
+	remove_oracle {
+		let o in 1 .. T::MaxOracles::get();
+		set_oracles::<T>(o);
+		// the last oracle, found after all the others
+		let who: T::AccountId = account("oracle", o - 1, 0);
+		let origin = T::AdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, who.clone())
+	verify {
+		assert!(!Oracles::<T>::get().contains(&who));
+	}
 }
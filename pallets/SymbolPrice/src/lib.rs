@@ -14,6 +14,10 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
+
 
 
 
@@ -72,26 +76,25 @@ pub mod crypto {
 
 #[frame_support::pallet]
 pub mod pallet {
-	use scale_info::prelude::collections::VecDeque;
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
 	use sp_std::vec::Vec;
-	use scale_info::prelude::string::String; // support String
 	use frame_system::{
 		self as system,
 		offchain::{
 			AppCrypto, CreateSignedTransaction, SendSignedTransaction, SendUnsignedTransaction,
-			SignedPayload, Signer, SigningTypes, SubmitTransaction,
+			SignedPayload, Signer, SigningTypes,
 		},
 	};
 	use lite_json::JsonValue;
+	use crate::weights::WeightInfo;
 	use sp_runtime::{
 		offchain::{
 			http,
 			storage::{MutateStorageError, StorageRetrievalError, StorageValueRef},
 			Duration,
 		},
-		traits::Zero,
+		traits::{IdentifyAccount, Zero},
 		transaction_validity::{InvalidTransaction, TransactionValidity, ValidTransaction},
 		RuntimeDebug,
 		SaturatedConversion,
@@ -137,21 +140,49 @@ pub mod pallet {
 		/// Maximum number of prices.
 		#[pallet::constant]
 		type MaxPrices: Get<u32>;
+
+		/// Maximum length of a symbol, eg: BTC_USDT
+		#[pallet::constant]
+		type MaxSymbolLength: Get<u32>;
+
+		/// Maximum number of oracles
+		#[pallet::constant]
+		type MaxOracles: Get<u32>;
+
+		/// Origin allowed to add and remove the oracles
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Handler notified every time a new price is accepted
+		type OnNewPrice: OnNewPrice;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
+	/// The current storage version
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 
 
+	/// The symbol the offchain worker fetches the price of, see `fetch_price`
+	pub const OCW_SYMBOL: &[u8] = b"BTC_USDT";
+
+	/// A symbol of at most `MaxSymbolLength` bytes, the key of the price storages
+	pub type SymbolOf<T> = BoundedVec<u8, <T as Config>::MaxSymbolLength>;
+
 	// Payload used by this example crate to hold price
 	/// data required to submit a transaction.
 	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo)]
 	pub struct PricePayload<Public, BlockNumber> {
-		block_number: BlockNumber,
-		price: u32,
-		public: Public,
+		pub block_number: BlockNumber,
+		pub symbol: Vec<u8>,
+		pub price: u32,
+		pub public: Public,
 	}
 
 	// impl<T: SigningTypes> Encode for PricePayload<frame_system::offchain::Public, frame_system::pallet::BlockNumber> {}
@@ -167,7 +198,6 @@ pub mod pallet {
 		Signed,
 		UnsignedForAny,
 		UnsignedForAll,
-		Raw,
 		None,
 	}
 
@@ -181,20 +211,30 @@ pub mod pallet {
 	// https://docs.substrate.io/v3/runtime/storage#declaring-storage-items
 	pub type Something<T> = StorageValue<_, u32>;
 
-	/// A vector of recently submitted prices.
+	/// The recently submitted prices of each symbol, oldest first.
 	///
-	/// This is used to calculate average price, should have bounded size.
+	/// This is used to calculate average price, at most `MaxPrices` per symbol.
+	/// A `VecDeque<u32>` of BTC_USDT prices until storage version 1, see `migrations::v1`
 	#[pallet::storage]
 	#[pallet::getter(fn prices)]
-	// pub(super) type Prices<T: Config> = StorageValue<_, BoundedVec<u32, T::MaxPrices>, ValueQuery>;
-	pub(super) type Prices<T: Config> = StorageValue<_, VecDeque<u32>, ValueQuery>;
+	pub(super) type Prices<T: Config> =
+		StorageMap<_, Twox64Concat, SymbolOf<T>, BoundedVec<u32, T::MaxPrices>, ValueQuery>;
 
-	/// Predict the next price using EMA
+	/// Predict the next price of each symbol using EMA, and the block it was predicted at
 	/// Why?
 	/// 	We need a realtime approximately price value => this is the best method
+	/// A single BTC_USDT value until storage version 1, see `migrations::v1`
 	#[pallet::storage]
 	#[pallet::getter(fn next_predicted_price)]
-	pub(super) type NextPredictedPrice<T: Config> = StorageValue<_, (u32, T::BlockNumber), ValueQuery>;
+	pub(super) type NextPredictedPrice<T: Config> =
+		StorageMap<_, Twox64Concat, SymbolOf<T>, (u32, T::BlockNumber), OptionQuery>;
+
+	/// Block number of the last accepted price of each symbol
+	/// Used by other pallets to detect a stale oracle
+	#[pallet::storage]
+	#[pallet::getter(fn price_updated_at)]
+	pub(super) type PriceUpdatedAt<T: Config> =
+		StorageMap<_, Twox64Concat, SymbolOf<T>, T::BlockNumber, OptionQuery>;
 
 	/// Defines the block when next unsigned transaction will be accepted.
 	///
@@ -205,6 +245,46 @@ pub mod pallet {
 	#[pallet::getter(fn next_unsigned_at)]
 	pub(super) type NextUnsignedAt<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
 
+	/// Accounts allowed to submit prices, set at genesis and by `T::AdminOrigin`
+	/// No price is accepted while it is empty
+	#[pallet::storage]
+	#[pallet::getter(fn oracles)]
+	pub(super) type Oracles<T: Config> =
+		StorageValue<_, BoundedVec<T::AccountId, T::MaxOracles>, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Accounts allowed to submit prices, eg: the account of the offchain worker key
+		pub oracles: Vec<T::AccountId>,
+		/// Price of each symbol until the oracle submits one, eg: (BTC_USDT, 40000)
+		pub prices: Vec<(Vec<u8>, u32)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { oracles: Vec::new(), prices: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let oracles = BoundedVec::<T::AccountId, T::MaxOracles>::try_from(self.oracles.clone())
+				.expect("at most MaxOracles genesis oracles");
+			<Oracles<T>>::put(oracles);
+			for (symbol, price) in &self.prices {
+				let symbol = SymbolOf::<T>::try_from(symbol.clone())
+					.expect("genesis symbols are at most MaxSymbolLength long");
+				<Prices<T>>::mutate(&symbol, |prices| {
+					let _ = prices.try_push(*price);
+				});
+				<NextPredictedPrice<T>>::insert(&symbol, (*price, T::BlockNumber::zero()));
+				<PriceUpdatedAt<T>>::insert(&symbol, T::BlockNumber::zero());
+			}
+		}
+	}
+
 
 
 	// Pallets use events to inform users when important changes are made.
@@ -217,7 +297,12 @@ pub mod pallet {
 		SomethingStored(u32, T::AccountId),
 
 		/// Event generated when new price is accepted to contribute to the average.
-		NewPrice { price: u32, maybe_who: Option<T::AccountId> },
+		NewPrice { symbol: Vec<u8>, price: u32, maybe_who: Option<T::AccountId> },
+
+		/// An account may submit prices
+		OracleAdded { who: T::AccountId },
+		/// An account may no longer submit prices
+		OracleRemoved { who: T::AccountId },
 	}
 
 	// Errors inform users that something went wrong.
@@ -230,6 +315,14 @@ pub mod pallet {
 
 		/// Symbol was not supported
 		NotSupportedSymbol,
+		/// Sender is not one of the oracles
+		NotOracle,
+		/// The account is already an oracle
+		AlreadyOracle,
+		/// There are already `MaxOracles` oracles
+		TooManyOracles,
+		/// The symbol is longer than `MaxSymbolLength`
+		SymbolTooLong,
 	}
 
 
@@ -254,9 +347,14 @@ pub mod pallet {
 				if !signature_valid {
 					return InvalidTransaction::BadProof.into()
 				}
-				Self::validate_transaction_parameters(&payload.block_number, &payload.price)
-			} else if let Call::submit_price_unsigned { block_number, price: new_price } = call {
-				Self::validate_transaction_parameters(block_number, new_price)
+				if !Self::is_oracle(&payload.public.clone().into_account()) {
+					return InvalidTransaction::BadSigner.into()
+				}
+				let symbol = match SymbolOf::<T>::try_from(payload.symbol.clone()) {
+					Ok(symbol) => symbol,
+					Err(_) => return InvalidTransaction::Call.into(),
+				};
+				Self::validate_transaction_parameters(&payload.block_number, &symbol, &payload.price)
 			} else {
 				InvalidTransaction::Call.into()
 			}
@@ -302,11 +400,10 @@ pub mod pallet {
 			let should_send = Self::choose_transaction_type(block_number);
 			let res = match should_send {
 				// TransactionType::Signed => Self::fetch_price_and_send_signed(),
-				// TransactionType::UnsignedForAny =>
-				// 	Self::fetch_price_and_send_unsigned_for_any_account(block_number),
+				TransactionType::UnsignedForAny =>
+					Self::fetch_price_and_send_unsigned_for_any_account(block_number),
 				// TransactionType::UnsignedForAll =>
 				// 	Self::fetch_price_and_send_unsigned_for_all_accounts(block_number),
-				TransactionType::Raw => Self::fetch_price_and_send_raw_unsigned(block_number),
 				TransactionType::None => Ok(()),
 				_ => Err("Invalid tx type"),
 			};
@@ -380,48 +477,32 @@ pub mod pallet {
 		///
 		/// This example is not focused on correctness of the oracle itself, but rather its
 		/// purpose is to showcase offchain worker capabilities.
-		#[pallet::weight(10)]
-		pub fn submit_price(origin: OriginFor<T>, price: u32) -> DispatchResultWithPostInfo {
-			// Retrieve sender of the transaction.
-			let who = ensure_signed(origin)?;
-			// Add the price to the on-chain list.
-			Self::add_price(Some(who), price);
-			Ok(().into())
-		}
-
-		/// Submit new price to the list via unsigned transaction.
-		///
-		/// Works exactly like the `submit_price` function, but since we allow sending the
-		/// transaction without a signature, and hence without paying any fees,
-		/// we need a way to make sure that only some transactions are accepted.
-		/// This function can be called only once every `T::UnsignedInterval` blocks.
-		/// Transactions that call that function are de-duplicated on the pool level
-		/// via `validate_unsigned` implementation and also are rendered invalid if
-		/// the function has already been called in current "session".
-		///
-		/// It's important to specify `weight` for unsigned calls as well, because even though
-		/// they don't charge fees, we still don't want a single block to contain unlimited
-		/// number of such transactions.
-		///
-		/// This example is not focused on correctness of the oracle itself, but rather its
-		/// purpose is to showcase offchain worker capabilities.
-		#[pallet::weight(10)]
-		pub fn submit_price_unsigned(
+		#[pallet::weight(
+			T::WeightInfo::submit_price(T::MaxPrices::get(), T::MaxOracles::get())
+				.saturating_add(T::OnNewPrice::max_weight())
+		)]
+		pub fn submit_price(
 			origin: OriginFor<T>,
-			_block_number: T::BlockNumber,
+			symbol: Vec<u8>,
 			price: u32,
 		) -> DispatchResultWithPostInfo {
-			// This ensures that the function can only be called via unsigned transaction.
-			ensure_none(origin)?;
-			// Add the price to the on-chain list, but mark it as coming from an empty address.
-			Self::add_price(None, price);
-			// now increment the block number at which we expect next unsigned transaction.
-			let current_block = <system::Pallet<T>>::block_number();
-			<NextUnsignedAt<T>>::put(current_block + T::UnsignedInterval::get());
-			Ok(().into())
+			// Retrieve sender of the transaction.
+			let who = ensure_signed(origin)?;
+			ensure!(Self::is_oracle(&who), Error::<T>::NotOracle);
+			let symbol = SymbolOf::<T>::try_from(symbol).map_err(|_| Error::<T>::SymbolTooLong)?;
+			// Add the price to the on-chain list.
+			let hook_weight = Self::add_price(Some(who), symbol, price);
+			Ok(Some(
+				T::WeightInfo::submit_price(T::MaxPrices::get(), T::MaxOracles::get())
+					.saturating_add(hook_weight),
+			)
+			.into())
 		}
 
-		#[pallet::weight(10)]
+		#[pallet::weight(
+			T::WeightInfo::submit_price_unsigned_with_signed_payload(T::MaxPrices::get())
+				.saturating_add(T::OnNewPrice::max_weight())
+		)]
 		pub fn submit_price_unsigned_with_signed_payload(
 			origin: OriginFor<T>,
 			price_payload: PricePayload<T::Public, T::BlockNumber>,
@@ -429,12 +510,49 @@ pub mod pallet {
 		) -> DispatchResultWithPostInfo {
 			// This ensures that the function can only be called via unsigned transaction.
 			ensure_none(origin)?;
+			let symbol = SymbolOf::<T>::try_from(price_payload.symbol)
+				.map_err(|_| Error::<T>::SymbolTooLong)?;
 			// Add the price to the on-chain list, but mark it as coming from an empty address.
-			Self::add_price(None, price_payload.price);
+			let hook_weight = Self::add_price(None, symbol, price_payload.price);
 			// now increment the block number at which we expect next unsigned transaction.
 			let current_block = <system::Pallet<T>>::block_number();
 			<NextUnsignedAt<T>>::put(current_block + T::UnsignedInterval::get());
-			Ok(().into())
+			Ok(Some(
+				T::WeightInfo::submit_price_unsigned_with_signed_payload(T::MaxPrices::get())
+					.saturating_add(hook_weight),
+			)
+			.into())
+		}
+
+		/// Allow `who` to submit prices
+		#[pallet::weight(T::WeightInfo::add_oracle(T::MaxOracles::get()))]
+		pub fn add_oracle(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			<Oracles<T>>::try_mutate(|oracles| -> DispatchResult {
+				ensure!(!oracles.contains(&who), Error::<T>::AlreadyOracle);
+				oracles.try_push(who.clone()).map_err(|_| Error::<T>::TooManyOracles)?;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::OracleAdded { who });
+			Ok(())
+		}
+
+		/// Stop `who` from submitting prices
+		/// - No price is accepted once the last oracle is removed
+		#[pallet::weight(T::WeightInfo::remove_oracle(T::MaxOracles::get()))]
+		pub fn remove_oracle(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			<Oracles<T>>::try_mutate(|oracles| -> DispatchResult {
+				let index = oracles.iter().position(|o| *o == who).ok_or(Error::<T>::NotOracle)?;
+				oracles.remove(index);
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::OracleRemoved { who });
+			Ok(())
 		}
 	}
 
@@ -505,8 +623,8 @@ pub mod pallet {
 					// 	TransactionType::Raw
 					// }
 
-					// always return 1 type
-					TransactionType::Raw
+					// always return 1 type: the prices are signed by an oracle key of the keystore
+					TransactionType::UnsignedForAny
 				},
 				// We are in the grace period, we should not send a transaction this time.
 				Err(MutateStorageError::ValueFunctionFailed(RECENTLY_SENT)) => TransactionType::None,
@@ -554,38 +672,6 @@ pub mod pallet {
 		}
 		*/
 
-		/// A helper function to fetch the price and send a raw unsigned transaction.
-		fn fetch_price_and_send_raw_unsigned(block_number: T::BlockNumber) -> Result<(), &'static str> {
-			// Make sure we don't fetch the price if unsigned transaction is going to be rejected
-			// anyway.
-			let next_unsigned_at = <NextUnsignedAt<T>>::get();
-			if next_unsigned_at > block_number {
-				return Err("Too early to send unsigned transaction");
-			}
-
-			// Make an external HTTP request to fetch the current price.
-			// Note this call will block until response is received.
-			let price = Self::fetch_price().map_err(|_| "Failed to fetch price")?;
-
-			// Received price is wrapped into a call to `submit_price_unsigned` public function of this
-			// pallet. This means that the transaction, when executed, will simply call that function
-			// passing `price` as an argument.
-			let call = Call::submit_price_unsigned { block_number, price };
-
-			// Now let's create a transaction out of this call and submit it to the pool.
-			// Here we showcase two ways to send an unsigned transaction / unsigned payload (raw)
-			//
-			// By default unsigned transactions are disallowed, so we need to whitelist this case
-			// by writing `UnsignedValidator`. Note that it's EXTREMELY important to carefuly
-			// implement unsigned validation logic, as any mistakes can lead to opening DoS or spam
-			// attack vectors. See validation logic docs for more details.
-			//
-			SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
-				.map_err(|()| "Unable to submit unsigned transaction.")?;
-
-			Ok(())
-		}
-
 		/// A helper function to fetch the price, sign payload and send an unsigned transaction
 		fn fetch_price_and_send_unsigned_for_any_account(
 			block_number: T::BlockNumber,
@@ -604,7 +690,12 @@ pub mod pallet {
 			// -- Sign using any account
 			let (_, result) = Signer::<T, T::AuthorityId>::any_account()
 				.send_unsigned_transaction(
-					|account| PricePayload { price, block_number, public: account.public.clone() },
+					|account| PricePayload {
+						price,
+						symbol: OCW_SYMBOL.to_vec(),
+						block_number,
+						public: account.public.clone(),
+					},
 					|payload, signature| Call::submit_price_unsigned_with_signed_payload {
 						price_payload: payload,
 						signature,
@@ -723,12 +814,20 @@ pub mod pallet {
 				_ => return None,
 			};
 
+			// None instead of overflowing on a price with too many digits
 			let exp = price.fraction_length.checked_sub(2).unwrap_or(0);
-			Some(price.integer as u32 * 100 + (price.fraction / 10_u64.pow(exp)) as u32)
+			let cents = u32::try_from(price.fraction / 10_u64.checked_pow(exp)?).ok()?;
+			u32::try_from(price.integer).ok()?.checked_mul(100)?.checked_add(cents)
 		}
 
-		/// Add new price to the list.
-		fn add_price(maybe_who: Option<T::AccountId>, price: u32) {
+		/// Whether `who` may submit prices, nobody may until oracles are configured
+		fn is_oracle(who: &T::AccountId) -> bool {
+			Self::oracles().contains(who)
+		}
+
+		/// Add new price of a symbol to the list.
+		/// Return the weight used by `T::OnNewPrice`
+		fn add_price(maybe_who: Option<T::AccountId>, symbol: SymbolOf<T>, price: u32) -> Weight {
 			log::info!("Adding to the average: {}", price);
 			// <Prices<T>>::mutate(|prices| {
 			// 	if prices.try_push(price).is_err() {
@@ -736,13 +835,14 @@ pub mod pallet {
 			// 	}
 			// });
 
-			<Prices<T>>::mutate(|prices| {
+			<Prices<T>>::mutate(&symbol, |prices| {
 				// Ensure len is bounded to MaxPrices
-				if prices.len() >= T::MaxPrices::get() as usize {
-					prices.pop_front();
+				if prices.len() >= T::MaxPrices::get() as usize && !prices.is_empty() {
+					prices.remove(0);
 				}
 
-				prices.push_back(price);
+				// can not fail, there is room for one more price
+				let _ = prices.try_push(price);
 			});
 
 
@@ -750,16 +850,26 @@ pub mod pallet {
 			// 	.expect("The average is not empty, because it was just mutated; qed");
 			// log::info!("Current average price is: {}", average);
 
-			let predict_price = Self::calc_ema();
+			let predict_price = Self::calc_ema(&symbol);
 			if predict_price.is_some() {
 				let current_block_number = <frame_system::Pallet<T>>::block_number();
 				log::info!("block@{:?} next predict_price is: {}", current_block_number, predict_price.unwrap());
 
-				<NextPredictedPrice<T>>::put((predict_price.unwrap(), current_block_number));
+				<NextPredictedPrice<T>>::insert(
+					&symbol,
+					(predict_price.unwrap(), current_block_number),
+				);
 			}
 
+			<PriceUpdatedAt<T>>::insert(&symbol, <frame_system::Pallet<T>>::block_number());
+
+			// let other pallets observe every price update
+			let hook_weight = T::OnNewPrice::on_new_price(symbol.to_vec(), price.into());
+
 			// here we are raising the NewPrice event
-			Self::deposit_event(Event::NewPrice { price, maybe_who });
+			Self::deposit_event(Event::NewPrice { symbol: symbol.into_inner(), price, maybe_who });
+
+			hook_weight
 		}
 
 		/// Calculate current average price.
@@ -772,8 +882,8 @@ pub mod pallet {
 		// 	}
 		// }
 
-		fn calc_ema() -> Option<u32> {
-			let prices = <Prices<T>>::get();
+		fn calc_ema(symbol: &SymbolOf<T>) -> Option<u32> {
+			let prices = <Prices<T>>::get(symbol);
 			if prices.len() < 2 {
 				None
 			} else {
@@ -792,8 +902,8 @@ pub mod pallet {
 			}
 		}
 
-		fn calc_price_change_percent(new_price: &u32) -> u32 {
-			let (next_predicted_price, _) = <NextPredictedPrice<T>>::get();
+		fn calc_price_change_percent(symbol: &SymbolOf<T>, new_price: &u32) -> u32 {
+			let (next_predicted_price, _) = <NextPredictedPrice<T>>::get(symbol).unwrap_or_default();
 			if next_predicted_price > 0 {
 				let price_delta = if next_predicted_price > *new_price { next_predicted_price - new_price } else { new_price - next_predicted_price };
 				price_delta * 100 / next_predicted_price
@@ -804,6 +914,7 @@ pub mod pallet {
 
 		fn validate_transaction_parameters(
 			block_number: &T::BlockNumber,
+			symbol: &SymbolOf<T>,
 			new_price: &u32,
 		) -> TransactionValidity {
 			// Now let's check if the transaction has any chance to succeed.
@@ -822,7 +933,7 @@ pub mod pallet {
 			// Note this doesn't make much sense when building an actual oracle, but this example
 			// is here mostly to show off offchain workers capabilities, not about building an
 			// oracle.
-			let price_delta = Self::calc_price_change_percent(new_price);
+			let price_delta = Self::calc_price_change_percent(symbol, new_price);
 
 			ValidTransaction::with_tag_prefix("pallet-symbol-price___ocw")
 				// We set base priority to 2**20 and hope it's included before any other
@@ -875,6 +986,31 @@ pub mod pallet {
 		fn get_price_at(symbol: Vec<u8>, unix_ts: Option<u64>) -> Option<SymbolPrice>;
 		fn get_price(symbol: Vec<u8>) -> Option<SymbolPrice>;
 		fn fetch_live_price(symbol: Vec<u8>) -> Option<SymbolPrice>;
+		/// Block number of the last accepted price of a symbol
+		/// Return None if no price was ever set
+		fn price_updated_at(symbol: Vec<u8>) -> Option<u64>;
+
+		/// Make `price` the current price of a symbol, for the benchmarks of the pallets
+		/// reading prices
+		#[cfg(feature = "runtime-benchmarks")]
+		fn set_price(symbol: Vec<u8>, price: SymbolPrice);
+	}
+
+	/// Hook for other pallets to observe every accepted price, eg: barrier options
+	pub trait OnNewPrice {
+		/// Return the weight consumed
+		fn on_new_price(symbol: Vec<u8>, price: SymbolPrice) -> Weight;
+		/// Upper bound of `on_new_price`, charged upfront by the price calls
+		fn max_weight() -> Weight;
+	}
+
+	impl OnNewPrice for () {
+		fn on_new_price(_symbol: Vec<u8>, _price: SymbolPrice) -> Weight {
+			0
+		}
+		fn max_weight() -> Weight {
+			0
+		}
 	}
 
 	// impl<T: Config> BoLiquidityInterface for Module<T> {
@@ -884,31 +1020,16 @@ pub mod pallet {
 			None
 		}
 		fn get_price(symbol: Vec<u8>) -> Option<SymbolPrice> {
-			let btc_usdt = String::from("BTC_USDT").into_bytes();
-
-			match symbol {
-				btc_usdt => {
-					let (next_predicted_price, predicted_at) = <NextPredictedPrice<T>>::get();
-					let current_block_number = <frame_system::Pallet<T>>::block_number();
-					if current_block_number > predicted_at {
-						// New block: return predict data
-						Some(next_predicted_price.into())
-					} else {
-						// Old block: Return current price
-						let prices = <Prices<T>>::get();
-						let p = prices.back();
-						if p.is_some() {
-							Some((p.unwrap().clone() + 0u32).into())
-							// Some(0u32.into())
-						} else {
-							None
-						}
-					}
-				},
-				_ => {
-					log::error!("InvalidTradingVolume: We support BTC_USDT only");
-					None
-				}
+			// a symbol longer than `MaxSymbolLength` has no price
+			let symbol = SymbolOf::<T>::try_from(symbol).ok()?;
+			let current_block_number = <frame_system::Pallet<T>>::block_number();
+			match <NextPredictedPrice<T>>::get(&symbol) {
+				// New block: return predict data
+				Some((next_predicted_price, predicted_at))
+					if current_block_number > predicted_at =>
+					Some(next_predicted_price.into()),
+				// Old block or no prediction yet: Return current price, None for an unknown symbol
+				_ => <Prices<T>>::get(&symbol).last().map(|price| (*price).into()),
 			}
 		}
 
@@ -924,6 +1045,27 @@ pub mod pallet {
 				Some(price.into())
 			}
 		}
+
+		fn price_updated_at(symbol: Vec<u8>) -> Option<u64> {
+			<PriceUpdatedAt<T>>::get(SymbolOf::<T>::try_from(symbol).ok()?)
+				.map(|block_number| block_number.saturated_into::<u64>())
+		}
+
+		#[cfg(feature = "runtime-benchmarks")]
+		fn set_price(symbol: Vec<u8>, price: SymbolPrice) {
+			let price: u32 = price.saturated_into();
+			let symbol = SymbolOf::<T>::try_from(symbol).expect("at most MaxSymbolLength");
+			let current_block_number = <frame_system::Pallet<T>>::block_number();
+			<Prices<T>>::mutate(&symbol, |prices| {
+				if prices.len() >= T::MaxPrices::get() as usize && !prices.is_empty() {
+					prices.remove(0);
+				}
+				let _ = prices.try_push(price);
+			});
+			// the latest price is the current price until the next block
+			<NextPredictedPrice<T>>::insert(&symbol, (price, current_block_number));
+			<PriceUpdatedAt<T>>::insert(symbol, current_block_number);
+		}
 	}
 	// End loosely coupling
 }
@@ -0,0 +1,101 @@
+//! Storage migrations of the SymbolPrice pallet
+
+/// Version 1: `Prices` is a bounded list per symbol, `NextPredictedPrice` is per symbol
+///
+/// Version 0 kept a single `VecDeque<u32>` of BTC_USDT prices in a `StorageValue`,
+/// it becomes the BTC_USDT entry of the `Prices` map, keeping the latest `MaxPrices` prices.
+/// The BTC_USDT predicted price moves the same way
+pub mod v1 {
+	use crate::{Config, NextPredictedPrice, Pallet, Prices, SymbolOf};
+	use frame_support::{
+		pallet_prelude::*,
+		storage::migration,
+		traits::{GetStorageVersion, OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+		weights::Weight,
+	};
+	use scale_info::prelude::collections::VecDeque;
+	use sp_std::{marker::PhantomData, vec::Vec};
+
+	#[cfg(feature = "try-runtime")]
+	use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+
+	/// The only symbol fed before version 1
+	pub const LEGACY_SYMBOL: &[u8] = b"BTC_USDT";
+
+	/// The legacy symbol as a storage key
+	fn legacy_symbol<T: Config>() -> SymbolOf<T> {
+		// can not fail for a `MaxSymbolLength` of at least 8
+		SymbolOf::<T>::try_from(LEGACY_SYMBOL.to_vec()).unwrap_or_default()
+	}
+
+	/// The version 0 `Prices` value, stored at the prefix of the version 1 `Prices` map
+	fn take_old_prices<T: Config>() -> Option<VecDeque<u32>> {
+		migration::take_storage_value::<VecDeque<u32>>(
+			<Pallet<T>>::name().as_bytes(),
+			b"Prices",
+			&[],
+		)
+	}
+
+	/// The version 0 `NextPredictedPrice` value, stored at the prefix of the version 1 map
+	fn take_old_predicted_price<T: Config>() -> Option<(u32, T::BlockNumber)> {
+		migration::take_storage_value::<(u32, T::BlockNumber)>(
+			<Pallet<T>>::name().as_bytes(),
+			b"NextPredictedPrice",
+			&[],
+		)
+	}
+
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let on_chain_version = <Pallet<T>>::on_chain_storage_version();
+			if on_chain_version != 0 {
+				log::info!("SymbolPrice: skip migration to v1, on chain {:?}", on_chain_version);
+				return T::DbWeight::get().reads(1)
+			}
+
+			if let Some(old_prices) = take_old_prices::<T>() {
+				let skip = old_prices.len().saturating_sub(T::MaxPrices::get() as usize);
+				let prices: Vec<u32> = old_prices.into_iter().skip(skip).collect();
+				// can not fail, at most `MaxPrices` prices are kept
+				let prices = BoundedVec::<u32, T::MaxPrices>::try_from(prices).unwrap_or_default();
+				<Prices<T>>::insert(legacy_symbol::<T>(), prices);
+			}
+			if let Some(predicted_price) = take_old_predicted_price::<T>() {
+				<NextPredictedPrice<T>>::insert(legacy_symbol::<T>(), predicted_price);
+			}
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			log::info!("SymbolPrice: migrated to v1");
+			T::DbWeight::get().reads_writes(3, 5)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			let old_len = migration::get_storage_value::<VecDeque<u32>>(
+				<Pallet<T>>::name().as_bytes(),
+				b"Prices",
+				&[],
+			)
+			.map(|prices| prices.len() as u32)
+			.unwrap_or_default();
+			Self::set_temp_storage(old_len, "old_prices_len");
+
+			Ok(())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			ensure!(<Pallet<T>>::on_chain_storage_version() == 1, "SymbolPrice: not v1");
+
+			let old_len: u32 =
+				Self::get_temp_storage("old_prices_len").ok_or("SymbolPrice: no pre upgrade")?;
+			let new_len = <Prices<T>>::get(legacy_symbol::<T>()).len() as u32;
+			ensure!(new_len == old_len.min(T::MaxPrices::get()), "SymbolPrice: prices lost");
+
+			Ok(())
+		}
+	}
+}
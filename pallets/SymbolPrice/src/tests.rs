@@ -19,20 +19,25 @@ use crate as pallet_symbol_price;
 use crate::*;
 use codec::Decode;
 use frame_support::{
-	assert_ok, parameter_types,
-	traits::{ConstU32, ConstU64},
+	assert_noop, assert_ok, parameter_types,
+	storage::migration,
+	unsigned::ValidateUnsigned,
+	traits::{
+		ConstU32, ConstU64, GenesisBuild, GetStorageVersion, OnRuntimeUpgrade, StorageVersion,
+	},
 };
 use sp_core::{
 	offchain::{testing, OffchainWorkerExt, TransactionPoolExt},
 	sr25519::Signature,
 	H256,
 };
-use std::sync::Arc;
+use std::{collections::VecDeque, sync::Arc};
 
 use sp_keystore::{testing::KeyStore, KeystoreExt, SyncCryptoStore};
 use sp_runtime::{
 	testing::{Header, TestXt},
 	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentifyAccount, IdentityLookup, Verify},
+	transaction_validity::{InvalidTransaction, TransactionSource},
 	RuntimeAppPublic,
 };
 
@@ -124,25 +129,195 @@ impl Config for Test {
 	type UnsignedInterval = ConstU64<128>;
 	type UnsignedPriority = UnsignedPriority;
 	type MaxPrices = ConstU32<64>;
+	type MaxSymbolLength = ConstU32<16>;
+	type MaxOracles = ConstU32<4>;
+	type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
+	type OnNewPrice = ();
+	type WeightInfo = ();
 }
 
+/// The symbol fed by the offchain worker
+const BTC_USDT: &[u8] = b"BTC_USDT";
+
 fn test_pub() -> sp_core::sr25519::Public {
 	sp_core::sr25519::Public::from_raw([1u8; 32])
 }
 
+fn symbol(symbol: &[u8]) -> SymbolOf<Test> {
+	SymbolOf::<Test>::try_from(symbol.to_vec()).unwrap()
+}
+
+/// `test_pub` is the only oracle
+fn new_test_ext() -> sp_io::TestExternalities {
+	let config =
+		pallet_symbol_price::GenesisConfig::<Test> { oracles: vec![test_pub()], prices: vec![] };
+	sp_io::TestExternalities::new(GenesisBuild::<Test>::build_storage(&config).unwrap())
+}
+
 #[test]
 fn it_aggregates_the_price() {
-	sp_io::TestExternalities::default().execute_with(|| {
+	new_test_ext().execute_with(|| {
 		assert_eq!(Example::average_price(), None);
 
-		assert_ok!(Example::submit_price(Origin::signed(test_pub()), 27));
+		assert_ok!(Example::submit_price(Origin::signed(test_pub()), BTC_USDT.to_vec(), 27));
 		assert_eq!(Example::average_price(), Some(27));
 
-		assert_ok!(Example::submit_price(Origin::signed(test_pub()), 43));
+		assert_ok!(Example::submit_price(Origin::signed(test_pub()), BTC_USDT.to_vec(), 43));
 		assert_eq!(Example::average_price(), Some(35));
 	});
 }
 
+#[test]
+fn genesis_sets_the_prices_and_the_oracles() {
+	let oracle = sp_core::sr25519::Public::from_raw([2u8; 32]);
+	let config = pallet_symbol_price::GenesisConfig::<Test> {
+		oracles: vec![oracle],
+		prices: vec![(b"BTC_USDT".to_vec(), 40_000), (b"ETH_USDT".to_vec(), 3_000)],
+	};
+	let storage = GenesisBuild::<Test>::build_storage(&config).unwrap();
+
+	sp_io::TestExternalities::new(storage).execute_with(|| {
+		assert_eq!(Example::prices(symbol(b"BTC_USDT")).into_inner(), vec![40_000]);
+		assert_eq!(Example::prices(symbol(b"ETH_USDT")).into_inner(), vec![3_000]);
+		assert_eq!(Example::next_predicted_price(symbol(b"BTC_USDT")), Some((40_000, 0)));
+		assert_eq!(Example::next_predicted_price(symbol(b"ETH_USDT")), Some((3_000, 0)));
+		assert_eq!(Example::price_updated_at(symbol(b"BTC_USDT")), Some(0));
+		assert_eq!(Example::price_updated_at(symbol(b"ETH_USDT")), Some(0));
+
+		// only the oracles may submit prices
+		assert_noop!(
+			Example::submit_price(Origin::signed(test_pub()), BTC_USDT.to_vec(), 27),
+			Error::<Test>::NotOracle
+		);
+		assert_ok!(Example::submit_price(Origin::signed(oracle), BTC_USDT.to_vec(), 27));
+	});
+}
+
+#[test]
+fn refuses_prices_while_there_is_no_oracle() {
+	let keystore = KeyStore::new();
+	let public_key =
+		SyncCryptoStore::sr25519_generate_new(&keystore, crate::crypto::Public::ID, None).unwrap();
+	let mut t = sp_io::TestExternalities::default();
+	t.register_extension(KeystoreExt(Arc::new(keystore)));
+
+	t.execute_with(|| {
+		assert_noop!(
+			Example::submit_price(Origin::signed(test_pub()), BTC_USDT.to_vec(), 27),
+			Error::<Test>::NotOracle
+		);
+
+		// a valid signature of a key which is not an oracle
+		let price_payload = PricePayload {
+			block_number: 0,
+			symbol: BTC_USDT.to_vec(),
+			price: 27,
+			public: <Test as SigningTypes>::Public::from(public_key),
+		};
+		let signature = price_payload.sign::<crypto::TestAuthId>().unwrap();
+		let call = crate::Call::submit_price_unsigned_with_signed_payload {
+			price_payload,
+			signature,
+		};
+		assert_eq!(
+			<Example as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::BadSigner.into(),
+		);
+	});
+}
+
+#[test]
+fn admin_manages_the_oracles() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		assert_noop!(
+			Example::add_oracle(Origin::signed(test_pub()), test_pub()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Example::add_oracle(Origin::root(), test_pub()));
+		assert_noop!(
+			Example::add_oracle(Origin::root(), test_pub()),
+			Error::<Test>::AlreadyOracle
+		);
+		assert_ok!(Example::submit_price(Origin::signed(test_pub()), BTC_USDT.to_vec(), 27));
+
+		assert_ok!(Example::remove_oracle(Origin::root(), test_pub()));
+		assert_noop!(
+			Example::submit_price(Origin::signed(test_pub()), BTC_USDT.to_vec(), 28),
+			Error::<Test>::NotOracle
+		);
+		assert_noop!(
+			Example::remove_oracle(Origin::root(), test_pub()),
+			Error::<Test>::NotOracle
+		);
+	});
+}
+
+#[test]
+fn refuses_symbols_longer_than_the_max_length() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Example::submit_price(Origin::signed(test_pub()), vec![b'A'; 17], 27),
+			Error::<Test>::SymbolTooLong
+		);
+		assert_eq!(Example::get_price(vec![b'A'; 17]), None);
+	});
+}
+
+#[test]
+fn tracks_the_price_update_of_each_symbol() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Example::submit_price(Origin::signed(test_pub()), BTC_USDT.to_vec(), 27));
+		System::set_block_number(5);
+		assert_ok!(Example::submit_price(Origin::signed(test_pub()), b"ETH_USDT".to_vec(), 3));
+
+		assert_eq!(Example::price_updated_at(symbol(BTC_USDT)), Some(1));
+		assert_eq!(Example::price_updated_at(symbol(b"ETH_USDT")), Some(5));
+		assert_eq!(Example::price_updated_at(symbol(b"DOT_USDT")), None);
+	});
+}
+
+#[test]
+fn quotes_each_symbol_at_its_own_price() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		for price in [40_000, 40_100, 40_200] {
+			assert_ok!(Example::submit_price(Origin::signed(test_pub()), BTC_USDT.to_vec(), price));
+		}
+		assert_ok!(Example::submit_price(Origin::signed(test_pub()), b"ETH_USDT".to_vec(), 3_000));
+
+		assert_eq!(Example::get_price(BTC_USDT.to_vec()), Some(40_200));
+		assert_eq!(Example::get_price(b"ETH_USDT".to_vec()), Some(3_000));
+		assert_eq!(Example::get_price(b"DOT_USDT".to_vec()), None);
+		assert!(Example::next_predicted_price(symbol(BTC_USDT)).is_some());
+		assert!(Example::next_predicted_price(symbol(b"ETH_USDT")).is_none());
+	});
+}
+
+#[test]
+fn migrates_the_prices_to_v1() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let old_prices: VecDeque<u32> = (1..=70).collect();
+		migration::put_storage_value(b"Example", b"Prices", &[], old_prices);
+		migration::put_storage_value(b"Example", b"NextPredictedPrice", &[], (69u32, 3u64));
+		StorageVersion::new(0).put::<Example>();
+
+		migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(Example::on_chain_storage_version(), 1);
+		// the latest MaxPrices prices are kept
+		let prices = Example::prices(symbol(b"BTC_USDT"));
+		assert_eq!(prices.len(), 64);
+		assert_eq!(prices.first(), Some(&7));
+		assert_eq!(prices.last(), Some(&70));
+		assert_eq!(Example::next_predicted_price(symbol(b"BTC_USDT")), Some((69, 3)));
+
+		// a second run is a no-op
+		migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(Example::prices(symbol(b"BTC_USDT")).len(), 64);
+	});
+}
+
 #[test]
 fn should_make_http_call_and_parse_result() {
 	let (offchain, state) = testing::TestOffchainExt::new();
@@ -233,7 +408,10 @@ fn should_submit_signed_transaction_on_chain() {
 		assert!(pool_state.read().transactions.is_empty());
 		let tx = Extrinsic::decode(&mut &*tx).unwrap();
 		assert_eq!(tx.signature.unwrap().0, 0);
-		assert_eq!(tx.call, Call::Example(crate::Call::submit_price { price: 15523 }));
+		assert_eq!(
+			tx.call,
+			Call::Example(crate::Call::submit_price { symbol: BTC_USDT.to_vec(), price: 15523 })
+		);
 	});
 }
 
@@ -267,6 +445,7 @@ fn should_submit_unsigned_transaction_on_chain_for_any_account() {
 
 	let price_payload = PricePayload {
 		block_number: 1,
+		symbol: BTC_USDT.to_vec(),
 		price: 15523,
 		public: <Test as SigningTypes>::Public::from(public_key),
 	};
@@ -327,6 +506,7 @@ fn should_submit_unsigned_transaction_on_chain_for_all_accounts() {
 
 	let price_payload = PricePayload {
 		block_number: 1,
+		symbol: BTC_USDT.to_vec(),
 		price: 15523,
 		public: <Test as SigningTypes>::Public::from(public_key),
 	};
@@ -357,35 +537,6 @@ fn should_submit_unsigned_transaction_on_chain_for_all_accounts() {
 	});
 }
 
-#[test]
-fn should_submit_raw_unsigned_transaction_on_chain() {
-	let (offchain, offchain_state) = testing::TestOffchainExt::new();
-	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
-
-	let keystore = KeyStore::new();
-
-	let mut t = sp_io::TestExternalities::default();
-	t.register_extension(OffchainWorkerExt::new(offchain));
-	t.register_extension(TransactionPoolExt::new(pool));
-	t.register_extension(KeystoreExt(Arc::new(keystore)));
-
-	price_oracle_response(&mut offchain_state.write());
-
-	t.execute_with(|| {
-		// when
-		Example::fetch_price_and_send_raw_unsigned(1).unwrap();
-		// then
-		let tx = pool_state.write().transactions.pop().unwrap();
-		assert!(pool_state.read().transactions.is_empty());
-		let tx = Extrinsic::decode(&mut &*tx).unwrap();
-		assert_eq!(tx.signature, None);
-		assert_eq!(
-			tx.call,
-			Call::Example(crate::Call::submit_price_unsigned { block_number: 1, price: 15523 })
-		);
-	});
-}
-
 fn price_oracle_response(state: &mut testing::OffchainState) {
 	state.expect_request(testing::PendingRequest {
 		method: "GET".into(),
@@ -405,6 +556,8 @@ fn parse_price_works() {
 		("{\"USD\":6536}", Some(653600)),
 		("{\"USD2\":6536}", None),
 		("{\"USD\":\"6432\"}", None),
+		// more than u32::MAX cents
+		("{\"USD\":42949673}", None),
 	];
 
 	for (json, expected) in test_data {
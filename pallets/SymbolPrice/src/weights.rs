@@ -0,0 +1,111 @@
+//! Weights for pallet_symbol_price
+//!
+//! HAND-WRITTEN PLACEHOLDER: these weights were not measured, they are estimated from the
+//! storage accesses of each call. They must be regenerated on the reference hardware with
+//! `scripts/benchmark.sh` before a production release, which runs:
+
+// ./target/release/node-template
+// benchmark
+// --chain=dev
+// --execution=wasm
+// --wasm-execution=compiled
+// --pallet=pallet_symbol_price
+// --extrinsic=*
+// --steps=50
+// --repeat=20
+// --output=./pallets/SymbolPrice/src/weights.rs
+// --template=./.maintain/frame-weight-template.hbs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_symbol_price.
+pub trait WeightInfo {
+	fn submit_price(s: u32, o: u32, ) -> Weight;
+	fn submit_price_unsigned_with_signed_payload(s: u32, ) -> Weight;
+	fn add_oracle(o: u32, ) -> Weight;
+	fn remove_oracle(o: u32, ) -> Weight;
+}
+
+/// Weights for pallet_symbol_price using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: SymbolPriceModule Oracles (r:1 w:0)
+	// Storage: SymbolPriceModule Prices (r:1 w:1)
+	// Storage: SymbolPriceModule NextPredictedPrice (r:0 w:1)
+	// Storage: SymbolPriceModule PriceUpdatedAt (r:0 w:1)
+	fn submit_price(s: u32, o: u32, ) -> Weight {
+		(27_000_000 as Weight)
+			.saturating_add((95_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add((60_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: SymbolPriceModule Prices (r:1 w:1)
+	// Storage: SymbolPriceModule NextPredictedPrice (r:0 w:1)
+	// Storage: SymbolPriceModule PriceUpdatedAt (r:0 w:1)
+	// Storage: SymbolPriceModule NextUnsignedAt (r:0 w:1)
+	fn submit_price_unsigned_with_signed_payload(s: u32, ) -> Weight {
+		(26_000_000 as Weight)
+			.saturating_add((95_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: SymbolPriceModule Oracles (r:1 w:1)
+	fn add_oracle(o: u32, ) -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add((60_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: SymbolPriceModule Oracles (r:1 w:1)
+	fn remove_oracle(o: u32, ) -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add((60_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: SymbolPriceModule Oracles (r:1 w:0)
+	// Storage: SymbolPriceModule Prices (r:1 w:1)
+	// Storage: SymbolPriceModule NextPredictedPrice (r:0 w:1)
+	// Storage: SymbolPriceModule PriceUpdatedAt (r:0 w:1)
+	fn submit_price(s: u32, o: u32, ) -> Weight {
+		(27_000_000 as Weight)
+			.saturating_add((95_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add((60_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: SymbolPriceModule Prices (r:1 w:1)
+	// Storage: SymbolPriceModule NextPredictedPrice (r:0 w:1)
+	// Storage: SymbolPriceModule PriceUpdatedAt (r:0 w:1)
+	// Storage: SymbolPriceModule NextUnsignedAt (r:0 w:1)
+	fn submit_price_unsigned_with_signed_payload(s: u32, ) -> Weight {
+		(26_000_000 as Weight)
+			.saturating_add((95_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: SymbolPriceModule Oracles (r:1 w:1)
+	fn add_oracle(o: u32, ) -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add((60_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: SymbolPriceModule Oracles (r:1 w:1)
+	fn remove_oracle(o: u32, ) -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add((60_000 as Weight).saturating_mul(o as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+}
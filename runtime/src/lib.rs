@@ -42,11 +42,13 @@ use frame_support::{
 		ConstU32,
 		// ConstU64, // => In new substrate template version only
 	},
+	BoundedVec,
 };
 use frame_system::{
 	offchain::{
 		AppCrypto,
 	},
+	EnsureRoot,
 };
 
 pub use pallet_balances::Call as BalancesCall;
@@ -63,6 +65,7 @@ pub use pallet_template;
 pub use pallet_bo_trading;
 pub use pallet_bo_liquidity;
 pub use pallet_symbol_price;
+pub use pallet_bo_prediction;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -77,6 +80,12 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of a fungible asset of pallet_assets.
+pub type AssetId = u32;
+
+/// Dev stable coin: USDT, 6 decimals. Orders and liquidity pools are denominated in it.
+pub const USDT: AssetId = 1;
+
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
@@ -119,10 +128,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -282,6 +291,30 @@ parameter_types! {
 	pub OperationalFeeMultiplier: u8 = 5;
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 100_000;
+	pub const ApprovalDeposit: Balance = 500;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: Balance = 10_000;
+	pub const MetadataDepositPerByte: Balance = 1_000;
+}
+
+impl pallet_assets::Config for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
 impl pallet_transaction_payment::Config for Runtime {
 	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
 	type TransactionByteFee = TransactionByteFee;
@@ -295,26 +328,122 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+parameter_types! {
+	pub const SellBackLockPeriod: u64 = 30;
+	pub const SellBackSpread: Permill = Permill::from_percent(5);
+	pub const PriceVolatility: Permill = Permill::from_parts(1_000); // 0.1% per minute
+	pub const CancelWindow: u64 = 10;
+	pub const CancelFee: Permill = Permill::from_percent(1);
+	// 1m, 5m, 15m, 1h
+	pub const MaxTimeframes: u32 = 8;
+	pub DefaultTimeframes: BoundedVec<u64, MaxTimeframes> =
+		BoundedVec::try_from(vec![60, 300, 900, 3600]).expect("at most MaxTimeframes; qed");
+	pub const OracleUpdateInterval: u64 = 2;
+	pub const MinLeadIntervals: u64 = 5;
+	pub const MaxBarrierOrders: u32 = 256;
+	pub const ImbalanceSensitivity: u32 = 10;
+	pub const MinPayoutRate: u32 = 50;
+	pub const MaxPayoutRate: u32 = 200;
+	pub const BoTradingPalletId: PalletId = PalletId(*b"BoTradin");
+	pub const MaxRestingOrders: u32 = 128;
+	// 10%
+	pub const MaxProtocolFeeBps: u32 = 1_000;
+	// daily fee reports
+	pub const FeePeriod: u64 = 86_400;
+	// 1 hour break after a losing streak
+	pub const LossStreakCooldown: u64 = 3_600;
+	// 10% of the order protocol fees
+	pub const KeeperRewardBps: u32 = 1_000;
+	pub const MaxSettleBatch: u32 = 50;
+	pub const MaxOrdersPerBatch: u32 = 20;
+	pub const MaxConditionalOrders: u32 = 256;
+	pub const MaxTriggersPerPrice: u32 = 8;
+	// a few expiries of each timeframe of each pair waiting for the next price
+	pub const MaxPendingExpiries: u32 = 256;
+	// the copies of an order visiting every pool must fit in a normal extrinsic
+	pub const MaxFollowers: u32 = 20;
+	// 20% of the follower profit
+	pub const MaxLeaderFeeBps: u32 = 2_000;
+	// 100.00 units of the pair asset
+	pub const MinCopyBudget: Balance = 10_000;
+	// weekly leaderboards
+	pub const LeaderboardPeriod: u64 = 604_800;
+	pub const LeaderboardSize: u32 = 100;
+	pub const MaxParticipants: u32 = 500;
+	pub const MaxPrizeRanks: u32 = 10;
+	pub const MaxTournamentPairs: u32 = 16;
+	// tournament orders not settled a day after the end can be refunded
+	pub const TournamentGracePeriod: u64 = 86_400;
+	pub const MaxCreditGrants: u32 = 16;
+	pub const MaxReferralTiers: u32 = 3;
+}
+
 impl pallet_bo_trading::Config for Runtime {
 	type Event = Event;
-	type Currency = Balances; // TODO: Ask: What is this?
+	type Assets = Assets;
 	type BoLiquidity = BoLiquidityModule;
 	type SymbolPriceModule = SymbolPriceModule;
 	type MyRandomness = RandomnessCollectiveFlip;
 	type TimeProvider = Timestamp;
 	type UnsignedInterval = ConstU32<0>;
+	type SellBackLockPeriod = SellBackLockPeriod;
+	type SellBackSpread = SellBackSpread;
+	type PriceVolatility = PriceVolatility;
+	type CancelWindow = CancelWindow;
+	type CancelFee = CancelFee;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type DefaultTimeframes = DefaultTimeframes;
+	type OracleUpdateInterval = OracleUpdateInterval;
+	type MinLeadIntervals = MinLeadIntervals;
+	type MaxBarrierOrders = MaxBarrierOrders;
+	type ImbalanceSensitivity = ImbalanceSensitivity;
+	type MinPayoutRate = MinPayoutRate;
+	type MaxPayoutRate = MaxPayoutRate;
+	type PalletId = BoTradingPalletId;
+	type MaxRestingOrders = MaxRestingOrders;
+	type TreasuryPalletId = TreasuryPalletId;
+	type MaxProtocolFeeBps = MaxProtocolFeeBps;
+	type FeePeriod = FeePeriod;
+	type LossStreakCooldown = LossStreakCooldown;
+	type KeeperRewardBps = KeeperRewardBps;
+	type MaxSettleBatch = MaxSettleBatch;
+	type MaxOrdersPerBatch = MaxOrdersPerBatch;
+	type MaxConditionalOrders = MaxConditionalOrders;
+	type MaxFollowers = MaxFollowers;
+	type MaxLeaderFeeBps = MaxLeaderFeeBps;
+	type MinCopyBudget = MinCopyBudget;
+	type LeaderboardPeriod = LeaderboardPeriod;
+	type LeaderboardSize = LeaderboardSize;
+	type MaxParticipants = MaxParticipants;
+	type MaxPrizeRanks = MaxPrizeRanks;
+	type MaxTournamentPairs = MaxTournamentPairs;
+	type TournamentGracePeriod = TournamentGracePeriod;
+	type VoucherSignature = Signature;
+	type VoucherSigner = <Signature as Verify>::Signer;
+	type MaxCreditGrants = MaxCreditGrants;
+	type MaxReferralTiers = MaxReferralTiers;
+	type MaxPools = MaxPools;
+	type MaxTimeframes = MaxTimeframes;
+	type MaxTriggersPerPrice = MaxTriggersPerPrice;
+	type MaxPendingExpiries = MaxPendingExpiries;
+	type WeightInfo = pallet_bo_trading::weights::SubstrateWeight<Runtime>;
 }
 
 
 parameter_types! {
 	pub const BrightOptionId: PalletId = PalletId(*b"BrightOp");
+	pub const MaxPools: u32 = 1_000;
+	pub const MaxCurvePoints: u32 = 32;
 }
 
 impl pallet_bo_liquidity::Config for Runtime {
 	type Event = Event;
-	type Currency = Balances;
+	type Assets = Assets;
 	type MyRandomness = RandomnessCollectiveFlip;
 	type PalletId = BrightOptionId;
+	type MaxPools = MaxPools;
+	type MaxCurvePoints = MaxCurvePoints;
+	type WeightInfo = pallet_bo_liquidity::weights::SubstrateWeight<Runtime>;
 }
 
 parameter_types! {
@@ -329,6 +458,35 @@ impl pallet_symbol_price::Config for Runtime {
 	type UnsignedInterval = ConstU32<0>;
 	type UnsignedPriority = UnsignedPriority;
 	type MaxPrices = ConstU32<32>;
+	type MaxSymbolLength = ConstU32<16>;
+	type MaxOracles = ConstU32<16>;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type OnNewPrice = BoTradingModule;
+	type WeightInfo = pallet_symbol_price::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const BoPredictionPalletId: PalletId = PalletId(*b"BoPredic");
+	pub const TreasuryPalletId: PalletId = PalletId(*b"BoTreasu");
+	// 5 minutes rounds
+	pub const RoundDuration: BlockNumber = 300;
+	pub const ProtocolFee: Permill = Permill::from_percent(3);
+	pub const MaxPriceAge: BlockNumber = 10;
+	pub const MinBet: Balance = 1_000_000_000;
+}
+
+impl pallet_bo_prediction::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type SymbolPriceModule = SymbolPriceModule;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type PalletId = BoPredictionPalletId;
+	type TreasuryPalletId = TreasuryPalletId;
+	type RoundDuration = RoundDuration;
+	type ProtocolFee = ProtocolFee;
+	type MaxPriceAge = MaxPriceAge;
+	type MinBet = MinBet;
+	type WeightInfo = pallet_bo_prediction::weights::SubstrateWeight<Runtime>;
 }
 
 impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
@@ -404,9 +562,11 @@ construct_runtime!(
 		Sudo: pallet_sudo,
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template,
-		BoTradingModule: pallet_bo_trading::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+		BoTradingModule: pallet_bo_trading::{Pallet, Call, Storage, Config<T>, Event<T>, ValidateUnsigned},
 		BoLiquidityModule: pallet_bo_liquidity,
 		SymbolPriceModule: pallet_symbol_price,
+		BoPredictionModule: pallet_bo_prediction,
+		Assets: pallet_assets,
 	}
 );
 
@@ -432,6 +592,19 @@ pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signatu
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 
+parameter_types! {
+	/// The v0 orders and pools were in the native currency, they are accounted in USDT
+	pub const LegacyAssetId: AssetId = USDT;
+}
+
+/// Storage migrations run on runtime upgrade, each one checks the on chain storage version
+/// - the open v0 orders are refunded from the pools before the pools return their native funds
+pub type Migrations = (
+	pallet_symbol_price::migrations::v1::MigrateToV1<Runtime>,
+	pallet_bo_trading::migrations::v1::MigrateToV1<Runtime, LegacyAssetId, Balances>,
+	pallet_bo_liquidity::migrations::v1::MigrateToV1<Runtime, LegacyAssetId, Balances>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -439,6 +612,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPallets,
+	Migrations,
 >;
 
 impl_runtime_apis! {
@@ -572,6 +746,29 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_bo_trading_rpc_runtime_api::BoTradingApi<Block, AccountId, AssetId, Balance, pallet_bo_trading::PairId> for Runtime {
+		fn fees_collected(asset_id: AssetId, from_period: u64, to_period: u64) -> Vec<(u64, Balance)> {
+			BoTradingModule::fees_collected(asset_id, from_period, to_period)
+		}
+
+		fn trading_status(pair_id: pallet_bo_trading::PairId) -> pallet_bo_trading_rpc_runtime_api::TradingStatus {
+			let (new_orders_paused, settlement_paused) = BoTradingModule::trading_status(pair_id);
+			pallet_bo_trading_rpc_runtime_api::TradingStatus { new_orders_paused, settlement_paused }
+		}
+
+		fn account_stats(account_id: AccountId, asset_id: AssetId) -> pallet_bo_trading_rpc_runtime_api::TraderStats<Balance> {
+			BoTradingModule::account_stats(account_id, asset_id)
+		}
+
+		fn pair_stats(pair_id: pallet_bo_trading::PairId) -> pallet_bo_trading_rpc_runtime_api::TraderStats<Balance> {
+			BoTradingModule::pair_stats(pair_id)
+		}
+
+		fn leaderboard(asset_id: AssetId, period: Option<u64>) -> Vec<(AccountId, pallet_bo_trading_rpc_runtime_api::TraderStats<Balance>)> {
+			BoTradingModule::leaderboard(asset_id, period)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -590,6 +787,10 @@ impl_runtime_apis! {
 			list_benchmark!(list, extra, pallet_balances, Balances);
 			list_benchmark!(list, extra, pallet_timestamp, Timestamp);
 			list_benchmark!(list, extra, pallet_template, TemplateModule);
+			list_benchmark!(list, extra, pallet_bo_trading, BoTradingModule);
+			list_benchmark!(list, extra, pallet_bo_liquidity, BoLiquidityModule);
+			list_benchmark!(list, extra, pallet_symbol_price, SymbolPriceModule);
+			list_benchmark!(list, extra, pallet_bo_prediction, BoPredictionModule);
 
 			let storage_info = AllPalletsWithSystem::storage_info();
 
@@ -628,8 +829,88 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
 			add_benchmark!(params, batches, pallet_template, TemplateModule);
+			add_benchmark!(params, batches, pallet_bo_trading, BoTradingModule);
+			add_benchmark!(params, batches, pallet_bo_liquidity, BoLiquidityModule);
+			add_benchmark!(params, batches, pallet_symbol_price, SymbolPriceModule);
+			add_benchmark!(params, batches, pallet_bo_prediction, BoPredictionModule);
 
 			Ok(batches)
 		}
 	}
+
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade() -> (Weight, Weight) {
+			// Runs the migrations with their pre and post upgrade checks
+			let weight = Executive::try_runtime_upgrade().unwrap();
+			(weight, BlockWeights::get().max_block)
+		}
+
+		fn execute_block_no_check(block: Block) -> Weight {
+			Executive::execute_block_no_check(block)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::weights::{DispatchClass, GetDispatchInfo};
+	use pallet_bo_trading::{OrderRequest, TradeType};
+	use sp_core::sr25519;
+
+	/// Every order call must fit in a normal extrinsic for its worst case declared weight
+	#[test]
+	fn declared_weights_fit_in_a_normal_extrinsic() {
+		let max_extrinsic = BlockWeights::get()
+			.get(DispatchClass::Normal)
+			.max_extrinsic
+			.expect("a normal extrinsic is limited; qed");
+		let order = OrderRequest::<Runtime> {
+			pair_id: 0,
+			trade_type: TradeType::Call,
+			volume_in_unit: 1_000,
+			expired_at: 0,
+		};
+		let calls: Vec<Call> = vec![
+			pallet_bo_trading::Call::place_order {
+				pair_id: 0,
+				trade_type: TradeType::Call,
+				volume_in_unit: 1_000,
+				expired_at: 0,
+			}
+			.into(),
+			pallet_bo_trading::Call::place_orders {
+				orders: BoundedVec::try_from(vec![order; MaxOrdersPerBatch::get() as usize])
+					.expect("MaxOrdersPerBatch orders; qed"),
+				best_effort: false,
+			}
+			.into(),
+			pallet_bo_trading::Call::place_bonus_order {
+				pair_id: 0,
+				trade_type: TradeType::Call,
+				volume_in_unit: 1_000,
+				expired_at: 0,
+			}
+			.into(),
+			pallet_symbol_price::Call::submit_price { symbol: b"BTC_USDT".to_vec(), price: 1 }
+				.into(),
+			pallet_symbol_price::Call::submit_price_unsigned_with_signed_payload {
+				price_payload: pallet_symbol_price::PricePayload {
+					block_number: 0,
+					symbol: b"BTC_USDT".to_vec(),
+					price: 1,
+					public: sr25519::Public::from_raw([0; 32]).into(),
+				},
+				signature: sr25519::Signature::from_raw([0; 64]).into(),
+			}
+			.into(),
+		];
+
+		for call in calls {
+			let info = call.get_dispatch_info();
+			assert_eq!(info.class, DispatchClass::Normal);
+			assert!(info.weight <= max_extrinsic, "{:?} weighs {}", call, info.weight);
+		}
+	}
 }
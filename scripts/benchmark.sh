@@ -0,0 +1,30 @@
+#!/usr/bin/env bash
+# This script is meant to be run on Unix/Linux based systems
+# Regenerate the weights of the custom pallets, on the reference hardware
+set -e
+
+echo "*** Building the node with the runtime benchmarks"
+
+cargo build --release -p node-template --features runtime-benchmarks
+
+for pallet in \
+	pallet_bo_trading:BoTrading \
+	pallet_bo_liquidity:BoLiquidity \
+	pallet_symbol_price:SymbolPrice \
+	pallet_bo_prediction:BoPrediction
+do
+	name=${pallet%%:*}
+	dir=${pallet##*:}
+	echo "*** Benchmarking $name"
+
+	./target/release/node-template benchmark \
+		--chain=dev \
+		--execution=wasm \
+		--wasm-execution=compiled \
+		--pallet="$name" \
+		--extrinsic='*' \
+		--steps=50 \
+		--repeat=20 \
+		--output="./pallets/$dir/src/weights.rs" \
+		--template=./.maintain/frame-weight-template.hbs
+done